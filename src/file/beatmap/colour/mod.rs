@@ -1,4 +1,4 @@
-use std::str::FromStr;
+use std::{fmt, io, str::FromStr};
 
 use crate::Error;
 
@@ -37,6 +37,13 @@ impl FromStr for Colour {
     }
 }
 
+impl fmt::Display for Colour {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{},{}", self.0, self.1, self.2)
+    }
+}
+
 #[derive(Debug, Clone, Default)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Colours {
@@ -100,4 +107,17 @@ impl Colours {
         }
         Ok(())
     }
+
+    pub fn write(&self, w: &mut impl io::Write) -> io::Result<()> {
+        for (i, colour) in self.combo_colours() {
+            writeln!(w, "Combo{} : {}", i + 1, colour)?;
+        }
+        if let Some(colour) = self.slider_track_override {
+            writeln!(w, "SliderTrackOverride : {}", colour)?;
+        }
+        if let Some(colour) = self.slider_border {
+            writeln!(w, "SliderBorder : {}", colour)?;
+        }
+        Ok(())
+    }
 }
@@ -0,0 +1,326 @@
+//! Fallbacks osu! applies to malformed or legacy lines, used by
+//! [`Strictness::OsuCompatible`][super::diagnostic::Strictness::OsuCompatible].
+//!
+//! Lines are rewritten into the form the regular parsers accept, so that every fallback lives in
+//! one place instead of being spread across the [`FromStr`][std::str::FromStr] implementations.
+
+use std::borrow::Cow;
+
+/// A value replaced while normalizing a line.
+pub(super) struct Fallback {
+    pub field: &'static str,
+    pub message: String,
+}
+
+/// Rewrites `line` the way osu! would interpret it, pushing a [`Fallback`] for every change.
+pub(super) fn normalize<'a>(
+    section: &str,
+    line: &'a str,
+    fallbacks: &mut Vec<Fallback>,
+) -> Cow<'a, str> {
+    match section {
+        "General" => general(line, fallbacks),
+        "Difficulty" => difficulty(line, fallbacks),
+        "Colours" => colour(line, fallbacks),
+        "Events" => event(line, fallbacks),
+        "TimingPoints" => timing_point(line, fallbacks),
+        "HitObjects" => hit_object(line, fallbacks),
+        _ => Cow::Borrowed(line),
+    }
+}
+
+/// Comma-separated fields of a line being normalized.
+struct Fields<'a, 'f> {
+    line: &'a str,
+    fields: Vec<Cow<'a, str>>,
+    changed: bool,
+    fallbacks: &'f mut Vec<Fallback>,
+}
+
+impl<'a, 'f> Fields<'a, 'f> {
+    fn new(line: &'a str, separator: char, fallbacks: &'f mut Vec<Fallback>) -> Self {
+        Self {
+            line,
+            fields: line
+                .split(separator)
+                .map(str::trim)
+                .map(Cow::Borrowed)
+                .collect(),
+            changed: false,
+            fallbacks,
+        }
+    }
+
+    #[inline]
+    fn get(&self, index: usize) -> Option<&str> {
+        self.fields.get(index).map(|f| f.as_ref())
+    }
+
+    /// Replaces the field at `index` if `f` returns a new value for it.
+    fn fix(
+        &mut self,
+        index: usize,
+        name: &'static str,
+        f: impl FnOnce(&str) -> Option<(String, String)>,
+    ) {
+        let Some(field) = self.fields.get(index) else {
+            return;
+        };
+
+        if let Some((value, message)) = f(field) {
+            self.fields[index] = Cow::Owned(value);
+            self.changed = true;
+            self.fallbacks.push(Fallback {
+                field: name,
+                message,
+            });
+        }
+    }
+
+    /// Appends the default value of every missing field.
+    fn pad(&mut self, defaults: &[(&'static str, &'static str)], first: usize) {
+        for (index, (name, value)) in defaults.iter().enumerate() {
+            if self.fields.len() <= first + index {
+                self.fields.push(Cow::Borrowed(value));
+                self.changed = true;
+                self.fallbacks.push(Fallback {
+                    field: name,
+                    message: format!("missing, using {}", value),
+                });
+            }
+        }
+    }
+
+    fn finish(self, separator: &str) -> Cow<'a, str> {
+        if self.changed {
+            Cow::Owned(self.fields.join(separator))
+        } else {
+            Cow::Borrowed(self.line)
+        }
+    }
+}
+
+fn sample_set(s: &str) -> Option<(String, String)> {
+    match s.parse::<i32>() {
+        Ok(0..=3) => None,
+        _ => Some((
+            "0".to_string(),
+            format!("unknown sample set {}, using the default", s),
+        )),
+    }
+}
+
+fn general<'a>(line: &'a str, fallbacks: &mut Vec<Fallback>) -> Cow<'a, str> {
+    let Some((key, value)) = line.split_once(':') else {
+        return Cow::Borrowed(line);
+    };
+
+    match (key.trim(), value.trim()) {
+        ("SampleSet", value @ ("None" | "All")) => {
+            fallbacks.push(Fallback {
+                field: "SampleSet",
+                message: format!("sample set {} is not selectable, using Normal", value),
+            });
+            Cow::Owned(format!("{}: Normal", key))
+        }
+        _ => Cow::Borrowed(line),
+    }
+}
+
+fn difficulty<'a>(line: &'a str, fallbacks: &mut Vec<Fallback>) -> Cow<'a, str> {
+    let Some((key, value)) = line.split_once(':') else {
+        return Cow::Borrowed(line);
+    };
+
+    let (field, min, max) = match key.trim() {
+        "SliderMultiplier" => ("SliderMultiplier", 0.4, 3.6),
+        "SliderTickRate" => ("SliderTickRate", 0.5, 8.0),
+        _ => return Cow::Borrowed(line),
+    };
+
+    match value.trim().parse::<f32>() {
+        Ok(v) if !(min..=max).contains(&v) => {
+            let clamped = v.clamp(min, max);
+            fallbacks.push(Fallback {
+                field,
+                message: format!("{} clamped to {}", v, clamped),
+            });
+            Cow::Owned(format!("{}:{}", key, clamped))
+        }
+        _ => Cow::Borrowed(line),
+    }
+}
+
+fn colour<'a>(line: &'a str, fallbacks: &mut Vec<Fallback>) -> Cow<'a, str> {
+    let Some((key, value)) = line.split_once(':') else {
+        return Cow::Borrowed(line);
+    };
+
+    let components = value.split(',').map(str::trim).collect::<Vec<_>>();
+    if components.len() != 4 {
+        return Cow::Borrowed(line);
+    }
+
+    fallbacks.push(Fallback {
+        field: "colour",
+        message: "alpha component ignored".to_string(),
+    });
+    Cow::Owned(format!("{}:{}", key, components[..3].join(",")))
+}
+
+fn event<'a>(line: &'a str, fallbacks: &mut Vec<Fallback>) -> Cow<'a, str> {
+    let mut fields = Fields::new(line, ',', fallbacks);
+
+    if matches!(fields.get(0), Some("1" | "Video")) {
+        fields.pad(&[("xOffset", "0"), ("yOffset", "0")], 3);
+    }
+
+    fields.finish(",")
+}
+
+fn timing_point<'a>(line: &'a str, fallbacks: &mut Vec<Fallback>) -> Cow<'a, str> {
+    let mut fields = Fields::new(line, ',', fallbacks);
+
+    // legacy timing points without the uninherited field are inherited if their beat length is
+    // negative
+    let inherited = fields
+        .get(1)
+        .and_then(|s| s.parse::<f32>().ok())
+        .is_some_and(|beat_length| beat_length < 0.0);
+
+    fields.pad(
+        &[
+            ("meter", "4"),
+            ("sampleSet", "0"),
+            ("sampleIndex", "0"),
+            ("volume", "100"),
+            ("uninherited", if inherited { "0" } else { "1" }),
+            ("effects", "0"),
+        ],
+        2,
+    );
+    fields.fix(2, "meter", |s| match s.parse::<i32>() {
+        Ok(meter) if meter >= 1 => None,
+        _ => Some(("4".to_string(), format!("invalid meter {}, using 4", s))),
+    });
+    fields.fix(3, "sampleSet", sample_set);
+
+    fields.finish(",")
+}
+
+fn hit_object<'a>(line: &'a str, fallbacks: &mut Vec<Fallback>) -> Cow<'a, str> {
+    const SLIDER: u8 = 1 << 1;
+    const SPINNER: u8 = 1 << 3;
+    const MANIA_HOLD_NOTE: u8 = 1 << 7;
+
+    let mut fields = Fields::new(line, ',', fallbacks);
+
+    let ty = fields
+        .get(3)
+        .and_then(|s| s.parse::<u8>().ok())
+        .unwrap_or(0);
+
+    let hit_sample_index = if ty & SLIDER != 0 {
+        fields.fix(5, "curve", |s| match s.split_once('|') {
+            Some(("B" | "C" | "L" | "P", _)) | None => None,
+            Some((ty, points)) => Some((
+                format!("C|{}", points),
+                format!("unknown curve type {}, using Catmull", ty),
+            )),
+        });
+        fields.fix(6, "slides", |s| match s.parse::<i64>() {
+            Ok(slides) if slides < 1 => Some(("1".to_string(), format!("{} slides, using 1", s))),
+            _ => None,
+        });
+        fields.fix(7, "length", |s| match s.parse::<f32>() {
+            Ok(length) if length < 0.0 => {
+                Some(("0".to_string(), format!("negative length {}, using 0", s)))
+            }
+            _ => None,
+        });
+        fields.fix(8, "edgeSounds", |s| {
+            let sounds = s
+                .split('|')
+                .map(|s| if s.parse::<u8>().is_ok() { s } else { "0" })
+                .collect::<Vec<_>>()
+                .join("|");
+            (sounds != s).then(|| (sounds, "invalid edge sounds replaced by 0".to_string()))
+        });
+        fields.fix(9, "edgeSets", |s| {
+            let sets = s
+                .split('|')
+                .map(|set| match set.split_once(':') {
+                    Some((normal, addition))
+                        if sample_set(normal).is_none() && sample_set(addition).is_none() =>
+                    {
+                        set
+                    }
+                    _ => "0:0",
+                })
+                .collect::<Vec<_>>()
+                .join("|");
+            (sets != s).then(|| (sets, "invalid edge sets replaced by 0:0".to_string()))
+        });
+        10
+    } else if ty & SPINNER != 0 {
+        let start = fields.get(2).and_then(|s| s.parse::<f64>().ok());
+        fields.fix(5, "endTime", |s| match (start, s.parse::<f64>()) {
+            (Some(start), Ok(end)) if end < start => Some((
+                start.to_string(),
+                format!("end time {} before start time, using {}", end, start),
+            )),
+            _ => None,
+        });
+        6
+    } else if ty & MANIA_HOLD_NOTE != 0 {
+        // the end time and the hit sample share a field
+        let start = fields.get(2).and_then(|s| s.parse::<f64>().ok());
+        fields.fix(5, "endTime", |s| {
+            let (end, rest) = s.split_once(':').unwrap_or((s, ""));
+            match (start, end.parse::<f64>()) {
+                (Some(start), Ok(end)) if end < start => Some((
+                    format!("{}:{}", start, rest),
+                    format!("end time {} before start time, using {}", end, start),
+                )),
+                _ => None,
+            }
+        });
+        fields.fix(5, "hitSample", |s| {
+            let (end, sample) = s.split_once(':')?;
+            hit_sample(sample).map(|(sample, message)| (format!("{}:{}", end, sample), message))
+        });
+
+        return fields.finish(",");
+    } else {
+        5
+    };
+
+    fields.fix(hit_sample_index, "hitSample", hit_sample);
+
+    fields.finish(",")
+}
+
+fn hit_sample(s: &str) -> Option<(String, String)> {
+    let mut parts = s.split(':').map(str::to_string).collect::<Vec<_>>();
+    let mut messages = Vec::new();
+
+    for part in parts.iter_mut().take(2) {
+        if let Some((set, message)) = sample_set(part) {
+            *part = set;
+            messages.push(message);
+        }
+    }
+    if let Some(volume) = parts.get_mut(3) {
+        if volume.parse::<i32>().is_ok_and(|v| v < 0) {
+            *volume = "0".to_string();
+            messages.push("negative volume, using 0".to_string());
+        }
+    }
+    if parts.len() < 5 {
+        parts.resize(5, "0".to_string());
+        parts[4].clear();
+        messages.push("missing fields, using defaults".to_string());
+    }
+
+    (!messages.is_empty()).then(|| (parts.join(":"), messages.join(", ")))
+}
@@ -0,0 +1,106 @@
+use std::fmt;
+
+use crate::Error;
+
+/// How [`BeatmapDecoder`][super::BeatmapDecoder] reacts to lines it cannot parse.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Strictness {
+    /// Fail on the first line that cannot be parsed.
+    #[default]
+    Strict,
+    /// Skip lines that cannot be parsed, recording a [`Diagnostic`] for each of them.
+    Lenient,
+    /// Apply the same fallbacks osu! does for malformed or legacy values (missing timing point
+    /// fields, unknown curve types, short hit samples, ...), recording a [`Diagnostic`] for each of
+    /// them, and skip the lines that still cannot be parsed like [`Lenient`][Strictness::Lenient].
+    OsuCompatible,
+}
+
+/// What the decoder did about a problem it reported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Resolution {
+    /// The whole line was ignored.
+    Skipped,
+    /// A value was replaced by the one osu! would have used.
+    Fallback,
+}
+
+/// A problem found while decoding with [`Strictness::Lenient`] or [`Strictness::OsuCompatible`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Diagnostic {
+    /// 1-based line number, or [`None`] for problems that are not tied to a single line.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub line: Option<usize>,
+    /// 1-based byte column of the field within the line.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub column: Option<usize>,
+    /// Name of the section, without square brackets.
+    pub section: String,
+    /// Raw text of the line.
+    pub text: String,
+    /// Name of the field, either a key like `AudioLeadIn` or a positional field like `time`.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub field: Option<String>,
+    /// Description of the problem.
+    pub message: String,
+    pub resolution: Resolution,
+}
+
+impl Diagnostic {
+    /// Creates a [`Skipped`][Resolution::Skipped] diagnostic from a parse error.
+    pub(crate) fn skipped(e: &Error) -> Self {
+        let (line, column, section, text, field) = match e.parse_error() {
+            Some(e) => (
+                e.line,
+                e.column,
+                e.section.clone().unwrap_or_default(),
+                e.text.clone().unwrap_or_default(),
+                e.field.clone(),
+            ),
+            None => (None, None, String::new(), String::new(), None),
+        };
+
+        Self {
+            line,
+            column,
+            section,
+            text,
+            field,
+            message: e.cause().to_string(),
+            resolution: Resolution::Skipped,
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(line) = self.line {
+            write!(f, "line {}", line)?;
+            if let Some(column) = self.column {
+                write!(f, ":{}", column)?;
+            }
+            f.write_str(" ")?;
+        }
+        if !self.section.is_empty() {
+            write!(f, "in [{}] ", self.section)?;
+        }
+        if let Some(field) = &self.field {
+            write!(f, "field `{}` ", field)?;
+        }
+
+        let resolution = match self.resolution {
+            Resolution::Skipped => "skipped",
+            Resolution::Fallback => "fallback",
+        };
+        write!(f, "({}): {}", resolution, self.message)?;
+
+        if !self.text.is_empty() {
+            write!(f, " (`{}`)", self.text)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,161 @@
+use crate::{mode::Mode, mods::Mods, time::Time};
+
+use super::{effective::EffectiveDifficulty, Difficulty};
+
+/// Maps a difficulty setting to a value, linearly between `min` at 0, `mid` at 5 and `max` at 10.
+#[inline]
+pub fn difficulty_range(value: f64, min: f64, mid: f64, max: f64) -> f64 {
+    if value > 5.0 {
+        mid + (max - mid) * (value - 5.0) / 5.0
+    } else if value < 5.0 {
+        mid - (mid - min) * (5.0 - value) / 5.0
+    } else {
+        mid
+    }
+}
+
+/// Largest distance from the hit time at which each judgement is given.
+///
+/// Judgements that do not exist in a mode are [`None`]: only osu!mania has
+/// [`perfect`][HitWindows::perfect] and [`good`][HitWindows::good], and osu!taiko has no
+/// [`meh`][HitWindows::meh].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HitWindows {
+    /// MAX (320) in osu!mania
+    pub perfect: Option<Time>,
+    /// 300, or GREAT in osu!taiko
+    pub great: Time,
+    /// 200 in osu!mania
+    pub good: Option<Time>,
+    /// 100, or OK in osu!taiko
+    pub ok: Time,
+    /// 50
+    pub meh: Option<Time>,
+    /// Hits further than this are ignored instead of counting as a miss
+    pub miss: Time,
+}
+
+impl HitWindows {
+    /// Returns the hit windows of `mode` for an OD setting, or [`None`] for osu!catch, which has no
+    /// hit windows.
+    pub fn new(mode: Mode, od: f32) -> Option<Self> {
+        let od = od as f64;
+
+        match mode {
+            Mode::Standard => Some(Self {
+                perfect: None,
+                great: Time(difficulty_range(od, 80.0, 50.0, 20.0)),
+                good: None,
+                ok: Time(difficulty_range(od, 140.0, 100.0, 60.0)),
+                meh: Some(Time(difficulty_range(od, 200.0, 150.0, 100.0))),
+                miss: Time(400.0),
+            }),
+            Mode::Taiko => Some(Self {
+                perfect: None,
+                great: Time(difficulty_range(od, 50.0, 35.0, 20.0)),
+                good: None,
+                ok: Time(difficulty_range(od, 120.0, 80.0, 50.0)),
+                meh: None,
+                miss: Time(difficulty_range(od, 135.0, 95.0, 70.0)),
+            }),
+            Mode::Catch => None,
+            Mode::Mania => Some(Self {
+                perfect: Some(Time(16.0)),
+                great: Time(64.0 - 3.0 * od),
+                good: Some(Time(97.0 - 3.0 * od)),
+                ok: Time(127.0 - 3.0 * od),
+                meh: Some(Time(151.0 - 3.0 * od)),
+                miss: Time(188.0 - 3.0 * od),
+            }),
+        }
+    }
+
+    /// Multiplies every window by `factor`.
+    pub fn scale(&self, factor: f64) -> Self {
+        Self {
+            perfect: self.perfect.map(|w| w * factor),
+            great: self.great * factor,
+            good: self.good.map(|w| w * factor),
+            ok: self.ok * factor,
+            meh: self.meh.map(|w| w * factor),
+            miss: self.miss * factor,
+        }
+    }
+}
+
+/// Gameplay constants derived from [`Difficulty`] for a game mode.
+///
+/// Values that do not apply to a mode are [`None`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DifficultyAttributes {
+    pub mode: Mode,
+    /// Radius of circles and fruits in osu! pixels
+    pub radius: Option<f64>,
+    /// Time an object appears before its hit time
+    pub preempt: Option<Time>,
+    /// Time an object takes to fade in once it appears
+    pub fade_in: Option<Time>,
+    pub hit_windows: Option<HitWindows>,
+    /// Spinner rotations per second required to clear a spinner
+    pub spins_per_second: Option<f64>,
+    /// Lowest health the HP drain should bring a player to over the beatmap, between 0 and 1
+    pub target_minimum_health: f64,
+}
+
+impl DifficultyAttributes {
+    /// Returns the number of full rotations required to clear a spinner lasting `duration`.
+    #[inline]
+    pub fn required_spins(&self, duration: Time) -> Option<u32> {
+        self.spins_per_second
+            .map(|sps| (duration.as_secs().max(0.0) * sps) as u32)
+    }
+}
+
+impl Difficulty {
+    /// Returns the gameplay constants of the beatmap played in `mode`, in beatmap time.
+    pub fn attributes(&self, mode: Mode) -> DifficultyAttributes {
+        let has_approach = matches!(mode, Mode::Standard | Mode::Catch);
+        let ar = self.ar as f64;
+
+        DifficultyAttributes {
+            mode,
+            radius: has_approach.then_some(54.4 - 4.48 * self.cs as f64),
+            preempt: has_approach.then(|| Time(difficulty_range(ar, 1800.0, 1200.0, 450.0))),
+            fade_in: has_approach.then(|| Time(difficulty_range(ar, 1200.0, 800.0, 300.0))),
+            hit_windows: HitWindows::new(mode, self.od),
+            spins_per_second: (mode == Mode::Standard)
+                .then(|| difficulty_range(self.od as f64, 1.5, 2.5, 3.75)),
+            target_minimum_health: difficulty_range(self.hp as f64, 0.99, 0.9, 0.4),
+        }
+    }
+}
+
+impl EffectiveDifficulty {
+    /// Returns the gameplay constants of the beatmap played with the mods, in real time.
+    ///
+    /// Times are divided by the clock rate, except for the osu!mania hit windows, which are scaled
+    /// by [`HARD_ROCK`][Mods::HARD_ROCK] and [`EASY`][Mods::EASY] instead.
+    pub fn attributes(&self) -> DifficultyAttributes {
+        let mut attributes = self.base.attributes(self.mode);
+        let rate = self.clock_rate;
+
+        attributes.preempt = attributes.preempt.map(|t| t / rate);
+        attributes.fade_in = attributes.fade_in.map(|t| t / rate);
+        attributes.spins_per_second = attributes.spins_per_second.map(|sps| sps * rate);
+        attributes.hit_windows = attributes.hit_windows.map(|windows| {
+            if self.mode != Mode::Mania {
+                windows.scale(1.0 / rate)
+            } else if self.mods.contains(Mods::HARD_ROCK) {
+                windows.scale(1.0 / 1.4)
+            } else if self.mods.contains(Mods::EASY) {
+                windows.scale(1.4)
+            } else {
+                windows
+            }
+        });
+
+        attributes
+    }
+}
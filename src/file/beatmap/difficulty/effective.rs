@@ -0,0 +1,108 @@
+use crate::{mode::Mode, mods::Mods};
+
+use super::{attributes::difficulty_range, Difficulty};
+
+/// Difficulty settings of a beatmap once mods are applied, see [`Difficulty::with_mods`].
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EffectiveDifficulty {
+    pub mode: Mode,
+    pub mods: Mods,
+    /// Settings with the [`HARD_ROCK`][Mods::HARD_ROCK] and [`EASY`][Mods::EASY] multipliers
+    /// applied, in beatmap time.
+    pub base: Difficulty,
+    /// Playback rate of the beatmap.
+    pub clock_rate: f64,
+    /// Effective HP setting
+    pub hp: f32,
+    /// Effective CS setting
+    pub cs: f32,
+    /// Effective OD setting, above 10 when the hit windows are shorter than at OD 10
+    pub od: f32,
+    /// Effective AR setting, above 10 when the preempt is shorter than at AR 10
+    pub ar: f32,
+}
+
+impl Difficulty {
+    /// Returns the settings with the [`HARD_ROCK`][Mods::HARD_ROCK] and [`EASY`][Mods::EASY]
+    /// multipliers applied the way `mode` does.
+    ///
+    /// Hard Rock multiplies CS by 1.3 and the other settings by 1.4, capped to 10, and Easy halves
+    /// every setting. osu!mania keeps CS, which is its key count, and OD, since the mods scale its
+    /// hit windows instead.
+    pub fn apply_mods(&self, mode: Mode, mods: Mods) -> Self {
+        let mut difficulty = *self;
+        let keeps_cs = mode == Mode::Mania || mode == Mode::Taiko;
+        let keeps_od = mode == Mode::Mania;
+
+        if mods.contains(Mods::HARD_ROCK) {
+            if !keeps_cs {
+                difficulty.cs = (difficulty.cs * 1.3).min(10.0);
+            }
+            if !keeps_od {
+                difficulty.od = (difficulty.od * 1.4).min(10.0);
+            }
+            difficulty.ar = (difficulty.ar * 1.4).min(10.0);
+            difficulty.hp = (difficulty.hp * 1.4).min(10.0);
+        }
+
+        if mods.contains(Mods::EASY) {
+            if !keeps_cs {
+                difficulty.cs *= 0.5;
+            }
+            if !keeps_od {
+                difficulty.od *= 0.5;
+            }
+            difficulty.ar *= 0.5;
+            difficulty.hp *= 0.5;
+        }
+
+        difficulty
+    }
+
+    /// Returns the effective settings of the beatmap played in `mode` with `mods`.
+    ///
+    /// The playback rate shortens or lengthens the approach preempt and the hit windows, which are
+    /// turned back into the AR and OD that would give the same times at the normal rate. osu!mania
+    /// hit windows are not scaled by the playback rate, so its OD is left as is.
+    pub fn with_mods(&self, mode: Mode, mods: Mods) -> EffectiveDifficulty {
+        let base = self.apply_mods(mode, mods);
+        let clock_rate = mods.clock_rate();
+
+        let ar = preempt_to_ar(difficulty_range(base.ar as f64, 1800.0, 1200.0, 450.0) / clock_rate)
+            as f32;
+        let od = match mode {
+            Mode::Standard | Mode::Catch => {
+                let great = (80.0 - 6.0 * base.od as f64) / clock_rate;
+                ((80.0 - great) / 6.0) as f32
+            }
+            Mode::Taiko => {
+                let great = (50.0 - 3.0 * base.od as f64) / clock_rate;
+                ((50.0 - great) / 3.0) as f32
+            }
+            Mode::Mania => base.od,
+        };
+
+        EffectiveDifficulty {
+            mode,
+            mods,
+            base,
+            clock_rate,
+            hp: base.hp,
+            cs: base.cs,
+            od,
+            ar,
+        }
+    }
+}
+
+/// Returns the AR setting giving `preempt`, the inverse of the mapping in
+/// [`Difficulty::attributes`].
+#[inline]
+fn preempt_to_ar(preempt: f64) -> f64 {
+    if preempt > 1200.0 {
+        5.0 - (preempt - 1200.0) / 120.0
+    } else {
+        5.0 + (1200.0 - preempt) / 150.0
+    }
+}
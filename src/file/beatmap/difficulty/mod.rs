@@ -1,3 +1,8 @@
+pub mod attributes;
+pub mod effective;
+
+use std::io;
+
 use crate::Error;
 
 #[derive(Debug, Clone, Copy)]
@@ -32,6 +37,12 @@ impl Default for Difficulty {
 }
 
 impl Difficulty {
+    /// Returns the number of keys of an osu!mania beatmap, which is stored as the circle size.
+    #[inline]
+    pub fn key_count(&self) -> u32 {
+        self.cs.round().clamp(1.0, 18.0) as u32
+    }
+
     pub fn parse(&mut self, s: &str) -> Result<(), Error> {
         if let Some((key, value)) = s.split_once(':') {
             let key = key.trim();
@@ -50,4 +61,14 @@ impl Difficulty {
 
         Ok(())
     }
+
+    pub fn write(&self, w: &mut impl io::Write) -> io::Result<()> {
+        writeln!(w, "HPDrainRate:{}", self.hp)?;
+        writeln!(w, "CircleSize:{}", self.cs)?;
+        writeln!(w, "OverallDifficulty:{}", self.od)?;
+        writeln!(w, "ApproachRate:{}", self.ar)?;
+        writeln!(w, "SliderMultiplier:{}", self.slider_multiplier)?;
+        writeln!(w, "SliderTickRate:{}", self.slider_tick_rate)?;
+        Ok(())
+    }
 }
@@ -0,0 +1,318 @@
+use std::{
+    fmt,
+    fs::File,
+    io::{self, Read, Write},
+    path::Path,
+};
+
+use crate::Error;
+
+/// Line terminator of a single [`Line`], kept so that files are re-emitted byte for byte.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LineEnding {
+    /// The last line of a file that does not end with a newline.
+    None,
+    /// `\n`
+    #[default]
+    Lf,
+    /// `\r\n`
+    CrLf,
+}
+
+impl LineEnding {
+    #[inline]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            LineEnding::None => "",
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n",
+        }
+    }
+}
+
+/// A single line of a `.osu` file, stored exactly as it appeared in the source.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Line {
+    /// Text of the line, without its line terminator.
+    pub text: String,
+    pub ending: LineEnding,
+}
+
+impl Line {
+    #[inline]
+    pub fn new(text: impl Into<String>, ending: LineEnding) -> Self {
+        Self {
+            text: text.into(),
+            ending,
+        }
+    }
+
+    #[inline]
+    pub fn is_blank(&self) -> bool {
+        self.text.trim().is_empty()
+    }
+
+    #[inline]
+    pub fn is_comment(&self) -> bool {
+        self.text.starts_with("//")
+    }
+
+    /// Returns the trimmed key of a `Key: Value` line, or [`None`] for blank lines and comments.
+    #[inline]
+    pub fn key(&self) -> Option<&str> {
+        self.key_value().map(|(key, _)| key)
+    }
+
+    /// Returns the trimmed value of a `Key: Value` line, or [`None`] for blank lines and comments.
+    #[inline]
+    pub fn value(&self) -> Option<&str> {
+        self.key_value().map(|(_, value)| value)
+    }
+
+    #[inline]
+    pub fn key_value(&self) -> Option<(&str, &str)> {
+        if self.is_comment() {
+            return None;
+        }
+
+        self.text
+            .split_once(':')
+            .map(|(key, value)| (key.trim(), value.trim()))
+    }
+
+    /// Replaces the value of a `Key: Value` line, keeping the key and the separator (`:`, `: `,
+    /// ` : `, ...) exactly as they were written.
+    ///
+    /// Returns `false` and leaves the line untouched if it is not a `Key: Value` line.
+    pub fn set_value(&mut self, value: impl fmt::Display) -> bool {
+        if self.is_comment() {
+            return false;
+        }
+
+        let Some(colon) = self.text.find(':') else {
+            return false;
+        };
+
+        let rest = &self.text[colon + 1..];
+        let prefix_len = colon + 1 + (rest.len() - rest.trim_start().len());
+
+        self.text.truncate(prefix_len);
+        self.text.push_str(&value.to_string());
+
+        true
+    }
+}
+
+/// A `[Section]` header followed by every line up to the next section.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Section {
+    pub header: Line,
+    /// Lines of the section, including blank lines and comments.
+    pub lines: Vec<Line>,
+}
+
+impl Section {
+    /// Creates a new empty section, with a header like `[name]`.
+    #[inline]
+    pub fn new(name: &str) -> Self {
+        Self {
+            header: Line::new(format!("[{}]", name), LineEnding::Lf),
+            lines: Vec::new(),
+        }
+    }
+
+    /// Returns the name of the section, without the square brackets.
+    #[inline]
+    pub fn name(&self) -> &str {
+        let header = self.header.text.trim();
+        header
+            .strip_prefix('[')
+            .and_then(|h| h.strip_suffix(']'))
+            .unwrap_or(header)
+    }
+
+    /// Iterates over the lines that carry data, skipping blank lines and comments.
+    #[inline]
+    pub fn entries(&self) -> impl Iterator<Item = &Line> {
+        self.lines
+            .iter()
+            .filter(|line| !line.is_blank() && !line.is_comment())
+    }
+
+    /// Returns the value of the first `Key: Value` line with a matching key.
+    #[inline]
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.lines
+            .iter()
+            .filter_map(Line::key_value)
+            .find(|(k, _)| *k == key)
+            .map(|(_, value)| value)
+    }
+
+    /// Sets the value of `key`, rewriting only the line that holds it.
+    ///
+    /// If the key is not present, a new `Key: Value` line is inserted after the last non-blank
+    /// line of the section, using the line ending of its neighbours.
+    pub fn set(&mut self, key: &str, value: impl fmt::Display) {
+        if let Some(line) = self.lines.iter_mut().find(|l| l.key() == Some(key)) {
+            line.set_value(value);
+            return;
+        }
+
+        let index = self
+            .lines
+            .iter()
+            .rposition(|line| !line.is_blank())
+            .map_or(0, |i| i + 1);
+
+        let ending = std::iter::once(&self.header)
+            .chain(&self.lines[..index])
+            .map(|line| line.ending)
+            .rfind(|ending| *ending != LineEnding::None)
+            .unwrap_or_default();
+
+        // the previous line may have been the last line of the file
+        match index.checked_sub(1) {
+            Some(i) => self.lines[i].ending = ending,
+            None => self.header.ending = ending,
+        }
+
+        self.lines
+            .insert(index, Line::new(format!("{}: {}", key, value), ending));
+    }
+
+    /// Removes every `Key: Value` line with a matching key, returning whether any was removed.
+    pub fn remove(&mut self, key: &str) -> bool {
+        let len = self.lines.len();
+        self.lines.retain(|line| line.key() != Some(key));
+        self.lines.len() != len
+    }
+}
+
+/// Lossless representation of a `.osu` file as a list of sections and raw lines.
+///
+/// Unlike decoding straight into a [`Visitor`][super::Visitor], a [`Document`] keeps unknown
+/// keys, unknown sections, comments, blank lines and line endings, so that it can be edited and
+/// written back out byte-identical everywhere except for the lines that were changed.
+///
+/// A document can still be decoded into a visitor with
+/// [`BeatmapDecoder::parse_document`][super::BeatmapDecoder::parse_document].
+///
+/// Only UTF-8 files can be represented, like with [`BeatmapDecoder`][super::BeatmapDecoder];
+/// other encodings are rejected rather than re-encoded, so that a document never writes back
+/// bytes different from the ones it read.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Document {
+    /// Lines before the first section, usually the `osu file format vN` line and a blank line.
+    pub preamble: Vec<Line>,
+    pub sections: Vec<Section>,
+}
+
+impl Document {
+    #[inline]
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, Error> {
+        Self::parse(File::open(path)?)
+    }
+
+    /// Splits a `.osu` file into sections and lines.
+    ///
+    /// This function will return [`Err(Error::Io)`][Error::Io] if `data` is not valid UTF-8.
+    pub fn parse(mut data: impl Read) -> Result<Self, Error> {
+        let mut text = String::new();
+        data.read_to_string(&mut text)?;
+
+        let mut document = Document::default();
+
+        for line in split_lines(&text) {
+            let trimmed = line.text.trim();
+
+            if trimmed.starts_with('[') && trimmed.ends_with(']') {
+                document.sections.push(Section {
+                    header: line,
+                    lines: Vec::new(),
+                });
+                continue;
+            }
+
+            match document.sections.last_mut() {
+                Some(section) => section.lines.push(line),
+                None => document.preamble.push(line),
+            }
+        }
+
+        Ok(document)
+    }
+
+    #[inline]
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), Error> {
+        self.write_to(File::create(path)?)
+    }
+
+    pub fn write_to(&self, data: impl Write) -> Result<(), Error> {
+        let w = &mut io::BufWriter::new(data);
+
+        for line in self.lines() {
+            w.write_all(line.text.as_bytes())?;
+            w.write_all(line.ending.as_str().as_bytes())?;
+        }
+
+        w.flush()?;
+
+        Ok(())
+    }
+
+    /// Iterates over every line of the document in order, including section headers.
+    pub fn lines(&self) -> impl Iterator<Item = &Line> {
+        self.preamble.iter().chain(
+            self.sections
+                .iter()
+                .flat_map(|section| std::iter::once(&section.header).chain(&section.lines)),
+        )
+    }
+
+    #[inline]
+    pub fn section(&self, name: &str) -> Option<&Section> {
+        self.sections.iter().find(|section| section.name() == name)
+    }
+
+    #[inline]
+    pub fn section_mut(&mut self, name: &str) -> Option<&mut Section> {
+        self.sections
+            .iter_mut()
+            .find(|section| section.name() == name)
+    }
+}
+
+impl fmt::Display for Document {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for line in self.lines() {
+            f.write_str(&line.text)?;
+            f.write_str(line.ending.as_str())?;
+        }
+        Ok(())
+    }
+}
+
+fn split_lines(text: &str) -> impl Iterator<Item = Line> + '_ {
+    let mut rest = text;
+
+    std::iter::from_fn(move || {
+        if rest.is_empty() {
+            return None;
+        }
+
+        let (line, ending, len) = match rest.find('\n') {
+            Some(i) if rest[..i].ends_with('\r') => (&rest[..i - 1], LineEnding::CrLf, i + 1),
+            Some(i) => (&rest[..i], LineEnding::Lf, i + 1),
+            None => (rest, LineEnding::None, rest.len()),
+        };
+
+        rest = &rest[len..];
+
+        Some(Line::new(line, ending))
+    })
+}
@@ -1,12 +1,12 @@
-use std::time::Duration;
+use std::io;
 
-use crate::{iter::OkMapExt, Error};
+use crate::{time::Time, Error};
 
 #[derive(Debug, Clone, Default)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Editor {
     /// Time in milliseconds of bookmarks
-    pub bookmarks: Vec<Duration>,
+    pub bookmarks: Vec<Time>,
     /// Distance snap multiplier
     pub distance_spacing: f32,
     /// Beat snap divisor
@@ -29,7 +29,6 @@ impl Editor {
                         .split(',')
                         .map(str::trim)
                         .map(str::parse)
-                        .ok_map(Duration::from_millis)
                         .collect::<Result<_, _>>()?;
                 }
                 "DistanceSpacing" => self.distance_spacing = value.parse()?,
@@ -41,4 +40,20 @@ impl Editor {
         }
         Ok(())
     }
+
+    pub fn write(&self, w: &mut impl io::Write) -> io::Result<()> {
+        if !self.bookmarks.is_empty() {
+            let bookmarks = self
+                .bookmarks
+                .iter()
+                .map(Time::to_string)
+                .collect::<Vec<_>>();
+            writeln!(w, "Bookmarks: {}", bookmarks.join(","))?;
+        }
+        writeln!(w, "DistanceSpacing: {}", self.distance_spacing)?;
+        writeln!(w, "BeatDivisor: {}", self.beat_divisor)?;
+        writeln!(w, "GridSize: {}", self.grid_size)?;
+        writeln!(w, "TimelineZoom: {}", self.timeline_zoom)?;
+        Ok(())
+    }
 }
@@ -1,8 +1,8 @@
-use std::{path::PathBuf, str::FromStr};
+use std::{fmt, path::PathBuf, str::FromStr};
 
 use nalgebra_glm::IVec2;
 
-use crate::Error;
+use crate::{iter::Fields, Error};
 
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -19,28 +19,43 @@ impl FromStr for Background {
     type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut parts = s.split(',').map(str::trim);
+        let mut parts = Fields::new(s, ',');
 
-        let _ = parts.next().ok_or(Error::InvalidData(
-            "expected type while parsing background event",
-        ))?; // ignore the type
+        let _ = parts.field("eventType", "expected type while parsing background event")?; // ignore the type
 
-        let _ = parts.next().ok_or(Error::InvalidData(
+        let _ = parts.field(
+            "startTime",
             "expected start time while parsing background event",
-        ))?;
+        )?;
 
-        let filename = parts.next().ok_or(Error::InvalidData(
+        let filename = parts.field(
+            "filename",
             "expected filename while parsing background event",
-        ))?;
-        let filename = PathBuf::from(filename);
-
-        let x = parts.next().map(str::parse);
-        let y = parts.next().map(str::parse);
-        let offset = IVec2::new(
-            if let Some(x) = x { x? } else { 0 },
-            if let Some(y) = y { y? } else { 0 },
-        );
+        )?;
+        let filename = PathBuf::from(filename.trim_matches('"'));
+
+        let x = match parts.next() {
+            Some(x) => x.parse().map_err(|e| parts.error("xOffset", e))?,
+            None => 0,
+        };
+        let y = match parts.next() {
+            Some(y) => y.parse().map_err(|e| parts.error("yOffset", e))?,
+            None => 0,
+        };
+        let offset = IVec2::new(x, y);
 
         Ok(Self { offset, filename })
     }
 }
+
+impl fmt::Display for Background {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "0,0,\"{}\",{},{}",
+            self.filename.display(),
+            self.offset.x,
+            self.offset.y
+        )
+    }
+}
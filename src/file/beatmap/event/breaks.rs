@@ -1,38 +1,26 @@
-use std::{str::FromStr, time::Duration};
+use std::{fmt, str::FromStr};
 
-use crate::Error;
+use crate::{iter::Fields, time::Time, Error};
 
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Break {
     /// Start time of the break, in milliseconds from the beginning of the beatmap's audio.
-    pub start_time: Duration,
+    pub start_time: Time,
     /// End time of the break, in milliseconds from the beginning of the beatmap's audio.
-    pub end_time: Duration,
+    pub end_time: Time,
 }
 
 impl FromStr for Break {
     type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut parts = s.split(',').map(str::trim);
-        let _ = parts.next().ok_or(Error::InvalidData(
-            "expected type while parsing break event",
-        ))?; // ignore the type
-        let start_time = parts
-            .next()
-            .ok_or(Error::InvalidData(
-                "expected start time while parsing break event",
-            ))?
-            .parse()
-            .map(Duration::from_millis)?;
-        let end_time = parts
-            .next()
-            .ok_or(Error::InvalidData(
-                "expected end time while parsing break event",
-            ))?
-            .parse()
-            .map(Duration::from_millis)?;
+        let mut parts = Fields::new(s, ',');
+        let _ = parts.field("eventType", "expected type while parsing break event")?; // ignore the type
+        let start_time: Time =
+            parts.parse_field("startTime", "expected start time while parsing break event")?;
+        let end_time: Time =
+            parts.parse_field("endTime", "expected end time while parsing break event")?;
 
         Ok(Self {
             start_time,
@@ -40,3 +28,10 @@ impl FromStr for Break {
         })
     }
 }
+
+impl fmt::Display for Break {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "2,{},{}", self.start_time, self.end_time)
+    }
+}
@@ -4,7 +4,7 @@ use background::Background;
 use breaks::Break;
 use video::Video;
 
-use crate::Error;
+use crate::{iter::Fields, Error};
 
 pub mod background;
 pub mod breaks;
@@ -29,6 +29,17 @@ impl fmt::Debug for EventKind {
     }
 }
 
+impl fmt::Display for EventKind {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EventKind::Background(b) => b.fmt(f),
+            EventKind::Video(v) => v.fmt(f),
+            EventKind::Break(b) => b.fmt(f),
+        }
+    }
+}
+
 impl EventKind {
     #[inline]
     pub const fn is_background(&self) -> bool {
@@ -50,16 +61,14 @@ impl FromStr for EventKind {
     type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut parts = s.split(',').map(str::trim);
-        let ty = parts
-            .next()
-            .ok_or(Error::InvalidData("expected event type"))?;
+        let mut parts = Fields::new(s, ',');
+        let ty = parts.field("eventType", "expected event type")?;
 
         match ty {
             "0" => Ok(EventKind::Background(Background::from_str(s)?)),
             "1" | "Video" => Ok(EventKind::Video(Video::from_str(s)?)),
             "2" | "Break" => Ok(EventKind::Break(Break::from_str(s)?)),
-            _ => Err(Error::InvalidData("invalid event type")),
+            _ => Err(parts.error("eventType", Error::InvalidData("invalid event type"))),
         }
     }
 }
@@ -1,8 +1,8 @@
-use std::{path::PathBuf, str::FromStr, time::Duration};
+use std::{fmt, path::PathBuf, str::FromStr};
 
 use nalgebra_glm::IVec2;
 
-use crate::Error;
+use crate::{iter::Fields, time::Time, Error};
 
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -12,7 +12,7 @@ pub struct Video {
     /// If the offset is `0,0`, writing it is optional.
     pub offset: IVec2,
     /// Start time of the event, in milliseconds from the beginning of the beatmap's audio. For events that do not use a start time, the default is 0.
-    pub start_time: Duration,
+    pub start_time: Time,
     /// Location of the video file relative to the beatmap directory.
     pub filename: PathBuf,
 }
@@ -21,32 +21,15 @@ impl FromStr for Video {
     type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut parts = s.split(',').map(str::trim);
-        let _ = parts.next().ok_or(Error::InvalidData(
-            "expected type while parsing video event",
-        ))?; // ignore the type
-        let start_time = parts
-            .next()
-            .ok_or(Error::InvalidData(
-                "expected start time while parsing video event",
-            ))?
-            .parse()
-            .map(Duration::from_millis)?;
-        let filename = parts.next().ok_or(Error::InvalidData(
-            "expected filename while parsing video event",
-        ))?;
-        let filename = PathBuf::from(filename);
-
-        let x = parts
-            .next()
-            .ok_or(Error::InvalidData("expected x while parsing video event"))?
-            .parse()
-            .map_err(Error::from)?;
-        let y = parts
-            .next()
-            .ok_or(Error::InvalidData("expected y while parsing video event"))?
-            .parse()
-            .map_err(Error::from)?;
+        let mut parts = Fields::new(s, ',');
+        let _ = parts.field("eventType", "expected type while parsing video event")?; // ignore the type
+        let start_time: Time =
+            parts.parse_field("startTime", "expected start time while parsing video event")?;
+        let filename = parts.field("filename", "expected filename while parsing video event")?;
+        let filename = PathBuf::from(filename.trim_matches('"'));
+
+        let x = parts.parse_field("xOffset", "expected x while parsing video event")?;
+        let y = parts.parse_field("yOffset", "expected y while parsing video event")?;
 
         let offset = IVec2::new(x, y);
 
@@ -57,3 +40,16 @@ impl FromStr for Video {
         })
     }
 }
+
+impl fmt::Display for Video {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Video,{},\"{}\",{},{}",
+            self.start_time,
+            self.filename.display(),
+            self.offset.x,
+            self.offset.y
+        )
+    }
+}
@@ -1,7 +1,7 @@
 pub mod countdown;
 pub mod overlay_position;
 
-use std::{path::PathBuf, str::FromStr};
+use std::{io, path::PathBuf, str::FromStr};
 
 use countdown::Countdown;
 use overlay_position::OverlayPosition;
@@ -20,7 +20,9 @@ pub struct General {
     /// Time in milliseconds when the audio preview should start
     #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub preview_time: Option<i32>,
-    /// Speed of the countdown before the first hit object
+    /// Speed of the countdown before the first hit object, [`None`] if there is no countdown
+    ///
+    /// osu! shows a [`Normal`][Countdown::Normal] countdown when the key is missing.
     #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub countdown: Option<Countdown>,
     /// Sample set that will be used if timing points do not override it
@@ -51,6 +53,8 @@ pub struct General {
     /// Whether or not the storyboard allows widescreen viewing
     pub widescreen_storyboard: bool,
     /// Whether or not sound samples will change rate when playing with speed-changing mods
+    ///
+    /// Only maps saved by osu!lazer have the key; samples never change rate in other maps.
     pub samples_match_playback_rate: bool,
 }
 
@@ -61,7 +65,7 @@ impl Default for General {
             audio_filename: PathBuf::new(),
             audio_lead_in: 0,
             preview_time: None,
-            countdown: None,
+            countdown: Some(Countdown::Normal),
             sample_set: SampleSet::default(),
             stack_leniency: 0.7,
             mode: Mode::Standard,
@@ -73,7 +77,7 @@ impl Default for General {
             countdown_offset: 0,
             special_style: false,
             widescreen_storyboard: false,
-            samples_match_playback_rate: true,
+            samples_match_playback_rate: false,
         }
     }
 }
@@ -88,7 +92,12 @@ impl General {
                 "AudioFilename" => self.audio_filename = value.into(),
                 "AudioLeadIn" => self.audio_lead_in = value.parse()?,
                 "PreviewTime" => self.preview_time = Some(value.parse()?),
-                "Countdown" => self.countdown = Some(Countdown::try_from(value.parse::<i32>()?)?),
+                "Countdown" => {
+                    self.countdown = match value.parse::<i32>()? {
+                        0 => None,
+                        value => Some(Countdown::try_from(value)?),
+                    }
+                }
                 "SampleSet" => self.sample_set = SampleSet::from_str(value)?,
                 "StackLeniency" => self.stack_leniency = value.parse()?,
                 "Mode" => self.mode = Mode::try_from(value.parse::<i32>()?)?,
@@ -114,4 +123,42 @@ impl General {
         }
         Ok(())
     }
+
+    pub fn write(&self, w: &mut impl io::Write) -> io::Result<()> {
+        writeln!(w, "AudioFilename: {}", self.audio_filename.display())?;
+        writeln!(w, "AudioLeadIn: {}", self.audio_lead_in)?;
+        writeln!(w, "PreviewTime: {}", self.preview_time.unwrap_or(-1))?;
+        writeln!(w, "Countdown: {}", self.countdown.map_or(0, |c| c as i32))?;
+        writeln!(w, "SampleSet: {}", self.sample_set)?;
+        writeln!(w, "StackLeniency: {}", self.stack_leniency)?;
+        writeln!(w, "Mode: {}", self.mode as i32)?;
+        writeln!(w, "LetterboxInBreaks: {}", self.letterbox_in_breaks as u8)?;
+        if self.use_skin_sprites {
+            writeln!(w, "UseSkinSprites: 1")?;
+        }
+        if let Some(overlay_position) = self.overlay_position {
+            writeln!(w, "OverlayPosition: {}", overlay_position)?;
+        }
+        if let Some(skin_preference) = &self.skin_preference {
+            writeln!(w, "SkinPreference: {}", skin_preference)?;
+        }
+        if self.epilepsy_warning {
+            writeln!(w, "EpilepsyWarning: 1")?;
+        }
+        if self.countdown_offset > 0 {
+            writeln!(w, "CountdownOffset: {}", self.countdown_offset)?;
+        }
+        if self.mode == Mode::Mania {
+            writeln!(w, "SpecialStyle: {}", self.special_style as u8)?;
+        }
+        writeln!(
+            w,
+            "WidescreenStoryboard: {}",
+            self.widescreen_storyboard as u8
+        )?;
+        if self.samples_match_playback_rate {
+            writeln!(w, "SamplesMatchPlaybackRate: 1")?;
+        }
+        Ok(())
+    }
 }
@@ -1,4 +1,4 @@
-use std::str::FromStr;
+use std::{fmt, str::FromStr};
 
 use crate::Error;
 
@@ -40,3 +40,12 @@ impl FromStr for OverlayPosition {
         }
     }
 }
+
+impl fmt::Display for OverlayPosition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            OverlayPosition::Below => "Below",
+            OverlayPosition::Above => "Above",
+        })
+    }
+}
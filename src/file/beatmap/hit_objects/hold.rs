@@ -0,0 +1,33 @@
+use std::{fmt, str::FromStr};
+
+use crate::{time::Time, Error};
+
+/// An osu!mania hold note (long note).
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Hold {
+    /// End time of the hold, in milliseconds from the beginning of the beatmap's audio.
+    pub end_time: Time,
+}
+
+impl Hold {
+    /// Parses the `endTime:hitSample` field of a hold note, returning the hold and the hit sample
+    /// part of the field, if there is one.
+    pub fn from_field(s: &str) -> Result<(Self, Option<&str>), Error> {
+        let (end_time, hit_sample) = match s.split_once(':') {
+            Some((end_time, hit_sample)) => (end_time, Some(hit_sample)),
+            None => (s, None),
+        };
+
+        let end_time = Time::from_str(end_time.trim()).map_err(|e| e.in_field("endTime"))?;
+
+        Ok((Self { end_time }, hit_sample))
+    }
+}
+
+impl fmt::Display for Hold {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.end_time)
+    }
+}
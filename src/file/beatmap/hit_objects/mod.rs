@@ -1,16 +1,30 @@
+pub mod hold;
 pub mod slider;
+pub mod slider_events;
+pub mod slider_path;
 pub mod spinner;
+pub mod stacking;
 
-use std::{fmt, str::FromStr, time::Duration};
+use std::{fmt, str::FromStr};
 
 use bitflags::bitflags;
+use hold::Hold;
 use nalgebra_glm::Vec2;
 use slider::Slider;
+use slider_events::SliderEvents;
 use spinner::Spinner;
 
-use crate::Error;
+use crate::{iter::Fields, time::Time, Error};
 
-use super::{hit_sample::HitSample, hit_sound::HitSound};
+use super::{
+    difficulty::Difficulty, hit_sample::HitSample, hit_sound::HitSound,
+    timing_point::control_points::ControlPoints,
+};
+
+/// Width of the playfield in osu! pixels.
+pub const PLAYFIELD_WIDTH: f32 = 512.0;
+/// Height of the playfield in osu! pixels.
+pub const PLAYFIELD_HEIGHT: f32 = 384.0;
 
 #[derive(Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -18,6 +32,8 @@ pub enum HitObjectKind {
     HitCircle,
     Slider(Slider),
     Spinner(Spinner),
+    /// osu!mania hold note.
+    Hold(Hold),
 }
 
 impl fmt::Debug for HitObjectKind {
@@ -26,6 +42,7 @@ impl fmt::Debug for HitObjectKind {
             HitObjectKind::HitCircle => write!(f, "HitCircle"),
             HitObjectKind::Slider(slider) => slider.fmt(f),
             HitObjectKind::Spinner(spinner) => spinner.fmt(f),
+            HitObjectKind::Hold(hold) => hold.fmt(f),
         }
     }
 }
@@ -45,6 +62,11 @@ impl HitObjectKind {
     pub const fn is_hit_circle(&self) -> bool {
         matches!(self, HitObjectKind::HitCircle)
     }
+
+    #[inline]
+    pub const fn is_hold(&self) -> bool {
+        matches!(self, HitObjectKind::Hold(_))
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -53,7 +75,7 @@ pub struct HitObject {
     /// Position in osu! pixels of the object.
     pub position: Vec2,
     /// Time when the object is to be hit, in milliseconds from the beginning of the beatmap's audio.
-    pub time: Duration,
+    pub time: Time,
     pub new_combo: bool,
     pub colour_hax: u8,
     /// Bit flags indicating the hitsound applied to the object.
@@ -78,35 +100,83 @@ bitflags! {
     }
 }
 
+impl HitObject {
+    /// Returns the time at which the object ends; the same as [`time`][HitObject::time] for
+    /// objects without a duration, or for sliders whose duration depends on timing.
+    #[inline]
+    pub fn end_time(&self) -> Time {
+        match &self.kind {
+            HitObjectKind::Spinner(spinner) => spinner.end_time,
+            HitObjectKind::Hold(hold) => hold.end_time,
+            HitObjectKind::HitCircle | HitObjectKind::Slider(_) => self.time,
+        }
+    }
+
+    /// Computes the timing and the nested judgement points of a slider, returning [`None`] for
+    /// other objects.
+    ///
+    /// `control_points` are built from the timing points of the beatmap, see
+    /// [`Beatmap::control_points`][super::Beatmap::control_points], and `file_format_version` its
+    /// [`file_format_version`][super::Beatmap::file_format_version], which changes how ticks are
+    /// placed before v8.
+    #[inline]
+    pub fn slider_events(
+        &self,
+        difficulty: &Difficulty,
+        control_points: &ControlPoints,
+        file_format_version: u8,
+    ) -> Option<SliderEvents> {
+        match &self.kind {
+            HitObjectKind::Slider(slider) => Some(SliderEvents::new(
+                self.position,
+                self.time,
+                slider,
+                difficulty,
+                control_points,
+                file_format_version,
+            )),
+            _ => None,
+        }
+    }
+
+    /// Mirrors the object and its slider curve across the horizontal centre of the playfield, the
+    /// way Hard Rock does.
+    pub fn flip_vertically(&mut self) {
+        self.position.y = PLAYFIELD_HEIGHT - self.position.y;
+
+        if let HitObjectKind::Slider(slider) = &mut self.kind {
+            for point in slider.curve.points_mut() {
+                point.y = PLAYFIELD_HEIGHT - point.y;
+            }
+        }
+    }
+
+    /// Returns the 0-based osu!mania column of the object for a beatmap with `key_count` keys,
+    /// see [`Difficulty::key_count`][super::difficulty::Difficulty::key_count].
+    #[inline]
+    pub fn mania_column(&self, key_count: u32) -> u32 {
+        let key_count = key_count.max(1);
+        let column = (self.position.x * key_count as f32 / PLAYFIELD_WIDTH).floor();
+        (column.max(0.0) as u32).min(key_count - 1)
+    }
+}
+
 impl FromStr for HitObject {
     type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut parts = s.split(',').map(str::trim);
-        let x = parts
-            .next()
-            .ok_or(Error::InvalidData("expected x while parsing hit object"))?
-            .parse()
-            .map_err(Error::from)?;
-        let y = parts
-            .next()
-            .ok_or(Error::InvalidData("expected y while parsing hit object"))?
-            .parse()
-            .map_err(Error::from)?;
+        let mut parts = Fields::new(s, ',');
+        let x = parts.parse_field("x", "expected x while parsing hit object")?;
+        let y = parts.parse_field("y", "expected y while parsing hit object")?;
 
         let position = Vec2::new(x, y);
 
-        let time = parts
-            .next()
-            .ok_or(Error::InvalidData("expected time while parsing hit object"))
-            .map(str::parse)?
-            .map(Duration::from_millis)?;
+        let time = parts.parse_field("time", "expected time while parsing hit object")?;
 
         let ty = parts
-            .next()
-            .ok_or(Error::InvalidData("expected type while parsing hit object"))
-            .and_then(|s| s.parse().map_err(Error::from))
+            .parse_field("type", "expected type while parsing hit object")
             .map(HitObjectType::from_bits_truncate)?;
+        let ty_column = parts.column();
 
         let new_combo = ty.contains(HitObjectType::NEW_COMBO);
         let colour_hax = ty & HitObjectType::COLOUR_HAX;
@@ -114,27 +184,39 @@ impl FromStr for HitObject {
         let colour_hax = colour_hax >> 4;
 
         let hit_sound = parts
-            .next()
-            .ok_or(Error::InvalidData(
-                "expected hit sound while parsing hit object",
-            ))
-            .and_then(|s| s.parse().map_err(Error::from))
+            .parse_field("hitSound", "expected hit sound while parsing hit object")
             .map(HitSound::from_bits_truncate)?;
 
+        let mut hold_hit_sample = None;
+
         let kind = if ty.contains(HitObjectType::HIT_CIRCLE) {
             HitObjectKind::HitCircle
         } else if ty.contains(HitObjectType::SLIDER) {
-            HitObjectKind::Slider(Slider::from_parts(&mut parts)?)
+            HitObjectKind::Slider(
+                Slider::from_parts(&mut parts).map_err(|e| e.at_column(parts.column()))?,
+            )
         } else if ty.contains(HitObjectType::SPINNER) {
-            HitObjectKind::Spinner(Spinner::from_parts(&mut parts)?)
+            HitObjectKind::Spinner(
+                Spinner::from_parts(&mut parts).map_err(|e| e.at_column(parts.column()))?,
+            )
+        } else if ty.contains(HitObjectType::MANIA_HOLD_NOTE) {
+            let field = parts.field("endTime", "expected end time while parsing hold note")?;
+            let (hold, hit_sample) =
+                Hold::from_field(field).map_err(|e| parts.error("endTime", e))?;
+            hold_hit_sample = hit_sample;
+            HitObjectKind::Hold(hold)
         } else {
-            return Err(Error::InvalidData("invalid hit object type"));
+            return Err(Error::InvalidData("invalid hit object type")
+                .in_field("type")
+                .at_column(ty_column));
         };
 
-        let hit_sample = parts
-            .next()
+        // hold notes store their hit sample in the same field as their end time
+        let hit_sample = hold_hit_sample
+            .or_else(|| parts.next())
             .map(str::parse)
-            .unwrap_or(Ok(HitSample::default()))?;
+            .unwrap_or(Ok(HitSample::default()))
+            .map_err(|e| parts.error("hitSample", e))?;
 
         Ok(HitObject {
             position,
@@ -147,3 +229,47 @@ impl FromStr for HitObject {
         })
     }
 }
+
+impl fmt::Display for HitObject {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut ty = match self.kind {
+            HitObjectKind::HitCircle => HitObjectType::HIT_CIRCLE,
+            HitObjectKind::Slider(_) => HitObjectType::SLIDER,
+            HitObjectKind::Spinner(_) => HitObjectType::SPINNER,
+            HitObjectKind::Hold(_) => HitObjectType::MANIA_HOLD_NOTE,
+        };
+        if self.new_combo {
+            ty |= HitObjectType::NEW_COMBO;
+        }
+        ty |= HitObjectType::from_bits_truncate(self.colour_hax << 4) & HitObjectType::COLOUR_HAX;
+
+        write!(
+            f,
+            "{},{},{},{},{},",
+            self.position.x,
+            self.position.y,
+            self.time,
+            ty.bits(),
+            self.hit_sound.bits()
+        )?;
+
+        match &self.kind {
+            HitObjectKind::HitCircle => {}
+            // lines without edge sounds keep them missing, so that they still default to the
+            // hit object's sound when it is changed
+            HitObjectKind::Slider(slider)
+                if slider.has_default_edges() && self.hit_sample == HitSample::default() =>
+            {
+                return write!(f, "{},{},{}", slider.curve, slider.slides, slider.length);
+            }
+            HitObjectKind::Slider(slider) => {
+                slider.fmt_with(f, self.hit_sound)?;
+                f.write_str(",")?;
+            }
+            HitObjectKind::Spinner(spinner) => write!(f, "{},", spinner)?,
+            HitObjectKind::Hold(hold) => write!(f, "{}:", hold)?,
+        }
+
+        write!(f, "{}", self.hit_sample)
+    }
+}
@@ -1,10 +1,10 @@
-use std::str::FromStr;
+use std::{fmt, str::FromStr};
 
 use nalgebra_glm::Vec2;
 
 use crate::{
     file::beatmap::{hit_sound::HitSound, sample_set::SampleSet},
-    iter::{AndThenExt, OkMapExt},
+    iter::{AndThenExt, ErrMapExt, OkMapExt},
     Error,
 };
 
@@ -24,6 +24,29 @@ pub enum Curve {
     Perfect(Vec<Vec2>),
 }
 
+impl Curve {
+    /// Returns the anchor points of the curve, excluding the hit object's position.
+    #[inline]
+    pub fn points(&self) -> &[Vec2] {
+        match self {
+            Curve::Bezier(points)
+            | Curve::CatmullRom(points)
+            | Curve::Linear(points)
+            | Curve::Perfect(points) => points,
+        }
+    }
+
+    #[inline]
+    pub fn points_mut(&mut self) -> &mut Vec<Vec2> {
+        match self {
+            Curve::Bezier(points)
+            | Curve::CatmullRom(points)
+            | Curve::Linear(points)
+            | Curve::Perfect(points) => points,
+        }
+    }
+}
+
 impl FromStr for Curve {
     type Err = Error;
 
@@ -69,6 +92,23 @@ impl FromStr for Curve {
     }
 }
 
+impl fmt::Display for Curve {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Curve::Bezier(_) => "B",
+            Curve::CatmullRom(_) => "C",
+            Curve::Linear(_) => "L",
+            Curve::Perfect(_) => "P",
+        })?;
+
+        for point in self.points() {
+            write!(f, "|{}:{}", point.x, point.y)?;
+        }
+
+        Ok(())
+    }
+}
+
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Slider {
@@ -81,8 +121,8 @@ pub struct Slider {
     /// Hitsounds that play when hitting edges of the slider's curve.
     /// The first sound is the one that plays when the slider is first clicked, and the last sound is the one that plays when the slider's end is hit.
     pub edge_sounds: Vec<HitSound>,
-    /// Sample sets used for the edgeSounds.
-    pub edge_sets: Vec<(SampleSet, SampleSet)>,
+    /// Sample sets used for the edgeSounds, as `(normal, addition)` pairs ([`None`] = inherit).
+    pub edge_sets: Vec<(Option<SampleSet>, Option<SampleSet>)>,
 }
 
 impl Slider {
@@ -90,17 +130,20 @@ impl Slider {
         let curve = parts
             .next()
             .ok_or(Error::InvalidData("expected curve while parsing slider"))
-            .and_then(Curve::from_str)?;
+            .and_then(Curve::from_str)
+            .map_err(|e| e.in_field("curve"))?;
 
         let slides = parts
             .next()
             .ok_or(Error::InvalidData("expected slides while parsing slider"))
-            .and_then(|s| s.parse().map_err(Error::from))?;
+            .and_then(|s| s.parse().map_err(Error::from))
+            .map_err(|e| e.in_field("slides"))?;
 
         let length = parts
             .next()
             .ok_or(Error::InvalidData("expected length while parsing slider"))
-            .and_then(|s| s.parse().map_err(Error::from))?;
+            .and_then(|s| s.parse().map_err(Error::from))
+            .map_err(|e| e.in_field("length"))?;
 
         let edge_sounds = if let Some(parts) = parts.next() {
             Some(
@@ -108,6 +151,7 @@ impl Slider {
                     .split('|')
                     .map(u8::from_str)
                     .ok_map(HitSound::from_bits_truncate)
+                    .err_map(|e| Error::from(e).in_field("edgeSounds"))
                     .collect::<Result<Vec<_>, _>>()?,
             )
         } else {
@@ -127,8 +171,9 @@ impl Slider {
                         Ok((i32::from_str(normal)?, i32::from_str(addition)?))
                     })
                     .and_then(|(normal, addition)| {
-                        Ok((SampleSet::try_from(normal)?, SampleSet::try_from(addition)?))
+                        Ok((sample_set_from_i32(normal)?, sample_set_from_i32(addition)?))
                     })
+                    .err_map(|e| e.in_field("edgeSets"))
                     .collect::<Result<Vec<_>, _>>()?,
             )
         } else {
@@ -146,3 +191,76 @@ impl Slider {
         })
     }
 }
+
+impl Slider {
+    /// Returns `true` if the edge sounds and edge sets were left out of the hit object line.
+    #[inline]
+    pub fn has_default_edges(&self) -> bool {
+        self.edge_sounds.is_empty() && self.edge_sets.is_empty()
+    }
+
+    /// Formats the slider's object parameters, writing `hit_sound` for every edge when the edge
+    /// sounds are missing, as osu! plays the hit object's sound on each of them.
+    pub(super) fn fmt_with(&self, f: &mut fmt::Formatter<'_>, hit_sound: HitSound) -> fmt::Result {
+        write!(f, "{},{},{},", self.curve, self.slides, self.length)?;
+
+        let edges = self.slides as usize + 1;
+
+        if self.edge_sounds.is_empty() {
+            write_joined(f, (0..edges).map(|_| hit_sound.bits()), '|')?;
+        } else {
+            write_joined(f, self.edge_sounds.iter().map(|s| s.bits()), '|')?;
+        }
+
+        f.write_str(",")?;
+
+        if self.edge_sets.is_empty() {
+            write_joined(f, (0..edges).map(|_| "0:0"), '|')
+        } else {
+            write_joined(
+                f,
+                self.edge_sets.iter().map(|(normal, addition)| {
+                    format!(
+                        "{}:{}",
+                        normal.map_or(0, |s| s as i32),
+                        addition.map_or(0, |s| s as i32)
+                    )
+                }),
+                '|',
+            )
+        }
+    }
+}
+
+impl fmt::Display for Slider {
+    /// Formats the slider's object parameters: `curve,slides,length,edgeSounds,edgeSets`.
+    ///
+    /// Missing edge sounds and edge sets are written out as defaults for every edge, so that
+    /// the hit sample following them stays in the right column.
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_with(f, HitSound::empty())
+    }
+}
+
+#[inline]
+fn sample_set_from_i32(value: i32) -> Result<Option<SampleSet>, Error> {
+    Ok(match value {
+        0 => None,
+        _ => Some(SampleSet::try_from(value)?),
+    })
+}
+
+fn write_joined<T: fmt::Display>(
+    f: &mut fmt::Formatter<'_>,
+    items: impl Iterator<Item = T>,
+    separator: char,
+) -> fmt::Result {
+    for (i, item) in items.enumerate() {
+        if i > 0 {
+            write!(f, "{}", separator)?;
+        }
+        write!(f, "{}", item)?;
+    }
+    Ok(())
+}
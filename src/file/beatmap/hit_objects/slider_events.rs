@@ -0,0 +1,240 @@
+use nalgebra_glm::Vec2;
+
+use crate::{
+    file::beatmap::{difficulty::Difficulty, timing_point::control_points::ControlPoints},
+    time::Time,
+};
+
+use super::{slider::Slider, slider_path::SliderPath};
+
+/// Distance in osu! pixels a slider travels in one beat at a slider multiplier of 1.
+pub const BASE_SCORING_DISTANCE: f64 = 100.0;
+/// Offset of the legacy last tick from the end of a slider.
+pub const LEGACY_LAST_TICK_OFFSET: Time = Time(36.0);
+/// Sliders longer than this are cut short when generating ticks.
+const MAX_LENGTH: f64 = 100_000.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SliderEventKind {
+    Head,
+    Tick,
+    Repeat,
+    /// Judgement placed [`LEGACY_LAST_TICK_OFFSET`] before the tail (but never before the middle
+    /// of the slider), which osu!stable uses instead of the tail itself.
+    LegacyLastTick,
+    Tail,
+}
+
+/// A judgement point along a slider.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SliderEvent {
+    pub kind: SliderEventKind,
+    /// 0-based index of the span the event belongs to.
+    pub span_index: u32,
+    pub span_start_time: Time,
+    pub time: Time,
+    /// Progress along the path, from 0 at the head to 1 at the end of the path.
+    pub path_progress: f64,
+    /// Absolute position in osu! pixels.
+    pub position: Vec2,
+}
+
+/// Timing of a slider and its nested judgement points, see
+/// [`HitObject::slider_events`][super::HitObject::slider_events].
+#[derive(Debug, Clone, PartialEq)]
+pub struct SliderEvents {
+    pub path: SliderPath,
+    /// Speed of the slider ball in osu! pixels per millisecond.
+    pub velocity: f64,
+    /// Distance between two ticks in osu! pixels.
+    pub tick_distance: f64,
+    /// Number of times the path is travelled, see [`Slider::slides`].
+    pub spans: u32,
+    /// Duration of a single span, from one end of the path to the other.
+    pub span_duration: Time,
+    pub end_time: Time,
+    /// Head, ticks, repeats, legacy last tick and tail, in chronological order.
+    pub events: Vec<SliderEvent>,
+}
+
+impl SliderEvents {
+    /// Computes the timing of `slider`, with the same rules as osu!stable.
+    pub fn new(
+        head: Vec2,
+        start_time: Time,
+        slider: &Slider,
+        difficulty: &Difficulty,
+        control_points: &ControlPoints,
+        file_format_version: u8,
+    ) -> Self {
+        let path = SliderPath::from_slider(head, slider);
+        let beat_length = control_points.beat_length_at(start_time);
+        let slider_velocity = control_points.slider_velocity_at(start_time);
+
+        let scoring_distance =
+            BASE_SCORING_DISTANCE * difficulty.slider_multiplier as f64 * slider_velocity;
+        let velocity = scoring_distance / beat_length;
+
+        // before v8, slider velocity did not change how many ticks were generated over the same
+        // distance
+        let tick_distance_multiplier = if file_format_version < 8 {
+            1.0 / slider_velocity
+        } else {
+            1.0
+        };
+        let tick_distance =
+            scoring_distance / difficulty.slider_tick_rate as f64 * tick_distance_multiplier;
+
+        let spans = slider.slides.max(1);
+        let span_duration = Time(path.length() / velocity);
+        let end_time = start_time + span_duration * spans as f64;
+
+        let events = generate(
+            &path,
+            head,
+            start_time,
+            span_duration,
+            velocity,
+            tick_distance,
+            spans,
+        );
+
+        Self {
+            path,
+            velocity,
+            tick_distance,
+            spans,
+            span_duration,
+            end_time,
+            events,
+        }
+    }
+
+    #[inline]
+    pub fn duration(&self) -> Time {
+        self.span_duration * self.spans as f64
+    }
+
+    #[inline]
+    pub fn ticks(&self) -> impl Iterator<Item = &SliderEvent> {
+        self.events
+            .iter()
+            .filter(|e| e.kind == SliderEventKind::Tick)
+    }
+
+    #[inline]
+    pub fn repeats(&self) -> impl Iterator<Item = &SliderEvent> {
+        self.events
+            .iter()
+            .filter(|e| e.kind == SliderEventKind::Repeat)
+    }
+
+    #[inline]
+    pub fn legacy_last_tick(&self) -> Option<&SliderEvent> {
+        self.events
+            .iter()
+            .find(|e| e.kind == SliderEventKind::LegacyLastTick)
+    }
+
+    #[inline]
+    pub fn tail(&self) -> Option<&SliderEvent> {
+        self.events.last()
+    }
+}
+
+fn generate(
+    path: &SliderPath,
+    head: Vec2,
+    start_time: Time,
+    span_duration: Time,
+    velocity: f64,
+    tick_distance: f64,
+    spans: u32,
+) -> Vec<SliderEvent> {
+    let length = path.length().min(MAX_LENGTH);
+    let tick_distance = tick_distance.clamp(0.0, length);
+    let min_distance_from_end = velocity * 10.0;
+
+    let event = |kind, span_index: u32, time, path_progress: f64| SliderEvent {
+        kind,
+        span_index,
+        span_start_time: start_time + span_duration * span_index as f64,
+        time,
+        path_progress,
+        position: head + path.position_at(path_progress),
+    };
+
+    let mut events = vec![event(SliderEventKind::Head, 0, start_time, 0.0)];
+
+    for span in 0..spans {
+        let span_start_time = start_time + span_duration * span as f64;
+        let reversed = span % 2 == 1;
+
+        let first_tick = events.len();
+        if tick_distance != 0.0 {
+            let mut d = tick_distance;
+            while d <= length && d < length - min_distance_from_end {
+                // ticks are placed from the start of the path so that they are in the same
+                // positions in every span
+                let path_progress = d / length;
+                let time_progress = if reversed {
+                    1.0 - path_progress
+                } else {
+                    path_progress
+                };
+
+                events.push(event(
+                    SliderEventKind::Tick,
+                    span,
+                    span_start_time + span_duration * time_progress,
+                    path_progress,
+                ));
+
+                d += tick_distance;
+            }
+        }
+        if reversed {
+            events[first_tick..].reverse();
+        }
+
+        if span < spans - 1 {
+            events.push(event(
+                SliderEventKind::Repeat,
+                span,
+                span_start_time + span_duration,
+                ((span + 1) % 2) as f64,
+            ));
+        }
+    }
+
+    let total_duration = span_duration * spans as f64;
+    let final_span = spans - 1;
+    let final_span_start_time = start_time + span_duration * final_span as f64;
+
+    let legacy_last_tick_time = (start_time + total_duration / 2.0)
+        .max(final_span_start_time + span_duration - LEGACY_LAST_TICK_OFFSET);
+    let mut progress = (legacy_last_tick_time - final_span_start_time) / span_duration;
+    if spans % 2 == 0 {
+        progress = 1.0 - progress;
+    }
+    if !progress.is_finite() {
+        progress = (spans % 2) as f64;
+    }
+
+    events.push(event(
+        SliderEventKind::LegacyLastTick,
+        final_span,
+        legacy_last_tick_time,
+        progress,
+    ));
+    events.push(event(
+        SliderEventKind::Tail,
+        final_span,
+        start_time + total_duration,
+        (spans % 2) as f64,
+    ));
+
+    events
+}
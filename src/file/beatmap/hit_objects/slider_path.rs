@@ -0,0 +1,357 @@
+use nalgebra_glm::Vec2;
+
+use super::slider::{Curve, Slider};
+
+/// Maximum distance between a Bézier curve and its approximation.
+const BEZIER_TOLERANCE: f32 = 0.25;
+/// Maximum distance between a circular arc and its approximation.
+const CIRCULAR_ARC_TOLERANCE: f32 = 0.1;
+/// Number of line segments between two points of a Catmull curve.
+const CATMULL_DETAIL: usize = 50;
+
+/// Geometry of a slider, tessellated into line segments the same way osu! does.
+///
+/// Positions are relative to the slider's head, that is the [`position`][super::HitObject]
+/// of the hit object.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SliderPath {
+    points: Vec<Vec2>,
+    /// Distance along the path from the head to every point.
+    cumulative_length: Vec<f64>,
+}
+
+impl SliderPath {
+    /// Tessellates `curve`, whose anchor points are absolute, for a slider whose head is at
+    /// `head`.
+    ///
+    /// If `expected_length` is given, the path is shortened or extended along its last segment
+    /// to match it, like osu! does with [`Slider::length`].
+    pub fn new(head: Vec2, curve: &Curve, expected_length: Option<f64>) -> Self {
+        let mut vertices = Vec::with_capacity(curve.points().len() + 1);
+        vertices.push(Vec2::zeros());
+        vertices.extend(curve.points().iter().map(|p| p - head));
+
+        let points = match curve {
+            Curve::Linear(_) => vertices,
+            Curve::CatmullRom(_) => catmull(&vertices),
+            Curve::Perfect(_) if vertices.len() == 3 => circular_arc(&vertices),
+            // perfect curves without exactly three points are treated as béziers
+            Curve::Bezier(_) | Curve::Perfect(_) => {
+                let mut points: Vec<Vec2> = Vec::new();
+
+                // a repeated point ends a bézier segment and starts the next one, unless it is
+                // the last point
+                let mut start = 0;
+                for end in 1..=vertices.len() {
+                    if end + 1 < vertices.len() && vertices[end] != vertices[end - 1]
+                        || end + 1 == vertices.len()
+                    {
+                        continue;
+                    }
+
+                    let segment = match &vertices[start..end] {
+                        [point] => vec![*point],
+                        segment => bezier(segment),
+                    };
+                    let skip_first = points.last().is_some() && points.last() == segment.first();
+                    points.extend(segment.into_iter().skip(skip_first as usize));
+
+                    start = end;
+                }
+
+                points
+            }
+        };
+
+        let mut path = Self {
+            points,
+            cumulative_length: Vec::new(),
+        };
+        path.calculate_length(expected_length);
+
+        path
+    }
+
+    /// Tessellates the path of a slider whose head is at `head`, using its length.
+    #[inline]
+    pub fn from_slider(head: Vec2, slider: &Slider) -> Self {
+        let length = (slider.length > 0.0).then_some(slider.length as f64);
+        Self::new(head, &slider.curve, length)
+    }
+
+    /// Points of the tessellated path, relative to the slider's head.
+    #[inline]
+    pub fn points(&self) -> &[Vec2] {
+        &self.points
+    }
+
+    /// Distance along the path from the head to each of [`points`][SliderPath::points].
+    #[inline]
+    pub fn cumulative_lengths(&self) -> &[f64] {
+        &self.cumulative_length
+    }
+
+    /// Total length of the path in osu! pixels.
+    #[inline]
+    pub fn length(&self) -> f64 {
+        self.cumulative_length.last().copied().unwrap_or_default()
+    }
+
+    /// Returns the position at `progress`, from 0 at the head to 1 at the end of the path.
+    #[inline]
+    pub fn position_at(&self, progress: f64) -> Vec2 {
+        self.position_at_distance(progress.clamp(0.0, 1.0) * self.length())
+    }
+
+    /// Returns the position at `distance` osu! pixels along the path.
+    pub fn position_at_distance(&self, distance: f64) -> Vec2 {
+        let i = self.cumulative_length.partition_point(|&l| l < distance);
+
+        let (Some(first), Some(last)) = (self.points.first(), self.points.last()) else {
+            return Vec2::zeros();
+        };
+        if i == 0 {
+            return *first;
+        }
+        if i >= self.points.len() {
+            return *last;
+        }
+
+        let (p0, p1) = (self.points[i - 1], self.points[i]);
+        let (d0, d1) = (self.cumulative_length[i - 1], self.cumulative_length[i]);
+
+        // avoid dividing by almost zero for points that are extremely close to each other
+        if (d1 - d0).abs() < 1e-7 {
+            return p0;
+        }
+
+        let w = (distance - d0) / (d1 - d0);
+        p0 + (p1 - p0) * w as f32
+    }
+
+    fn calculate_length(&mut self, expected_length: Option<f64>) {
+        let mut length = 0.0;
+        self.cumulative_length.clear();
+        self.cumulative_length.push(0.0);
+
+        for pair in self.points.windows(2) {
+            length += (pair[1] - pair[0]).norm() as f64;
+            self.cumulative_length.push(length);
+        }
+
+        let Some(expected) = expected_length.filter(|&expected| expected != length) else {
+            return;
+        };
+
+        // osu!stable does not extend sliders whose last two points are equal
+        let n = self.points.len();
+        if n >= 2 && self.points[n - 1] == self.points[n - 2] && expected > length {
+            return;
+        }
+
+        // the last length is always incorrect
+        self.cumulative_length.pop();
+        let mut end = self.points.len().saturating_sub(1);
+
+        if length > expected {
+            // drop the segments past the expected length
+            while self
+                .cumulative_length
+                .last()
+                .is_some_and(|&last| last >= expected)
+            {
+                self.cumulative_length.pop();
+                self.points.remove(end);
+                end = end.saturating_sub(1);
+            }
+        }
+
+        if end == 0 {
+            self.points.truncate(1);
+            self.cumulative_length.clear();
+            self.cumulative_length.push(0.0);
+            return;
+        }
+
+        // shorten or lengthen the last segment
+        let last = self.cumulative_length[self.cumulative_length.len() - 1];
+        let direction = self.points[end] - self.points[end - 1];
+        let direction = if direction.norm() > 0.0 {
+            direction.normalize()
+        } else {
+            direction
+        };
+        self.points[end] = self.points[end - 1] + direction * (expected - last) as f32;
+        self.points.truncate(end + 1);
+        self.cumulative_length.push(expected);
+    }
+}
+
+/// Approximates a bézier curve by recursively subdividing it until every part is flat enough.
+fn bezier(control_points: &[Vec2]) -> Vec<Vec2> {
+    let count = control_points.len();
+    let mut output = Vec::new();
+
+    if count == 0 {
+        return output;
+    }
+
+    let mut to_flatten = vec![control_points.to_vec()];
+    let mut left = vec![Vec2::zeros(); count * 2 - 1];
+    let mut right = vec![Vec2::zeros(); count];
+    let mut midpoints = vec![Vec2::zeros(); count];
+
+    while let Some(mut parent) = to_flatten.pop() {
+        if bezier_is_flat_enough(&parent) {
+            // approximate the flat part with the midpoints of one more subdivision
+            bezier_subdivide(&parent, &mut left, &mut right, &mut midpoints);
+            left[count..].copy_from_slice(&right[1..]);
+
+            output.push(parent[0]);
+            for i in 1..count - 1 {
+                let index = 2 * i;
+                output.push((left[index - 1] + left[index] * 2.0 + left[index + 1]) * 0.25);
+            }
+            continue;
+        }
+
+        let mut right_child = vec![Vec2::zeros(); count];
+        bezier_subdivide(&parent, &mut left, &mut right_child, &mut midpoints);
+        parent.copy_from_slice(&left[..count]);
+
+        to_flatten.push(right_child);
+        to_flatten.push(parent);
+    }
+
+    output.push(control_points[count - 1]);
+    output
+}
+
+fn bezier_is_flat_enough(control_points: &[Vec2]) -> bool {
+    control_points.windows(3).all(|p| {
+        (p[0] - p[1] * 2.0 + p[2]).norm_squared() <= BEZIER_TOLERANCE * BEZIER_TOLERANCE * 4.0
+    })
+}
+
+/// Splits a bézier curve in two halves with de Casteljau's algorithm.
+fn bezier_subdivide(
+    control_points: &[Vec2],
+    left: &mut [Vec2],
+    right: &mut [Vec2],
+    midpoints: &mut [Vec2],
+) {
+    let count = control_points.len();
+    midpoints[..count].copy_from_slice(control_points);
+
+    for i in 0..count {
+        left[i] = midpoints[0];
+        right[count - i - 1] = midpoints[count - i - 1];
+
+        for j in 0..count - i - 1 {
+            midpoints[j] = (midpoints[j] + midpoints[j + 1]) / 2.0;
+        }
+    }
+}
+
+fn catmull(control_points: &[Vec2]) -> Vec<Vec2> {
+    let mut output =
+        Vec::with_capacity((control_points.len().saturating_sub(1)) * CATMULL_DETAIL * 2);
+
+    for i in 0..control_points.len().saturating_sub(1) {
+        let v1 = if i > 0 {
+            control_points[i - 1]
+        } else {
+            control_points[i]
+        };
+        let v2 = control_points[i];
+        let v3 = control_points[i + 1];
+        let v4 = if i + 2 < control_points.len() {
+            control_points[i + 2]
+        } else {
+            v3 + v3 - v2
+        };
+
+        for c in 0..CATMULL_DETAIL {
+            output.push(catmull_point(
+                v1,
+                v2,
+                v3,
+                v4,
+                c as f32 / CATMULL_DETAIL as f32,
+            ));
+            output.push(catmull_point(
+                v1,
+                v2,
+                v3,
+                v4,
+                (c + 1) as f32 / CATMULL_DETAIL as f32,
+            ));
+        }
+    }
+
+    output
+}
+
+fn catmull_point(v1: Vec2, v2: Vec2, v3: Vec2, v4: Vec2, t: f32) -> Vec2 {
+    let t2 = t * t;
+    let t3 = t * t2;
+
+    (v2 * 2.0
+        + (-v1 + v3) * t
+        + (v1 * 2.0 - v2 * 5.0 + v3 * 4.0 - v4) * t2
+        + (-v1 + v2 * 3.0 - v3 * 3.0 + v4) * t3)
+        * 0.5
+}
+
+/// Approximates the circular arc going through three points, falling back to a bézier curve if
+/// the points are (almost) collinear.
+fn circular_arc(control_points: &[Vec2]) -> Vec<Vec2> {
+    let (a, b, c) = (control_points[0], control_points[1], control_points[2]);
+
+    if ((b.y - a.y) * (c.x - a.x) - (b.x - a.x) * (c.y - a.y)).abs() <= 1e-3 {
+        return bezier(control_points);
+    }
+
+    let d = 2.0 * (a.x * (b - c).y + b.x * (c - a).y + c.x * (a - b).y);
+    let (a_sq, b_sq, c_sq) = (a.norm_squared(), b.norm_squared(), c.norm_squared());
+
+    let centre = Vec2::new(
+        a_sq * (b - c).y + b_sq * (c - a).y + c_sq * (a - b).y,
+        a_sq * (c - b).x + b_sq * (a - c).x + c_sq * (b - a).x,
+    ) / d;
+
+    let d_a = a - centre;
+    let d_c = c - centre;
+    let radius = d_a.norm();
+
+    let theta_start = (d_a.y as f64).atan2(d_a.x as f64);
+    let mut theta_end = (d_c.y as f64).atan2(d_c.x as f64);
+    while theta_end < theta_start {
+        theta_end += 2.0 * std::f64::consts::PI;
+    }
+
+    let mut direction = 1.0;
+    let mut theta_range = theta_end - theta_start;
+
+    // draw the arc on the side of AC that B lies on
+    let ortho_a_to_c = Vec2::new((c - a).y, -(c - a).x);
+    if ortho_a_to_c.dot(&(b - a)) < 0.0 {
+        direction = -direction;
+        theta_range = 2.0 * std::f64::consts::PI - theta_range;
+    }
+
+    let amount_points = if 2.0 * radius <= CIRCULAR_ARC_TOLERANCE {
+        2
+    } else {
+        let step = 2.0 * (1.0 - CIRCULAR_ARC_TOLERANCE as f64 / radius as f64).acos();
+        ((theta_range / step).ceil() as usize).max(2)
+    };
+
+    (0..amount_points)
+        .map(|i| {
+            let fract = i as f64 / (amount_points - 1) as f64;
+            let theta = theta_start + direction * fract * theta_range;
+            centre + Vec2::new(theta.cos() as f32, theta.sin() as f32) * radius
+        })
+        .collect()
+}
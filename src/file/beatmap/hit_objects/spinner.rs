@@ -1,12 +1,11 @@
-use crate::Error;
-use std::str::FromStr;
-use std::time::Duration;
+use crate::{time::Time, Error};
+use std::{fmt, str::FromStr};
 
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Spinner {
     /// End time of the spinner, in milliseconds from the beginning of the beatmap's audio.
-    pub end_time: Duration,
+    pub end_time: Time,
 }
 
 impl Spinner {
@@ -16,9 +15,16 @@ impl Spinner {
             .ok_or(Error::InvalidData(
                 "expected end time while parsing spinner",
             ))
-            .and_then(|s| u64::from_str(s).map_err(Error::from))
-            .map(Duration::from_millis)?;
+            .and_then(Time::from_str)
+            .map_err(|e| e.in_field("endTime"))?;
 
         Ok(Self { end_time })
     }
 }
+
+impl fmt::Display for Spinner {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.end_time)
+    }
+}
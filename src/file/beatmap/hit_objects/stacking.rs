@@ -0,0 +1,203 @@
+use nalgebra_glm::Vec2;
+
+use crate::{
+    file::beatmap::{difficulty::Difficulty, timing_point::control_points::ControlPoints},
+    mode::Mode,
+    time::Time,
+};
+
+use super::HitObject;
+
+/// Objects closer than this distance in osu! pixels stack on each other.
+pub const STACK_DISTANCE: f32 = 3.0;
+
+/// Stacking of an osu!standard object, see [`stacking`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Stacking {
+    /// Number of objects the object is stacked on; negative for objects stacked down and right
+    /// from a slider end by the pre-v6 algorithm.
+    pub height: i32,
+    /// Offset applied to the object and its nested objects, in osu! pixels.
+    pub offset: Vec2,
+    /// Position of the object with the offset applied.
+    pub position: Vec2,
+    /// Position at the end of the object with the offset applied; the tail of sliders, or the
+    /// position of other objects.
+    pub end_position: Vec2,
+}
+
+/// Positions and times of an object used while stacking.
+struct Object {
+    time: Time,
+    end_time: Time,
+    position: Vec2,
+    /// Position of the tail, after every span.
+    end_position: Vec2,
+    /// Position at the end of the path, regardless of the number of spans.
+    path_end_position: Vec2,
+    is_slider: bool,
+    is_spinner: bool,
+    height: i32,
+}
+
+#[inline]
+fn is_near(a: Vec2, b: Vec2) -> bool {
+    nalgebra_glm::distance(&a, &b) < STACK_DISTANCE
+}
+
+/// Computes the stacking osu!stable applies to overlapping osu!standard objects.
+///
+/// Objects stack when they are within [`STACK_DISTANCE`] of each other, or of the end of a
+/// slider, and close enough in time: the approach preempt given by `difficulty` times
+/// `stack_leniency`. Beatmaps older than v6 use the original algorithm, which stacks objects
+/// following a slider end down and right instead of stacking the slider up and left.
+///
+/// Returns one [`Stacking`] per hit object, in the same order.
+pub fn stacking(
+    hit_objects: &[HitObject],
+    difficulty: &Difficulty,
+    stack_leniency: f32,
+    control_points: &ControlPoints,
+    file_format_version: u8,
+) -> Vec<Stacking> {
+    let mut objects = hit_objects
+        .iter()
+        .map(|hit_object| {
+            let events = hit_object.slider_events(difficulty, control_points, file_format_version);
+            let (end_position, path_end_position) = match &events {
+                Some(events) => (
+                    events
+                        .tail()
+                        .map_or(hit_object.position, |tail| tail.position),
+                    hit_object.position + events.path.position_at(1.0),
+                ),
+                None => (hit_object.position, hit_object.position),
+            };
+
+            Object {
+                time: hit_object.time,
+                end_time: events.map_or(hit_object.end_time(), |events| events.end_time),
+                position: hit_object.position,
+                end_position,
+                path_end_position,
+                is_slider: hit_object.kind.is_slider(),
+                is_spinner: hit_object.kind.is_spinner(),
+                height: 0,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let attributes = difficulty.attributes(Mode::Standard);
+    let threshold = attributes.preempt.unwrap_or_default() * stack_leniency as f64;
+
+    if file_format_version >= 6 {
+        apply(&mut objects, threshold);
+    } else {
+        apply_old(&mut objects, threshold);
+    }
+
+    // the same scale osu! applies to circles, 1/64 of the radius times -6.4
+    let radius = attributes.radius.unwrap_or_default() as f32;
+    objects
+        .iter()
+        .map(|object| {
+            let offset = Vec2::repeat(object.height as f32 * radius / -10.0);
+            Stacking {
+                height: object.height,
+                offset,
+                position: object.position + offset,
+                end_position: object.end_position + offset,
+            }
+        })
+        .collect()
+}
+
+/// Stacking used since v6.
+fn apply(objects: &mut [Object], threshold: Time) {
+    let Some(last) = objects.len().checked_sub(1) else {
+        return;
+    };
+
+    // reverse pass: the objects ending a stack are stacked up and left
+    for i in (1..=last).rev() {
+        if objects[i].height != 0 || objects[i].is_spinner {
+            continue;
+        }
+
+        let mut current = i;
+
+        if !objects[i].is_slider {
+            for n in (0..i).rev() {
+                if objects[n].is_spinner {
+                    continue;
+                }
+                if objects[current].time - objects[n].end_time > threshold {
+                    break;
+                }
+
+                // a slider ending under the stack moves the objects after it down and right
+                if objects[n].is_slider
+                    && is_near(objects[n].end_position, objects[current].position)
+                {
+                    let offset = objects[current].height - objects[n].height + 1;
+                    for j in n + 1..=i {
+                        if is_near(objects[n].end_position, objects[j].position) {
+                            objects[j].height -= offset;
+                        }
+                    }
+                    break;
+                }
+
+                if is_near(objects[n].position, objects[current].position) {
+                    objects[n].height = objects[current].height + 1;
+                    current = n;
+                }
+            }
+        } else {
+            for n in (0..i).rev() {
+                if objects[n].is_spinner {
+                    continue;
+                }
+                if objects[current].time - objects[n].time > threshold {
+                    break;
+                }
+
+                if is_near(objects[n].end_position, objects[current].position) {
+                    objects[n].height = objects[current].height + 1;
+                    current = n;
+                }
+            }
+        }
+    }
+}
+
+/// Stacking used before v6.
+fn apply_old(objects: &mut [Object], threshold: Time) {
+    for i in 0..objects.len() {
+        if objects[i].height != 0 && !objects[i].is_slider {
+            continue;
+        }
+
+        let mut start_time = objects[i].end_time;
+        let mut slider_stack = 0;
+
+        for j in i + 1..objects.len() {
+            if objects[j].time - threshold > start_time {
+                break;
+            }
+
+            // osu!stable never computed the end time of the inner object, so a slider or spinner
+            // in the chain does not extend it
+            if is_near(objects[j].position, objects[i].position) {
+                objects[i].height += 1;
+                start_time = objects[j].time;
+            } else if is_near(objects[j].position, objects[i].path_end_position) {
+                // objects on the end of a slider are stacked down and right
+                slider_stack += 1;
+                objects[j].height -= slider_stack;
+                start_time = objects[j].time;
+            }
+        }
+    }
+}
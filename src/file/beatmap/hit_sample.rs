@@ -1,10 +1,10 @@
-use std::str::FromStr;
+use std::{fmt, str::FromStr};
 
 use crate::Error;
 
 use super::sample_set::SampleSet;
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct HitSample {
     #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
@@ -65,7 +65,7 @@ impl FromStr for HitSample {
             Some(volume.parse()?)
         };
 
-        let filename = if filename == "0" {
+        let filename = if filename.is_empty() || filename == "0" {
             None
         } else {
             Some(filename.to_string())
@@ -80,3 +80,17 @@ impl FromStr for HitSample {
         })
     }
 }
+
+impl fmt::Display for HitSample {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}:{}:{}:{}",
+            self.normal_set.map_or(0, |s| s as i32),
+            self.addition_set.map_or(0, |s| s as i32),
+            self.index.unwrap_or(0),
+            self.volume.unwrap_or(0),
+            self.filename.as_deref().unwrap_or_default()
+        )
+    }
+}
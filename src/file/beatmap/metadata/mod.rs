@@ -1,3 +1,5 @@
+use std::io;
+
 use crate::Error;
 
 #[derive(Debug, Clone, Default)]
@@ -40,13 +42,7 @@ impl Metadata {
                 "Creator" => self.creator = value.into(),
                 "Version" => self.version = value.into(),
                 "Source" => self.source = value.into(),
-                "Tags" => {
-                    self.tags = value
-                        .split(' ')
-                        .map(str::trim)
-                        .map(str::to_string)
-                        .collect()
-                }
+                "Tags" => self.tags = value.split_whitespace().map(str::to_string).collect(),
                 "BeatmapID" => self.beatmap_id = Some(value.parse()?),
                 "BeatmapSetID" => self.beatmap_set_id = Some(value.parse()?),
 
@@ -55,4 +51,22 @@ impl Metadata {
         }
         Ok(())
     }
+
+    pub fn write(&self, w: &mut impl io::Write) -> io::Result<()> {
+        writeln!(w, "Title:{}", self.title)?;
+        writeln!(w, "TitleUnicode:{}", self.title_unicode)?;
+        writeln!(w, "Artist:{}", self.artist)?;
+        writeln!(w, "ArtistUnicode:{}", self.artist_unicode)?;
+        writeln!(w, "Creator:{}", self.creator)?;
+        writeln!(w, "Version:{}", self.version)?;
+        writeln!(w, "Source:{}", self.source)?;
+        writeln!(w, "Tags:{}", self.tags.join(" "))?;
+        if let Some(beatmap_id) = self.beatmap_id {
+            writeln!(w, "BeatmapID:{}", beatmap_id)?;
+        }
+        if let Some(beatmap_set_id) = self.beatmap_set_id {
+            writeln!(w, "BeatmapSetID:{}", beatmap_set_id)?;
+        }
+        Ok(())
+    }
 }
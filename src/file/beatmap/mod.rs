@@ -1,24 +1,33 @@
 use std::{
+    borrow::Cow,
     fs::File,
-    io::{self, BufRead, Read},
+    io::{self, BufRead, Read, Write},
     path::Path,
     str::FromStr,
 };
 
 use bitflags::bitflags;
 use colour::Colours;
+use diagnostic::{Diagnostic, Resolution, Strictness};
 use difficulty::Difficulty;
+use document::Document;
 use editor::Editor;
 use event::EventKind;
 use general::General;
-use hit_objects::HitObject;
+use hit_objects::{stacking::Stacking, HitObject};
 use metadata::Metadata;
-use timing_point::TimingPoint;
+use timing_point::{control_points::ControlPoints, TimingPoint};
 
-use crate::Error;
+use crate::{
+    file::storyboard::{variables::Variables, Storyboard},
+    Error,
+};
 
 pub mod colour;
+mod compat;
+pub mod diagnostic;
 pub mod difficulty;
+pub mod document;
 pub mod editor;
 pub mod event;
 pub mod general;
@@ -68,6 +77,12 @@ pub trait Visitor {
         let _ = events;
     }
 
+    /// Called with the sprites, animations and samples of the `[Events]` section.
+    #[inline]
+    fn visit_storyboard(&mut self, storyboard: Storyboard) {
+        let _ = storyboard;
+    }
+
     #[inline]
     fn visit_timing_points(&mut self, timing_points: impl Iterator<Item = TimingPoint>) {
         let _ = timing_points;
@@ -82,6 +97,13 @@ pub trait Visitor {
     fn visit_hit_objects(&mut self, hit_objects: impl Iterator<Item = HitObject>) {
         let _ = hit_objects;
     }
+
+    /// Called last with the problems found while decoding with [`Strictness::Lenient`] or
+    /// [`Strictness::OsuCompatible`].
+    #[inline]
+    fn visit_diagnostics(&mut self, diagnostics: impl Iterator<Item = Diagnostic>) {
+        let _ = diagnostics;
+    }
 }
 
 impl<T: Visitor> Visitor for &mut T {
@@ -115,6 +137,11 @@ impl<T: Visitor> Visitor for &mut T {
         T::visit_events(self, events)
     }
 
+    #[inline]
+    fn visit_storyboard(&mut self, storyboard: Storyboard) {
+        T::visit_storyboard(self, storyboard)
+    }
+
     #[inline]
     fn visit_timing_points(&mut self, timing_points: impl Iterator<Item = TimingPoint>) {
         T::visit_timing_points(self, timing_points)
@@ -129,6 +156,11 @@ impl<T: Visitor> Visitor for &mut T {
     fn visit_hit_objects(&mut self, hit_objects: impl Iterator<Item = HitObject>) {
         T::visit_hit_objects(self, hit_objects)
     }
+
+    #[inline]
+    fn visit_diagnostics(&mut self, diagnostics: impl Iterator<Item = Diagnostic>) {
+        T::visit_diagnostics(self, diagnostics)
+    }
 }
 
 impl<T: Visitor> Visitor for Box<T> {
@@ -162,6 +194,11 @@ impl<T: Visitor> Visitor for Box<T> {
         T::visit_events(self, events)
     }
 
+    #[inline]
+    fn visit_storyboard(&mut self, storyboard: Storyboard) {
+        T::visit_storyboard(self, storyboard)
+    }
+
     #[inline]
     fn visit_timing_points(&mut self, timing_points: impl Iterator<Item = TimingPoint>) {
         T::visit_timing_points(self, timing_points)
@@ -176,6 +213,11 @@ impl<T: Visitor> Visitor for Box<T> {
     fn visit_hit_objects(&mut self, hit_objects: impl Iterator<Item = HitObject>) {
         T::visit_hit_objects(self, hit_objects)
     }
+
+    #[inline]
+    fn visit_diagnostics(&mut self, diagnostics: impl Iterator<Item = Diagnostic>) {
+        T::visit_diagnostics(self, diagnostics)
+    }
 }
 
 #[derive(Debug, Clone, Default)]
@@ -187,9 +229,16 @@ pub struct Beatmap {
     pub metadata: Metadata,
     pub difficulty: Difficulty,
     pub events: Vec<EventKind>,
+    pub storyboard: Storyboard,
     pub timing_points: Vec<TimingPoint>,
     pub colours: Colours,
     pub hit_objects: Vec<HitObject>,
+    /// Problems found while decoding, see [`Strictness`].
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub diagnostics: Vec<Diagnostic>,
 }
 
 impl Visitor for Beatmap {
@@ -223,6 +272,11 @@ impl Visitor for Beatmap {
         self.events = events.collect();
     }
 
+    #[inline]
+    fn visit_storyboard(&mut self, storyboard: Storyboard) {
+        self.storyboard = storyboard;
+    }
+
     #[inline]
     fn visit_timing_points(&mut self, timing_points: impl Iterator<Item = TimingPoint>) {
         self.timing_points = timing_points.collect();
@@ -237,6 +291,104 @@ impl Visitor for Beatmap {
     fn visit_hit_objects(&mut self, hit_objects: impl Iterator<Item = HitObject>) {
         self.hit_objects = hit_objects.collect();
     }
+
+    #[inline]
+    fn visit_diagnostics(&mut self, diagnostics: impl Iterator<Item = Diagnostic>) {
+        self.diagnostics = diagnostics.collect();
+    }
+}
+
+impl Beatmap {
+    /// Builds an index of the timing points, to look up the timing active at a given time.
+    #[inline]
+    pub fn control_points(&self) -> ControlPoints {
+        ControlPoints::new(&self.timing_points)
+    }
+
+    /// Computes the osu!standard stacking of every hit object, see
+    /// [`stacking`][hit_objects::stacking::stacking].
+    #[inline]
+    pub fn stacking(&self) -> Vec<Stacking> {
+        hit_objects::stacking::stacking(
+            &self.hit_objects,
+            &self.difficulty,
+            self.general.stack_leniency,
+            &self.control_points(),
+            self.file_format_version,
+        )
+    }
+
+    /// Writes the beatmap to `path` in the `.osu` file format.
+    #[inline]
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), Error> {
+        self.write_to(File::create(path)?)
+    }
+
+    /// Writes the beatmap in the `.osu` file format, using the section layout osu!stable
+    /// produces when saving from the editor.
+    pub fn write_to(&self, data: impl Write) -> Result<(), Error> {
+        let w = &mut io::BufWriter::new(data);
+
+        writeln!(w, "osu file format v{}", self.file_format_version)?;
+        writeln!(w)?;
+
+        writeln!(w, "[General]")?;
+        self.general.write(w)?;
+        writeln!(w)?;
+
+        writeln!(w, "[Editor]")?;
+        self.editor.write(w)?;
+        writeln!(w)?;
+
+        writeln!(w, "[Metadata]")?;
+        self.metadata.write(w)?;
+        writeln!(w)?;
+
+        writeln!(w, "[Difficulty]")?;
+        self.difficulty.write(w)?;
+        writeln!(w)?;
+
+        writeln!(w, "[Events]")?;
+        writeln!(w, "//Background and Video events")?;
+        for event in self.events.iter().filter(|e| !e.is_break()) {
+            writeln!(w, "{}", event)?;
+        }
+        writeln!(w, "//Break Periods")?;
+        for event in self.events.iter().filter(|e| e.is_break()) {
+            writeln!(w, "{}", event)?;
+        }
+        self.storyboard.write(w)?;
+        writeln!(w)?;
+
+        writeln!(w, "[TimingPoints]")?;
+        let mut meter = 4;
+        for timing_point in &self.timing_points {
+            if let TimingPoint::Uninherited(t) = timing_point {
+                meter = t.meter;
+            }
+            timing_point.write(w, meter)?;
+        }
+        writeln!(w)?;
+        writeln!(w)?;
+
+        if self.colours.combo_colours().next().is_some()
+            || self.colours.slider_track_override.is_some()
+            || self.colours.slider_border.is_some()
+        {
+            writeln!(w, "[Colours]")?;
+            self.colours.write(w)?;
+            writeln!(w)?;
+        }
+
+        writeln!(w, "[HitObjects]")?;
+        for hit_object in &self.hit_objects {
+            writeln!(w, "{}", hit_object)?;
+        }
+
+        w.flush()?;
+
+        Ok(())
+    }
 }
 
 bitflags! {
@@ -250,6 +402,7 @@ bitflags! {
         const TIMING_POINTS = 1 << 5;
         const COLOURS = 1 << 6;
         const HIT_OBJECTS = 1 << 7;
+        const STORYBOARD = 1 << 8;
     }
 }
 
@@ -289,6 +442,12 @@ impl BeatmapDecoder {
         self.union(BeatmapDecoder::EVENTS)
     }
 
+    /// Decodes the storyboard objects and commands of the `[Events]` section.
+    #[inline]
+    pub const fn storyboard(self) -> Self {
+        self.union(BeatmapDecoder::STORYBOARD)
+    }
+
     #[inline]
     pub const fn timing_points(self) -> Self {
         self.union(BeatmapDecoder::TIMING_POINTS)
@@ -304,62 +463,40 @@ impl BeatmapDecoder {
         self.union(BeatmapDecoder::HIT_OBJECTS)
     }
 
-    pub fn parse<Vis>(self, mut beatmap: Vis, data: impl Read) -> Result<Vis, Error>
+    /// Decodes a beatmap with [`Strictness::Strict`], failing on the first malformed line.
+    #[inline]
+    pub fn parse<Vis>(self, beatmap: Vis, data: impl Read) -> Result<Vis, Error>
+    where
+        Vis: Visitor,
+    {
+        self.parse_with(Strictness::Strict, beatmap, data)
+    }
+
+    /// Decodes a beatmap, handling malformed lines according to `strictness`.
+    ///
+    /// Problems that did not abort decoding are passed to [`Visitor::visit_diagnostics`].
+    pub fn parse_with<Vis>(
+        self,
+        strictness: Strictness,
+        mut beatmap: Vis,
+        data: impl Read,
+    ) -> Result<Vis, Error>
     where
         Vis: Visitor,
     {
         let data = io::BufReader::new(data);
 
         let mut section = String::new();
+        let mut sections = Sections::new(self, strictness);
 
-        let mut general = if self.contains(BeatmapDecoder::GENERAL) {
-            Some(General::default())
-        } else {
-            None
-        };
-        let mut editor = if self.contains(BeatmapDecoder::EDITOR) {
-            Some(Editor::default())
-        } else {
-            None
-        };
-        let mut metadata = if self.contains(BeatmapDecoder::METADATA) {
-            Some(Metadata::default())
-        } else {
-            None
-        };
-        let mut difficulty = if self.contains(BeatmapDecoder::DIFFICULTY) {
-            Some(Difficulty::default())
-        } else {
-            None
-        };
-
-        let mut events = if self.contains(BeatmapDecoder::EVENTS) {
-            Some(Vec::with_capacity(8))
-        } else {
-            None
-        };
-        let mut timing_points = if self.contains(BeatmapDecoder::TIMING_POINTS) {
-            Some(Vec::with_capacity(128))
-        } else {
-            None
-        };
-        let mut colours = if self.contains(BeatmapDecoder::COLOURS) {
-            Some(Colours::default())
-        } else {
-            None
-        };
-        let mut hit_objects = if self.contains(BeatmapDecoder::HIT_OBJECTS) {
-            Some(Vec::with_capacity(1024))
-        } else {
-            None
-        };
-
-        for line in data.lines() {
+        for (i, line) in data.lines().enumerate() {
             let line = line?;
 
-            if line.starts_with("osu file format v") {
-                let version = line[16..].trim().parse::<u32>()?;
-                beatmap.visit_file_format_version(version);
+            if let Some(version) = parse_file_format_version(&line) {
+                match version {
+                    Ok(version) => beatmap.visit_file_format_version(version),
+                    Err(e) => sections.error(e.at_line(i + 1, "", &line))?,
+                }
                 continue;
             }
 
@@ -367,84 +504,313 @@ impl BeatmapDecoder {
                 continue;
             }
 
-            if line.starts_with("[") && line.ends_with("]") {
-                section = line[1..line.len() - 1].to_string();
+            let trimmed = line.trim();
+            if trimmed.starts_with('[') && trimmed.ends_with(']') {
+                section = trimmed[1..trimmed.len() - 1].to_string();
                 continue;
             }
 
-            match section.as_str() {
-                "General" => {
-                    if let Some(general) = general.as_mut() {
-                        general.parse(&line)?;
-                    }
+            sections.line(i + 1, &section, &line)?;
+        }
+
+        sections.visit(&mut beatmap);
+
+        Ok(beatmap)
+    }
+
+    /// Decodes an already parsed [`Document`] with [`Strictness::Strict`], so that it can be both
+    /// inspected through a [`Visitor`] and edited and written back out losslessly.
+    #[inline]
+    pub fn parse_document<Vis>(self, beatmap: Vis, document: &Document) -> Result<Vis, Error>
+    where
+        Vis: Visitor,
+    {
+        self.parse_document_with(Strictness::Strict, beatmap, document)
+    }
+
+    /// Decodes an already parsed [`Document`], handling malformed lines according to
+    /// `strictness`.
+    pub fn parse_document_with<Vis>(
+        self,
+        strictness: Strictness,
+        mut beatmap: Vis,
+        document: &Document,
+    ) -> Result<Vis, Error>
+    where
+        Vis: Visitor,
+    {
+        let mut sections = Sections::new(self, strictness);
+
+        let mut number = 0;
+
+        for line in &document.preamble {
+            number += 1;
+
+            if let Some(version) = parse_file_format_version(&line.text) {
+                match version {
+                    Ok(version) => beatmap.visit_file_format_version(version),
+                    Err(e) => sections.error(e.at_line(number, "", &line.text))?,
                 }
-                "Editor" => {
-                    if let Some(editor) = editor.as_mut() {
-                        editor.parse(&line)?;
-                    }
+            }
+        }
+
+        for section in &document.sections {
+            let name = section.name();
+            number += 1; // section header
+
+            for line in &section.lines {
+                number += 1;
+
+                if line.text.is_empty() || line.is_comment() {
+                    continue;
                 }
-                "Metadata" => {
-                    if let Some(metadata) = metadata.as_mut() {
-                        metadata.parse(&line)?;
-                    }
+
+                sections.line(number, name, &line.text)?;
+            }
+        }
+
+        sections.visit(&mut beatmap);
+
+        Ok(beatmap)
+    }
+}
+
+#[inline]
+fn parse_file_format_version(line: &str) -> Option<Result<u32, Error>> {
+    line.trim_start_matches('\u{feff}')
+        .strip_prefix("osu file format v")
+        .map(|version| version.trim().parse().map_err(Error::from))
+}
+
+/// Attaches the key and the column of the value of a `Key: Value` line to an error.
+fn in_key_value(e: Error, line: &str) -> Error {
+    match line.split_once(':') {
+        Some((key, value)) => e
+            .in_field(key.trim())
+            .at_column(line.len() - value.trim_start().len() + 1),
+        None => e,
+    }
+}
+
+/// Sections being accumulated by [`BeatmapDecoder`]; [`None`] for the ones it skips.
+struct Sections {
+    strictness: Strictness,
+    diagnostics: Vec<Diagnostic>,
+    has_approach_rate: bool,
+    general: Option<General>,
+    editor: Option<Editor>,
+    metadata: Option<Metadata>,
+    difficulty: Option<Difficulty>,
+    events: Option<Vec<EventKind>>,
+    storyboard: Option<Storyboard>,
+    variables: Variables,
+    timing_points: Option<Vec<TimingPoint>>,
+    colours: Option<Colours>,
+    hit_objects: Option<Vec<HitObject>>,
+}
+
+impl Sections {
+    fn new(decoder: BeatmapDecoder, strictness: Strictness) -> Self {
+        Self {
+            strictness,
+            diagnostics: Vec::new(),
+            has_approach_rate: false,
+            general: decoder
+                .contains(BeatmapDecoder::GENERAL)
+                .then(General::default),
+            editor: decoder
+                .contains(BeatmapDecoder::EDITOR)
+                .then(Editor::default),
+            metadata: decoder
+                .contains(BeatmapDecoder::METADATA)
+                .then(Metadata::default),
+            difficulty: decoder
+                .contains(BeatmapDecoder::DIFFICULTY)
+                .then(Difficulty::default),
+            events: decoder
+                .contains(BeatmapDecoder::EVENTS)
+                .then(|| Vec::with_capacity(8)),
+            storyboard: decoder
+                .contains(BeatmapDecoder::STORYBOARD)
+                .then(Storyboard::default),
+            variables: Variables::default(),
+            timing_points: decoder
+                .contains(BeatmapDecoder::TIMING_POINTS)
+                .then(|| Vec::with_capacity(128)),
+            colours: decoder
+                .contains(BeatmapDecoder::COLOURS)
+                .then(Colours::default),
+            hit_objects: decoder
+                .contains(BeatmapDecoder::HIT_OBJECTS)
+                .then(|| Vec::with_capacity(1024)),
+        }
+    }
+
+    /// Whether lines of `section` are decoded at all.
+    fn wants(&self, section: &str) -> bool {
+        match section {
+            "General" => self.general.is_some(),
+            "Editor" => self.editor.is_some(),
+            "Metadata" => self.metadata.is_some(),
+            "Difficulty" => self.difficulty.is_some(),
+            "Events" => self.events.is_some() || self.storyboard.is_some(),
+            "Variables" => self.storyboard.is_some(),
+            "TimingPoints" => self.timing_points.is_some(),
+            "Colours" => self.colours.is_some(),
+            "HitObjects" => self.hit_objects.is_some(),
+            _ => false,
+        }
+    }
+
+    fn line(&mut self, number: usize, section: &str, line: &str) -> Result<(), Error> {
+        if !self.wants(section) {
+            return Ok(());
+        }
+
+        let normalized = if self.strictness == Strictness::OsuCompatible {
+            let mut fallbacks = Vec::new();
+            let normalized = compat::normalize(section, line, &mut fallbacks);
+
+            self.diagnostics
+                .extend(fallbacks.into_iter().map(|fallback| Diagnostic {
+                    line: Some(number),
+                    column: None,
+                    section: section.to_string(),
+                    text: line.to_string(),
+                    field: Some(fallback.field.to_string()),
+                    message: fallback.message,
+                    resolution: Resolution::Fallback,
+                }));
+
+            normalized
+        } else {
+            Cow::Borrowed(line)
+        };
+
+        match self.parse_line(section, &normalized) {
+            Ok(()) => Ok(()),
+            Err(e) => self.error(e.at_line(number, section, line)),
+        }
+    }
+
+    /// Fails with `e` when decoding strictly, otherwise records it and carries on.
+    fn error(&mut self, e: Error) -> Result<(), Error> {
+        match self.strictness {
+            Strictness::Strict => Err(e),
+            Strictness::Lenient | Strictness::OsuCompatible => {
+                self.diagnostics.push(Diagnostic::skipped(&e));
+                Ok(())
+            }
+        }
+    }
+
+    fn parse_line(&mut self, section: &str, line: &str) -> Result<(), Error> {
+        match section {
+            "General" => {
+                if let Some(general) = self.general.as_mut() {
+                    general.parse(line).map_err(|e| in_key_value(e, line))?;
                 }
-                "Difficulty" => {
-                    if let Some(difficulty) = difficulty.as_mut() {
-                        difficulty.parse(&line)?;
-                    }
+            }
+            "Editor" => {
+                if let Some(editor) = self.editor.as_mut() {
+                    editor.parse(line).map_err(|e| in_key_value(e, line))?;
                 }
-                "TimingPoints" => {
-                    if let Some(timing_points) = timing_points.as_mut() {
-                        timing_points.push(TimingPoint::from_str(&line)?);
-                    }
+            }
+            "Metadata" => {
+                if let Some(metadata) = self.metadata.as_mut() {
+                    metadata.parse(line).map_err(|e| in_key_value(e, line))?;
                 }
-                "Events" => {
-                    if let Some(events) = events.as_mut() {
-                        if let Ok(event) = EventKind::from_str(&line) {
-                            events.push(event);
-                        }
+            }
+            "Difficulty" => {
+                if let Some(difficulty) = self.difficulty.as_mut() {
+                    difficulty.parse(line).map_err(|e| in_key_value(e, line))?;
+
+                    if line.split_once(':').map(|(key, _)| key.trim()) == Some("ApproachRate") {
+                        self.has_approach_rate = true;
                     }
                 }
-                "Colours" => {
-                    if let Some(colours) = colours.as_mut() {
-                        colours.parse(&line)?;
-                    }
+            }
+            "TimingPoints" => {
+                if let Some(timing_points) = self.timing_points.as_mut() {
+                    timing_points.push(TimingPoint::from_str(line)?);
                 }
-                "HitObjects" => {
-                    if let Some(hit_objects) = hit_objects.as_mut() {
-                        hit_objects.push(HitObject::from_str(&line)?);
+            }
+            "Variables" => self.variables.parse(line)?,
+            "Events" => {
+                let line = &self.variables.substitute(line);
+                if Storyboard::is_storyboard_line(line) {
+                    if let Some(storyboard) = self.storyboard.as_mut() {
+                        storyboard.parse_line(line)?;
+                    }
+                } else if let Some(events) = self.events.as_mut() {
+                    // legacy background colour events and the like are ignored
+                    let ty = line.split(',').next().map(str::trim);
+                    if matches!(ty, Some("0" | "1" | "Video" | "2" | "Break")) {
+                        events.push(EventKind::from_str(line)?);
                     }
                 }
-                _ => (),
             }
+            "Colours" => {
+                if let Some(colours) = self.colours.as_mut() {
+                    colours.parse(line).map_err(|e| in_key_value(e, line))?;
+                }
+            }
+            "HitObjects" => {
+                if let Some(hit_objects) = self.hit_objects.as_mut() {
+                    hit_objects.push(HitObject::from_str(line)?);
+                }
+            }
+            _ => (),
         }
 
-        if let Some(general) = general {
+        Ok(())
+    }
+
+    fn visit<Vis: Visitor>(mut self, beatmap: &mut Vis) {
+        if self.strictness == Strictness::OsuCompatible && !self.has_approach_rate {
+            // beatmaps older than v8 have no approach rate, osu! uses the overall difficulty
+            if let Some(difficulty) = self.difficulty.as_mut() {
+                difficulty.ar = difficulty.od;
+                self.diagnostics.push(Diagnostic {
+                    line: None,
+                    column: None,
+                    section: "Difficulty".to_string(),
+                    text: String::new(),
+                    field: Some("ApproachRate".to_string()),
+                    message: format!("missing, using overall difficulty {}", difficulty.od),
+                    resolution: Resolution::Fallback,
+                });
+            }
+        }
+
+        if let Some(general) = self.general {
             beatmap.visit_general(general);
         }
-        if let Some(editor) = editor {
+        if let Some(editor) = self.editor {
             beatmap.visit_editor(editor);
         }
-        if let Some(metadata) = metadata {
+        if let Some(metadata) = self.metadata {
             beatmap.visit_metadata(metadata);
         }
-        if let Some(difficulty) = difficulty {
+        if let Some(difficulty) = self.difficulty {
             beatmap.visit_difficulty(difficulty);
         }
-        if let Some(events) = events {
+        if let Some(events) = self.events {
             beatmap.visit_events(events.into_iter());
         }
-        if let Some(timing_points) = timing_points {
+        if let Some(storyboard) = self.storyboard {
+            beatmap.visit_storyboard(storyboard);
+        }
+        if let Some(timing_points) = self.timing_points {
             beatmap.visit_timing_points(timing_points.into_iter());
         }
-        if let Some(colours) = colours {
+        if let Some(colours) = self.colours {
             beatmap.visit_colours(colours);
         }
-        if let Some(hit_objects) = hit_objects {
+        if let Some(hit_objects) = self.hit_objects {
             beatmap.visit_hit_objects(hit_objects.into_iter());
         }
-
-        Ok(beatmap)
+        beatmap.visit_diagnostics(self.diagnostics.into_iter());
     }
 }
 
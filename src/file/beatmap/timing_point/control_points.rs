@@ -0,0 +1,239 @@
+use crate::{file::beatmap::sample_set::SampleSet, time::Time};
+
+use super::{Effects, TimingPoint};
+
+/// Sample settings active at a given time, see [`ControlPoints::sample_at`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SamplePoint {
+    /// Default sample set for hit objects ([`None`] = beatmap default).
+    pub sample_set: Option<SampleSet>,
+    /// Custom sample index for hit objects. [`None`] indicates osu!'s default hitsounds.
+    pub sample_index: Option<i32>,
+    /// Volume percentage for hit objects.
+    pub volume: i32,
+}
+
+impl Default for SamplePoint {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            sample_set: None,
+            sample_index: None,
+            volume: 100,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct TimingEntry {
+    beat_length: f64,
+    meter: i32,
+}
+
+/// Index of the timing points of a beatmap, answering "what is active at time t" lookups in
+/// O(log n).
+///
+/// Each property follows the same rules as osu!:
+///
+/// - beat length and meter come from uninherited timing points only, and the first uninherited
+///   timing point also applies before its own time;
+/// - slider velocity is reset to 1 by uninherited timing points and by inherited ones whose beat
+///   length is not negative, and is 1 before the first timing point, and so is the legacy BPM
+///   multiplier;
+/// - effects and sample settings come from every timing point, and the sample settings of the
+///   first timing point also apply before its own time.
+///
+/// When several timing points share the same time, the last inherited one wins over the
+/// uninherited ones, and the first uninherited one wins over the others.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ControlPoints {
+    timing: Vec<(Time, TimingEntry)>,
+    velocity: Vec<(Time, f64)>,
+    bpm_multipliers: Vec<(Time, f64)>,
+    effects: Vec<(Time, Effects)>,
+    samples: Vec<(Time, SamplePoint)>,
+}
+
+impl ControlPoints {
+    /// Beat length used when a beatmap has no uninherited timing point.
+    pub const DEFAULT_BEAT_LENGTH: f64 = 1000.0;
+
+    pub fn new(timing_points: &[TimingPoint]) -> Self {
+        let mut sorted = timing_points.iter().collect::<Vec<_>>();
+        sorted.sort_by(|a, b| a.time().total_cmp(&b.time()));
+
+        let mut control_points = Self::default();
+
+        for timing_point in sorted {
+            let time = timing_point.time();
+
+            if let TimingPoint::Uninherited(t) = timing_point {
+                push(
+                    &mut control_points.timing,
+                    time,
+                    TimingEntry {
+                        beat_length: t.beat_length.clamp(6.0, 60_000.0),
+                        meter: t.meter,
+                    },
+                    false,
+                );
+            }
+
+            // inherited points with a beat length that is not negative have a velocity of 0 or
+            // less (or NaN), which osu! treats as the default velocity
+            let slider_velocity = timing_point.slider_velocity();
+            let slider_velocity = if slider_velocity > 0.0 && slider_velocity.is_finite() {
+                slider_velocity.clamp(0.1, 10.0)
+            } else {
+                1.0
+            };
+
+            let bpm_multiplier: f32 = match timing_point {
+                // computed in single precision, like osu!stable
+                TimingPoint::Inherited(t) if t.beat_length() < 0.0 => {
+                    ((-t.beat_length()) as f32).clamp(10.0, 10_000.0) / 100.0
+                }
+                _ => 1.0,
+            };
+
+            let inherited = timing_point.inherited();
+            push(
+                &mut control_points.velocity,
+                time,
+                slider_velocity,
+                inherited,
+            );
+            push(
+                &mut control_points.bpm_multipliers,
+                time,
+                bpm_multiplier as f64,
+                inherited,
+            );
+            push(
+                &mut control_points.effects,
+                time,
+                timing_point.effects(),
+                inherited,
+            );
+
+            push(
+                &mut control_points.samples,
+                time,
+                SamplePoint {
+                    sample_set: timing_point.sample_set(),
+                    sample_index: timing_point.sample_index(),
+                    volume: timing_point.volume(),
+                },
+                inherited,
+            );
+        }
+
+        control_points
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.velocity.is_empty()
+    }
+
+    /// Duration of a beat in milliseconds at `time`.
+    #[inline]
+    pub fn beat_length_at(&self, time: Time) -> f64 {
+        self.timing_at(time)
+            .map_or(Self::DEFAULT_BEAT_LENGTH, |t| t.beat_length)
+    }
+
+    /// Amount of beats in a measure at `time`.
+    #[inline]
+    pub fn meter_at(&self, time: Time) -> i32 {
+        self.timing_at(time).map_or(4, |t| t.meter)
+    }
+
+    /// Slider velocity multiplier at `time`, between 0.1 and 10.
+    #[inline]
+    pub fn slider_velocity_at(&self, time: Time) -> f64 {
+        at(&self.velocity, time).copied().unwrap_or(1.0)
+    }
+
+    /// Multiplier osu!stable applies to the beat length at `time` when converting sliders, between
+    /// 0.1 and 100.
+    ///
+    /// It is the inverse of the slider velocity, but computed from the beat length of inherited
+    /// timing points with a wider range, so that sections slower than 0.1x keep their speed.
+    #[inline]
+    pub fn legacy_bpm_multiplier_at(&self, time: Time) -> f64 {
+        at(&self.bpm_multipliers, time).copied().unwrap_or(1.0)
+    }
+
+    /// Beat length at `time` multiplied by the [legacy BPM
+    /// multiplier][Self::legacy_bpm_multiplier_at], which osu!stable uses to time converted
+    /// sliders.
+    #[inline]
+    pub fn legacy_beat_length_at(&self, time: Time) -> f64 {
+        self.beat_length_at(time) * self.legacy_bpm_multiplier_at(time)
+    }
+
+    #[inline]
+    pub fn effects_at(&self, time: Time) -> Effects {
+        at(&self.effects, time).copied().unwrap_or(Effects::empty())
+    }
+
+    #[inline]
+    pub fn kiai_at(&self, time: Time) -> bool {
+        self.effects_at(time).contains(Effects::KIAI)
+    }
+
+    /// Sample settings at `time`, those of the first timing point applying before it.
+    #[inline]
+    pub fn sample_at(&self, time: Time) -> SamplePoint {
+        at(&self.samples, time)
+            .or_else(|| self.samples.first().map(|(_, s)| s))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Returns the time of the uninherited timing point active at `time`, which is where its
+    /// beats and measures start.
+    #[inline]
+    pub fn timing_start_at(&self, time: Time) -> Option<Time> {
+        let index = self.timing.partition_point(|(t, _)| *t <= time);
+        index
+            .checked_sub(1)
+            .or((!self.timing.is_empty()).then_some(0))
+            .map(|i| self.timing[i].0)
+    }
+
+    #[inline]
+    fn timing_at(&self, time: Time) -> Option<&TimingEntry> {
+        at(&self.timing, time).or_else(|| self.timing.first().map(|(_, t)| t))
+    }
+}
+
+impl From<&[TimingPoint]> for ControlPoints {
+    #[inline]
+    fn from(timing_points: &[TimingPoint]) -> Self {
+        Self::new(timing_points)
+    }
+}
+
+/// Appends a control point, or replaces the previous one if it is at the same time and
+/// `overrides` is set.
+#[inline]
+fn push<T>(entries: &mut Vec<(Time, T)>, time: Time, value: T, overrides: bool) {
+    match entries.last_mut() {
+        Some((t, previous)) if *t == time => {
+            if overrides {
+                *previous = value;
+            }
+        }
+        _ => entries.push((time, value)),
+    }
+}
+
+/// Returns the value of the last control point at or before `time`.
+#[inline]
+fn at<T>(entries: &[(Time, T)], time: Time) -> Option<&T> {
+    let index = entries.partition_point(|(t, _)| *t <= time);
+    index.checked_sub(1).map(|i| &entries[i].1)
+}
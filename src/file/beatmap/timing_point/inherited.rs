@@ -1,6 +1,4 @@
-use std::time::Duration;
-
-use crate::file::beatmap::sample_set::SampleSet;
+use crate::{file::beatmap::sample_set::SampleSet, time::Time};
 
 use super::Effects;
 
@@ -9,9 +7,9 @@ use super::Effects;
 pub struct InheritedTimingPoint {
     /// Start time of the timing section, in milliseconds from the beginning of the beatmap's audio.
     /// The end of the timing section is the next timing point's time (or never, if this is the last timing point).
-    pub time: Duration,
+    pub time: Time,
     /// Slider velocity multiplier.
-    pub slider_velocity: f32,
+    pub slider_velocity: f64,
     /// Default sample set for hit objects ([`None`] = beatmap default).
     #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub sample_set: Option<SampleSet>,
@@ -23,3 +21,11 @@ pub struct InheritedTimingPoint {
     /// Bit flags that give the timing point extra effects.
     pub effects: Effects,
 }
+
+impl InheritedTimingPoint {
+    /// Returns the negative inverse slider velocity percentage, as stored in the `.osu` file.
+    #[inline]
+    pub fn beat_length(&self) -> f64 {
+        -100.0 / self.slider_velocity
+    }
+}
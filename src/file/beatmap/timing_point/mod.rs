@@ -1,13 +1,14 @@
-use std::{fmt, str::FromStr, time::Duration};
+use std::{fmt, io, str::FromStr};
 
 use bitflags::bitflags;
 use inherited::InheritedTimingPoint;
 use uninherited::UninheritedTimingPoint;
 
-use crate::Error;
+use crate::{iter::Fields, time::Time, Error};
 
 use super::sample_set::SampleSet;
 
+pub mod control_points;
 pub mod inherited;
 pub mod uninherited;
 
@@ -29,7 +30,7 @@ pub enum TimingPoint {
 
 impl TimingPoint {
     #[inline]
-    pub const fn time(&self) -> Duration {
+    pub const fn time(&self) -> Time {
         match self {
             TimingPoint::Uninherited(t) => t.time,
             TimingPoint::Inherited(t) => t.time,
@@ -37,7 +38,7 @@ impl TimingPoint {
     }
 
     #[inline]
-    pub const fn slider_velocity(&self) -> f32 {
+    pub const fn slider_velocity(&self) -> f64 {
         match self {
             TimingPoint::Uninherited(_) => 1.0,
             TimingPoint::Inherited(t) => t.slider_velocity,
@@ -85,81 +86,74 @@ impl TimingPoint {
     pub const fn inherited(&self) -> bool {
         matches!(self, TimingPoint::Inherited(_))
     }
+
+    /// Writes the timing point as a single `[TimingPoints]` line.
+    ///
+    /// Inherited timing points do not store a meter, so the one passed in (usually the meter of
+    /// the preceding uninherited timing point) is written in its place.
+    pub fn write(&self, w: &mut impl io::Write, meter: i32) -> io::Result<()> {
+        let (beat_length, meter) = match self {
+            TimingPoint::Uninherited(t) => (t.beat_length, t.meter),
+            TimingPoint::Inherited(t) => (t.beat_length(), meter),
+        };
+
+        writeln!(
+            w,
+            "{},{},{},{},{},{},{},{}",
+            self.time(),
+            beat_length,
+            meter,
+            self.sample_set().map_or(0, |s| s as i32),
+            self.sample_index().unwrap_or(0),
+            self.volume(),
+            self.uninherited() as u8,
+            self.effects().bits()
+        )
+    }
 }
 
 impl FromStr for TimingPoint {
     type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut parts = s.split(',').map(str::trim);
-
-        let time = parts
-            .next()
-            .ok_or(Error::InvalidData(
-                "expected time while parsing timing point",
-            ))
-            .and_then(|s| s.parse().map_err(Error::from))
-            .map(Duration::from_millis)?;
-
-        let beat_length = parts
-            .next()
-            .ok_or(Error::InvalidData(
-                "expected beat length while parsing timing point",
-            ))
-            .and_then(|s| s.parse::<f32>().map_err(Error::from))?;
-
-        let meter = parts
-            .next()
-            .ok_or(Error::InvalidData(
-                "expected meter while parsing timing point",
-            ))
-            .and_then(|s| s.parse::<i32>().map_err(Error::from))?;
-
-        let sample_set = parts
-            .next()
-            .ok_or(Error::InvalidData(
-                "expected sample set while parsing timing point",
-            ))
-            .and_then(|s| s.parse::<i32>().map_err(Error::from))
-            .and_then(|i| {
-                Ok(match i {
-                    0 => None, // beatmap default
-                    _ => Some(SampleSet::try_from(i)?),
-                })
-            })?;
-
-        let sample_index = parts
-            .next()
-            .ok_or(Error::InvalidData(
-                "expected sample index while parsing timing point",
-            ))
-            .and_then(|s| s.parse::<i32>().map_err(Error::from))
-            .map(|i| match i {
-                0 => None,
-                _ => Some(i),
-            })?;
-
-        let volume = parts
-            .next()
-            .ok_or(Error::InvalidData(
-                "expected volume while parsing timing point",
-            ))
-            .and_then(|s| s.parse::<i32>().map_err(Error::from))?;
+        let mut parts = Fields::new(s, ',');
+
+        let time: Time = parts.parse_field("time", "expected time while parsing timing point")?;
+
+        let beat_length: f64 = parts.parse_field(
+            "beatLength",
+            "expected beat length while parsing timing point",
+        )?;
+
+        let meter = parts.parse_field("meter", "expected meter while parsing timing point")?;
+
+        let sample_set = match parts.parse_field(
+            "sampleSet",
+            "expected sample set while parsing timing point",
+        )? {
+            0 => None, // beatmap default
+            i => Some(SampleSet::try_from(i).map_err(|e| parts.error("sampleSet", e))?),
+        };
+
+        let sample_index = match parts.parse_field(
+            "sampleIndex",
+            "expected sample index while parsing timing point",
+        )? {
+            0 => None,
+            i => Some(i),
+        };
+
+        let volume = parts.parse_field("volume", "expected volume while parsing timing point")?;
 
         let uninherited = parts
-            .next()
-            .ok_or(Error::InvalidData(
+            .parse_field::<u8>(
+                "uninherited",
                 "expected uninherited while parsing timing point",
-            ))
-            .and_then(|s| s.parse::<u8>().map_err(Error::from))
+            )
             .map(|i| i == 1)?;
 
         let effects = parts
-            .next()
-            .ok_or(Error::InvalidData(
-                "expected effects while parsing timing point",
-            ))
-            .and_then(|s| s.parse::<u8>().map_err(Error::from))
+            .parse_field("effects", "expected effects while parsing timing point")
             .map(Effects::from_bits_truncate)?;
 
         Ok(if uninherited {
@@ -173,7 +167,7 @@ impl FromStr for TimingPoint {
                 effects,
             })
         } else {
-            let slider_velocity = beat_length / -25.0;
+            let slider_velocity = -100.0 / beat_length;
 
             TimingPoint::Inherited(InheritedTimingPoint {
                 time,
@@ -1,6 +1,4 @@
-use std::time::Duration;
-
-use crate::file::beatmap::sample_set::SampleSet;
+use crate::{file::beatmap::sample_set::SampleSet, time::Time};
 
 use super::Effects;
 
@@ -9,9 +7,9 @@ use super::Effects;
 pub struct UninheritedTimingPoint {
     /// Start time of the timing section, in milliseconds from the beginning of the beatmap's audio.
     /// The end of the timing section is the next timing point's time (or never, if this is the last timing point).
-    pub time: Duration,
+    pub time: Time,
     /// The duration of a beat, in milliseconds.
-    pub beat_length: f32,
+    pub beat_length: f64,
     /// Amount of beats in a measure. Inherited timing points ignore this property.
     pub meter: i32,
     /// Default sample set for hit objects ([`None`] = beatmap default).
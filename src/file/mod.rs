@@ -1 +1,3 @@
 pub mod beatmap;
+pub mod replay;
+pub mod storyboard;
@@ -0,0 +1,86 @@
+use std::{fmt, str::FromStr};
+
+use bitflags::bitflags;
+
+use crate::{iter::Fields, time::Time, Error};
+
+/// Time delta of the frame osu!stable appends to replays to store the seed of its random
+/// generator in the keys.
+pub const SEED_FRAME_TIME_DELTA: Time = Time(-12345.0);
+
+bitflags! {
+    /// Buttons held during a frame.
+    ///
+    /// The bits are those of osu!standard; osu!taiko and osu!catch reuse them for their own
+    /// keys, and osu!mania stores its held columns in the x coordinate of the frame instead.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct Keys: u32 {
+        const M1 = 1 << 0;
+        const M2 = 1 << 1;
+        const K1 = 1 << 2;
+        const K2 = 1 << 3;
+        const SMOKE = 1 << 4;
+    }
+}
+
+/// State of the cursor and keys at one point of a replay.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReplayFrame {
+    /// Time since the previous frame.
+    pub time_delta: Time,
+    /// Cursor position in osu! pixels.
+    pub x: f32,
+    pub y: f32,
+    pub keys: Keys,
+}
+
+impl ReplayFrame {
+    /// Returns `true` for the last frame of replays set since 2013, which stores the seed of the
+    /// random generator used during play in its keys.
+    #[inline]
+    pub fn is_seed(&self) -> bool {
+        self.time_delta == SEED_FRAME_TIME_DELTA
+    }
+}
+
+impl FromStr for ReplayFrame {
+    type Err = Error;
+
+    /// Attempts to convert a string slice into a ReplayFrame.
+    ///
+    /// The format for a frame is as follows: `timeDelta|x|y|keys`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = Fields::new(s, '|');
+        let time_delta = parts.parse_field(
+            "timeDelta",
+            "expected time delta while parsing replay frame",
+        )?;
+        let x = parts.parse_field("x", "expected x while parsing replay frame")?;
+        let y = parts.parse_field("y", "expected y while parsing replay frame")?;
+        // the keys are written as a float by some clients
+        let keys: f64 = parts.parse_field("keys", "expected keys while parsing replay frame")?;
+
+        Ok(Self {
+            time_delta,
+            x,
+            y,
+            keys: Keys::from_bits_retain(keys as i32 as u32),
+        })
+    }
+}
+
+impl fmt::Display for ReplayFrame {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}|{}|{}|{}",
+            self.time_delta,
+            self.x,
+            self.y,
+            self.keys.bits() as i32
+        )
+    }
+}
@@ -0,0 +1,35 @@
+use std::{fmt, str::FromStr};
+
+use crate::{iter::Fields, time::Time, Error};
+
+/// Amount of health at one point of a replay, sampled by osu!stable every few seconds.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LifeBarPoint {
+    /// Time since the start of the beatmap's audio.
+    pub time: Time,
+    /// Health between 0 and 1.
+    pub life: f32,
+}
+
+impl FromStr for LifeBarPoint {
+    type Err = Error;
+
+    /// Attempts to convert a string slice into a LifeBarPoint.
+    ///
+    /// The format for a point is as follows: `time|life`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = Fields::new(s, '|');
+        let time = parts.parse_field("time", "expected time while parsing life bar point")?;
+        let life = parts.parse_field("life", "expected life while parsing life bar point")?;
+
+        Ok(Self { time, life })
+    }
+}
+
+impl fmt::Display for LifeBarPoint {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}|{}", self.time, self.life)
+    }
+}
@@ -0,0 +1,424 @@
+use std::{
+    fs::File,
+    io::{self, BufReader, Read},
+    path::Path,
+};
+
+use bitflags::bitflags;
+use frame::ReplayFrame;
+use life_bar::LifeBarPoint;
+
+use crate::{mode::Mode, mods::Mods, Error};
+
+pub mod frame;
+pub mod life_bar;
+
+/// First game version writing the online score ID as a 64-bit integer.
+const LONG_SCORE_ID_VERSION: i32 = 20140721;
+/// First game version writing the online score ID at all.
+const SCORE_ID_VERSION: i32 = 20121008;
+
+pub trait Visitor {
+    #[inline]
+    fn open(path: impl AsRef<Path>, decoder: ReplayDecoder) -> Result<Self, Error>
+    where
+        Self: Default,
+    {
+        decoder.parse(Self::default(), File::open(path)?)
+    }
+
+    #[inline]
+    fn visit_header(&mut self, header: ReplayHeader) {
+        let _ = header;
+    }
+
+    #[inline]
+    fn visit_life_bar(&mut self, life_bar: impl Iterator<Item = LifeBarPoint>) {
+        let _ = life_bar;
+    }
+
+    /// Called with the time the replay was set, in ticks of 100ns since 0001-01-01 UTC.
+    #[inline]
+    fn visit_timestamp(&mut self, timestamp: i64) {
+        let _ = timestamp;
+    }
+
+    #[inline]
+    fn visit_frames(&mut self, frames: impl Iterator<Item = ReplayFrame>) {
+        let _ = frames;
+    }
+
+    /// Called with the ID of the score on the osu! website, 0 if it was not submitted.
+    #[inline]
+    fn visit_online_score_id(&mut self, online_score_id: i64) {
+        let _ = online_score_id;
+    }
+
+    /// Called only for replays played with [`TARGET_PRACTICE`][Mods::TARGET_PRACTICE].
+    #[inline]
+    fn visit_target_practice_accuracy(&mut self, accuracy: f64) {
+        let _ = accuracy;
+    }
+}
+
+impl<T: Visitor> Visitor for &mut T {
+    #[inline]
+    fn visit_header(&mut self, header: ReplayHeader) {
+        T::visit_header(self, header)
+    }
+
+    #[inline]
+    fn visit_life_bar(&mut self, life_bar: impl Iterator<Item = LifeBarPoint>) {
+        T::visit_life_bar(self, life_bar)
+    }
+
+    #[inline]
+    fn visit_timestamp(&mut self, timestamp: i64) {
+        T::visit_timestamp(self, timestamp)
+    }
+
+    #[inline]
+    fn visit_frames(&mut self, frames: impl Iterator<Item = ReplayFrame>) {
+        T::visit_frames(self, frames)
+    }
+
+    #[inline]
+    fn visit_online_score_id(&mut self, online_score_id: i64) {
+        T::visit_online_score_id(self, online_score_id)
+    }
+
+    #[inline]
+    fn visit_target_practice_accuracy(&mut self, accuracy: f64) {
+        T::visit_target_practice_accuracy(self, accuracy)
+    }
+}
+
+impl<T: Visitor> Visitor for Box<T> {
+    #[inline]
+    fn visit_header(&mut self, header: ReplayHeader) {
+        T::visit_header(self, header)
+    }
+
+    #[inline]
+    fn visit_life_bar(&mut self, life_bar: impl Iterator<Item = LifeBarPoint>) {
+        T::visit_life_bar(self, life_bar)
+    }
+
+    #[inline]
+    fn visit_timestamp(&mut self, timestamp: i64) {
+        T::visit_timestamp(self, timestamp)
+    }
+
+    #[inline]
+    fn visit_frames(&mut self, frames: impl Iterator<Item = ReplayFrame>) {
+        T::visit_frames(self, frames)
+    }
+
+    #[inline]
+    fn visit_online_score_id(&mut self, online_score_id: i64) {
+        T::visit_online_score_id(self, online_score_id)
+    }
+
+    #[inline]
+    fn visit_target_practice_accuracy(&mut self, accuracy: f64) {
+        T::visit_target_practice_accuracy(self, accuracy)
+    }
+}
+
+/// Judgements of a score, as osu!stable counts them in every mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Judgements {
+    pub n300: u16,
+    pub n100: u16,
+    pub n50: u16,
+    /// Gekis, which are MAX judgements in osu!mania.
+    pub n_geki: u16,
+    /// Katus, which are 200s in osu!mania.
+    pub n_katu: u16,
+    pub n_miss: u16,
+}
+
+/// Information about the score at the start of a replay file.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReplayHeader {
+    pub mode: Mode,
+    /// Version of the game the replay was set on, as a `yyyymmdd` date.
+    pub game_version: i32,
+    pub beatmap_md5: String,
+    pub player_name: String,
+    pub replay_md5: String,
+    pub judgements: Judgements,
+    pub score: i32,
+    pub max_combo: u16,
+    /// Whether the score is a full combo.
+    pub perfect: bool,
+    pub mods: Mods,
+}
+
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Replay {
+    pub header: ReplayHeader,
+    pub life_bar: Vec<LifeBarPoint>,
+    /// Time the replay was set, in ticks of 100ns since 0001-01-01 UTC.
+    pub timestamp: i64,
+    pub frames: Vec<ReplayFrame>,
+    pub online_score_id: i64,
+    pub target_practice_accuracy: Option<f64>,
+}
+
+impl Replay {
+    /// Returns the seed of the random generator used during play, stored in the last frame.
+    #[inline]
+    pub fn seed(&self) -> Option<i32> {
+        self.frames
+            .iter()
+            .rev()
+            .find(|frame| frame.is_seed())
+            .map(|frame| frame.keys.bits() as i32)
+    }
+}
+
+impl Visitor for Replay {
+    #[inline]
+    fn visit_header(&mut self, header: ReplayHeader) {
+        self.header = header;
+    }
+
+    #[inline]
+    fn visit_life_bar(&mut self, life_bar: impl Iterator<Item = LifeBarPoint>) {
+        self.life_bar.extend(life_bar);
+    }
+
+    #[inline]
+    fn visit_timestamp(&mut self, timestamp: i64) {
+        self.timestamp = timestamp;
+    }
+
+    #[inline]
+    fn visit_frames(&mut self, frames: impl Iterator<Item = ReplayFrame>) {
+        self.frames.extend(frames);
+    }
+
+    #[inline]
+    fn visit_online_score_id(&mut self, online_score_id: i64) {
+        self.online_score_id = online_score_id;
+    }
+
+    #[inline]
+    fn visit_target_practice_accuracy(&mut self, accuracy: f64) {
+        self.target_practice_accuracy = Some(accuracy);
+    }
+}
+
+bitflags! {
+    /// Parts of a replay to decode besides the header, timestamp and online score ID, which are
+    /// always decoded.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct ReplayDecoder: u32 {
+        const LIFE_BAR = 1 << 0;
+        const FRAMES = 1 << 1;
+    }
+}
+
+impl ReplayDecoder {
+    /// Decodes only the parts that are always decoded, skipping the life bar and the frames.
+    #[inline]
+    pub const fn minimal() -> Self {
+        Self::empty()
+    }
+
+    #[inline]
+    pub const fn life_bar(self) -> Self {
+        self.union(ReplayDecoder::LIFE_BAR)
+    }
+
+    /// Decompresses and decodes the frames, which make up most of a replay.
+    #[inline]
+    pub const fn frames(self) -> Self {
+        self.union(ReplayDecoder::FRAMES)
+    }
+
+    /// Decodes a replay.
+    ///
+    /// This function will return [Err(Error::InvalidData)][Error::InvalidData] if the file is
+    /// truncated or its frames cannot be decompressed.
+    pub fn parse<Vis>(self, mut replay: Vis, data: impl Read) -> Result<Vis, Error>
+    where
+        Vis: Visitor,
+    {
+        let mut reader = Reader(BufReader::new(data));
+
+        let mode = Mode::try_from(reader.u8()? as i32)?;
+        let game_version = reader.i32()?;
+        let beatmap_md5 = reader.string()?;
+        let player_name = reader.string()?;
+        let replay_md5 = reader.string()?;
+        let judgements = Judgements {
+            n300: reader.u16()?,
+            n100: reader.u16()?,
+            n50: reader.u16()?,
+            n_geki: reader.u16()?,
+            n_katu: reader.u16()?,
+            n_miss: reader.u16()?,
+        };
+        let score = reader.i32()?;
+        let max_combo = reader.u16()?;
+        let perfect = reader.u8()? != 0;
+        let mods = Mods::from_legacy(reader.i32()? as u32);
+
+        replay.visit_header(ReplayHeader {
+            mode,
+            game_version,
+            beatmap_md5,
+            player_name,
+            replay_md5,
+            judgements,
+            score,
+            max_combo,
+            perfect,
+            mods,
+        });
+
+        let life_bar = reader.string()?;
+        if self.contains(ReplayDecoder::LIFE_BAR) {
+            let life_bar = life_bar
+                .split(',')
+                .filter(|point| !point.trim().is_empty())
+                .map(str::parse)
+                .collect::<Result<Vec<LifeBarPoint>, _>>()?;
+            replay.visit_life_bar(life_bar.into_iter());
+        }
+
+        replay.visit_timestamp(reader.i64()?);
+
+        let compressed_length = reader.i32()?.max(0) as u64;
+        if self.contains(ReplayDecoder::FRAMES) && compressed_length == 0 {
+            // some replays have no frame data at all, not even an empty lzma stream
+            replay.visit_frames(std::iter::empty());
+        } else if self.contains(ReplayDecoder::FRAMES) {
+            let mut compressed = (&mut reader.0).take(compressed_length);
+            let mut frames = Vec::new();
+            lzma_rs::lzma_decompress(&mut BufReader::new(&mut compressed), &mut frames)
+                .map_err(|_| Error::InvalidData("invalid lzma stream in replay"))?;
+            // the decompressor may stop before the end of the stream, which must be skipped
+            io::copy(&mut compressed, &mut io::sink())?;
+
+            let frames = String::from_utf8(frames)
+                .map_err(|_| Error::InvalidData("replay frames are not valid utf-8"))?;
+            let frames = frames
+                .split(',')
+                .filter(|frame| !frame.trim().is_empty())
+                .map(str::parse)
+                .collect::<Result<Vec<ReplayFrame>, _>>()?;
+            replay.visit_frames(frames.into_iter());
+        } else {
+            reader.skip(compressed_length)?;
+        }
+
+        let online_score_id = if game_version >= LONG_SCORE_ID_VERSION {
+            reader.i64()?
+        } else if game_version >= SCORE_ID_VERSION {
+            reader.i32()? as i64
+        } else {
+            0
+        };
+        replay.visit_online_score_id(online_score_id);
+
+        if mods.contains(Mods::TARGET_PRACTICE) {
+            replay.visit_target_practice_accuracy(reader.f64()?);
+        }
+
+        Ok(replay)
+    }
+}
+
+/// Reads the little-endian values and strings of osu!'s binary formats.
+struct Reader<R>(R);
+
+impl<R: Read> Reader<R> {
+    fn bytes<const N: usize>(&mut self) -> Result<[u8; N], Error> {
+        let mut bytes = [0; N];
+        self.0.read_exact(&mut bytes).map_err(truncated)?;
+        Ok(bytes)
+    }
+
+    #[inline]
+    fn u8(&mut self) -> Result<u8, Error> {
+        Ok(self.bytes::<1>()?[0])
+    }
+
+    #[inline]
+    fn u16(&mut self) -> Result<u16, Error> {
+        Ok(u16::from_le_bytes(self.bytes()?))
+    }
+
+    #[inline]
+    fn i32(&mut self) -> Result<i32, Error> {
+        Ok(i32::from_le_bytes(self.bytes()?))
+    }
+
+    #[inline]
+    fn i64(&mut self) -> Result<i64, Error> {
+        Ok(i64::from_le_bytes(self.bytes()?))
+    }
+
+    #[inline]
+    fn f64(&mut self) -> Result<f64, Error> {
+        Ok(f64::from_le_bytes(self.bytes()?))
+    }
+
+    /// Reads a string: a 0x00 byte for an empty one, or a 0x0b byte followed by its length as a
+    /// ULEB128 and its UTF-8 bytes.
+    ///
+    /// Like .NET's reader, which old replays were written for, invalid UTF-8 is decoded lossily.
+    fn string(&mut self) -> Result<String, Error> {
+        match self.u8()? {
+            0x00 => Ok(String::new()),
+            0x0b => {
+                let mut length = 0u64;
+                let mut shift = 0;
+                loop {
+                    let byte = self.u8()?;
+                    length |= ((byte & 0x7f) as u64) << shift;
+                    if byte & 0x80 == 0 {
+                        break;
+                    }
+                    shift += 7;
+                    if shift >= 64 {
+                        return Err(Error::InvalidData("invalid string length"));
+                    }
+                }
+
+                let mut bytes = Vec::new();
+                (&mut self.0).take(length).read_to_end(&mut bytes)?;
+                if bytes.len() as u64 != length {
+                    return Err(Error::InvalidData("unexpected end of replay"));
+                }
+
+                Ok(String::from_utf8(bytes)
+                    .unwrap_or_else(|e| String::from_utf8_lossy(e.as_bytes()).into_owned()))
+            }
+            _ => Err(Error::InvalidData("invalid string marker")),
+        }
+    }
+
+    fn skip(&mut self, length: u64) -> Result<(), Error> {
+        let skipped = io::copy(&mut (&mut self.0).take(length), &mut io::sink())?;
+        if skipped != length {
+            return Err(Error::InvalidData("unexpected end of replay"));
+        }
+        Ok(())
+    }
+}
+
+/// Reports reaching the end of the file early as invalid data rather than an IO error.
+#[inline]
+fn truncated(e: io::Error) -> Error {
+    match e.kind() {
+        io::ErrorKind::UnexpectedEof => Error::InvalidData("unexpected end of replay"),
+        _ => Error::Io(e),
+    }
+}
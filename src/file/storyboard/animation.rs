@@ -0,0 +1,125 @@
+use std::{fmt, path::PathBuf, str::FromStr};
+
+use nalgebra_glm::Vec2;
+
+use crate::{iter::Fields, time::Time, Error};
+
+use super::{command::Command, layer::Layer, origin::Origin};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LoopType {
+    #[default]
+    LoopForever = 0,
+    /// Stops on the last frame.
+    LoopOnce = 1,
+}
+
+impl FromStr for LoopType {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "LoopForever" | "0" => Ok(LoopType::LoopForever),
+            "LoopOnce" | "1" => Ok(LoopType::LoopOnce),
+            _ => Err(Error::InvalidInput("invalid loop type value")),
+        }
+    }
+}
+
+impl fmt::Display for LoopType {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            LoopType::LoopForever => "LoopForever",
+            LoopType::LoopOnce => "LoopOnce",
+        })
+    }
+}
+
+/// `Animation,layer,origin,"filepath",x,y,frameCount,frameDelay,looptype`
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Animation {
+    pub layer: Layer,
+    pub origin: Origin,
+    /// Location of the images relative to the beatmap directory, without the frame number, see
+    /// [`Animation::frame_filepath`].
+    pub filepath: PathBuf,
+    /// Position in osu! pixels, where the storyboard area is 640x480.
+    pub position: Vec2,
+    pub frame_count: u32,
+    /// Time each frame is shown for.
+    pub frame_delay: Time,
+    pub loop_type: LoopType,
+    pub commands: Vec<Command>,
+}
+
+impl Animation {
+    /// Returns the location of the image of frame `index`, `sample.png` becoming `sample0.png`,
+    /// `sample1.png`, ...
+    pub fn frame_filepath(&self, index: u32) -> PathBuf {
+        let mut name = self.filepath.file_stem().unwrap_or_default().to_owned();
+        name.push(index.to_string());
+        if let Some(extension) = self.filepath.extension() {
+            name.push(".");
+            name.push(extension);
+        }
+
+        self.filepath.with_file_name(name)
+    }
+}
+
+impl FromStr for Animation {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = Fields::new(s, ',');
+
+        let _ = parts.field("objectType", "expected type while parsing animation")?; // ignore the type
+
+        let layer = parts.parse_field("layer", "expected layer while parsing animation")?;
+        let origin = parts.parse_field("origin", "expected origin while parsing animation")?;
+        let filepath = parts.field("filepath", "expected filepath while parsing animation")?;
+        let filepath = PathBuf::from(filepath.trim_matches('"'));
+        let x = parts.parse_field("x", "expected x while parsing animation")?;
+        let y = parts.parse_field("y", "expected y while parsing animation")?;
+        let frame_count =
+            parts.parse_field("frameCount", "expected frame count while parsing animation")?;
+        let frame_delay =
+            parts.parse_field("frameDelay", "expected frame delay while parsing animation")?;
+        let loop_type = match parts.next() {
+            Some(loop_type) => loop_type.parse().map_err(|e| parts.error("looptype", e))?,
+            None => LoopType::default(),
+        };
+
+        Ok(Self {
+            layer,
+            origin,
+            filepath,
+            position: Vec2::new(x, y),
+            frame_count,
+            frame_delay,
+            loop_type,
+            commands: Vec::new(),
+        })
+    }
+}
+
+impl fmt::Display for Animation {
+    /// Formats the object line, without its commands.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Animation,{},{},\"{}\",{},{},{},{},{}",
+            self.layer,
+            self.origin,
+            self.filepath.display(),
+            self.position.x,
+            self.position.y,
+            self.frame_count,
+            self.frame_delay,
+            self.loop_type
+        )
+    }
+}
@@ -0,0 +1,476 @@
+use std::{fmt, io, str::FromStr};
+
+use nalgebra_glm::Vec2;
+
+use crate::{file::beatmap::colour::Colour, iter::Fields, time::Time, Error};
+
+use super::easing::Easing;
+
+/// A value changing from `start` to `end` between `start_time` and `end_time`.
+///
+/// Before `start_time` the value is `start`, and after `end_time` it stays `end`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Tween<T> {
+    pub easing: Easing,
+    pub start_time: Time,
+    pub end_time: Time,
+    pub start: T,
+    pub end: T,
+}
+
+impl<T> Tween<T> {
+    #[inline]
+    pub fn duration(&self) -> Time {
+        self.end_time - self.start_time
+    }
+}
+
+/// Flag toggled by a `P` command for the duration of the command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Parameter {
+    /// `H`
+    FlipHorizontal,
+    /// `V`
+    FlipVertical,
+    /// `A`, additive colour blending.
+    Additive,
+}
+
+impl FromStr for Parameter {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "H" => Ok(Parameter::FlipHorizontal),
+            "V" => Ok(Parameter::FlipVertical),
+            "A" => Ok(Parameter::Additive),
+            _ => Err(Error::InvalidInput("invalid parameter value")),
+        }
+    }
+}
+
+impl fmt::Display for Parameter {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Parameter::FlipHorizontal => "H",
+            Parameter::FlipVertical => "V",
+            Parameter::Additive => "A",
+        })
+    }
+}
+
+/// `P` command, applying a [`Parameter`] between `start_time` and `end_time`.
+///
+/// A command whose start and end times are equal applies the parameter from its start time
+/// until the end of the object's lifetime.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ParameterCommand {
+    pub easing: Easing,
+    pub start_time: Time,
+    pub end_time: Time,
+    pub parameter: Parameter,
+}
+
+/// `L` command, repeating its commands `loop_count` times starting at `start_time`.
+///
+/// Times of the nested commands are relative to the start of each iteration.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Loop {
+    pub start_time: Time,
+    pub loop_count: u32,
+    pub commands: Vec<Command>,
+}
+
+impl Loop {
+    /// Number of times the commands run, at least once.
+    #[inline]
+    pub fn iterations(&self) -> u32 {
+        self.loop_count.max(1)
+    }
+
+    /// Earliest start time of the nested commands, relative to the start of an iteration.
+    #[inline]
+    pub fn commands_start(&self) -> Time {
+        self.commands
+            .iter()
+            .map(Command::start_time)
+            .reduce(Time::min)
+            .unwrap_or_default()
+    }
+
+    /// Latest end time of the nested commands, relative to the start of an iteration.
+    #[inline]
+    pub fn commands_end(&self) -> Time {
+        self.commands
+            .iter()
+            .map(Command::end_time)
+            .reduce(Time::max)
+            .unwrap_or_default()
+    }
+
+    /// Time between the starts of two iterations.
+    #[inline]
+    pub fn iteration_duration(&self) -> Time {
+        self.commands_end() - self.commands_start()
+    }
+}
+
+/// `T` command, running its commands whenever `trigger` fires between `start_time` and
+/// `end_time`.
+///
+/// Times of the nested commands are relative to the moment the trigger fires.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Trigger {
+    /// Trigger name, like `Passing`, `Failing` or `HitSoundClap`.
+    pub trigger: String,
+    pub start_time: Time,
+    pub end_time: Time,
+    /// Triggers of the same group cancel each other when they fire.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub group: Option<i32>,
+    pub commands: Vec<Command>,
+}
+
+/// A command of a storyboard sprite or animation.
+///
+/// Commands written with more than one pair of values (`F,0,1000,1500,0,0.5,1`) are expanded
+/// into consecutive commands of the same duration when parsed.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Command {
+    /// `F`, opacity from 0 to 1.
+    Fade(Tween<f32>),
+    /// `M`
+    Move(Tween<Vec2>),
+    /// `MX`
+    MoveX(Tween<f32>),
+    /// `MY`
+    MoveY(Tween<f32>),
+    /// `S`
+    Scale(Tween<f32>),
+    /// `V`, scale along each axis.
+    VectorScale(Tween<Vec2>),
+    /// `R`, clockwise rotation in radians.
+    Rotate(Tween<f32>),
+    /// `C`
+    Colour(Tween<Colour>),
+    /// `P`
+    Parameter(ParameterCommand),
+    /// `L`
+    Loop(Loop),
+    /// `T`
+    Trigger(Trigger),
+}
+
+impl Command {
+    /// Returns the time of the first change made by the command.
+    #[inline]
+    pub fn start_time(&self) -> Time {
+        match self {
+            Command::Fade(t)
+            | Command::MoveX(t)
+            | Command::MoveY(t)
+            | Command::Scale(t)
+            | Command::Rotate(t) => t.start_time,
+            Command::Move(t) | Command::VectorScale(t) => t.start_time,
+            Command::Colour(t) => t.start_time,
+            Command::Parameter(p) => p.start_time,
+            Command::Loop(l) => l.start_time + l.commands_start(),
+            Command::Trigger(t) => t.start_time,
+        }
+    }
+
+    /// Returns the time of the last change made by the command.
+    #[inline]
+    pub fn end_time(&self) -> Time {
+        match self {
+            Command::Fade(t)
+            | Command::MoveX(t)
+            | Command::MoveY(t)
+            | Command::Scale(t)
+            | Command::Rotate(t) => t.end_time,
+            Command::Move(t) | Command::VectorScale(t) => t.end_time,
+            Command::Colour(t) => t.end_time,
+            Command::Parameter(p) => p.end_time,
+            Command::Loop(l) => {
+                l.start_time + l.commands_start() + l.iteration_duration() * l.iterations() as f64
+            }
+            Command::Trigger(t) => t.end_time,
+        }
+    }
+
+    /// Returns the nested commands of a loop or a trigger.
+    #[inline]
+    pub fn commands(&self) -> Option<&[Command]> {
+        match self {
+            Command::Loop(l) => Some(&l.commands),
+            Command::Trigger(t) => Some(&t.commands),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn commands_mut(&mut self) -> Option<&mut Vec<Command>> {
+        match self {
+            Command::Loop(l) => Some(&mut l.commands),
+            Command::Trigger(t) => Some(&mut t.commands),
+            _ => None,
+        }
+    }
+
+    /// Parses a command line, pushing every command it expands into to `commands`.
+    ///
+    /// The indentation of the line (spaces or underscores) is ignored.
+    pub(super) fn parse(s: &str, commands: &mut Vec<Command>) -> Result<(), Error> {
+        let mut parts = Fields::new(s, ',');
+
+        let event = parts
+            .field("event", "expected event while parsing command")?
+            .trim_start_matches(['_', ' ']);
+
+        match event {
+            "L" => {
+                let start_time = parts.parse_field(
+                    "startTime",
+                    "expected start time while parsing loop command",
+                )?;
+                let loop_count = parts.parse_field(
+                    "loopCount",
+                    "expected loop count while parsing loop command",
+                )?;
+
+                commands.push(Command::Loop(Loop {
+                    start_time,
+                    loop_count,
+                    commands: Vec::new(),
+                }));
+                return Ok(());
+            }
+            "T" => {
+                let trigger = parts
+                    .field(
+                        "triggerType",
+                        "expected trigger type while parsing trigger command",
+                    )?
+                    .to_string();
+                let start_time = parts.parse_field(
+                    "startTime",
+                    "expected start time while parsing trigger command",
+                )?;
+                let end_time = parts
+                    .parse_field("endTime", "expected end time while parsing trigger command")?;
+                let group = match parts.next() {
+                    Some(group) => Some(group.parse().map_err(|e| parts.error("groupNumber", e))?),
+                    None => None,
+                };
+
+                commands.push(Command::Trigger(Trigger {
+                    trigger,
+                    start_time,
+                    end_time,
+                    group,
+                    commands: Vec::new(),
+                }));
+                return Ok(());
+            }
+            "F" | "M" | "MX" | "MY" | "S" | "V" | "R" | "C" | "P" => (),
+            _ => {
+                return Err(parts.error("event", Error::InvalidData("invalid command event")));
+            }
+        }
+
+        let easing = parts.parse_field("easing", "expected easing while parsing command")?;
+        let start_time: Time =
+            parts.parse_field("startTime", "expected start time while parsing command")?;
+        let end_time = match parts.field("endTime", "expected end time while parsing command")? {
+            "" => start_time,
+            end_time => end_time.parse().map_err(|e| parts.error("endTime", e))?,
+        };
+
+        if event == "P" {
+            let parameter =
+                parts.parse_field("parameter", "expected parameter while parsing command")?;
+
+            commands.push(Command::Parameter(ParameterCommand {
+                easing,
+                start_time,
+                end_time,
+                parameter,
+            }));
+            return Ok(());
+        }
+
+        let tweens = Tweens {
+            easing,
+            start_time,
+            end_time,
+        };
+
+        match event {
+            "F" => tweens.parse(&mut parts, commands, |[v]: [f32; 1]| v, Command::Fade),
+            "M" => tweens.parse(
+                &mut parts,
+                commands,
+                |[x, y]: [f32; 2]| Vec2::new(x, y),
+                Command::Move,
+            ),
+            "MX" => tweens.parse(&mut parts, commands, |[v]: [f32; 1]| v, Command::MoveX),
+            "MY" => tweens.parse(&mut parts, commands, |[v]: [f32; 1]| v, Command::MoveY),
+            "S" => tweens.parse(&mut parts, commands, |[v]: [f32; 1]| v, Command::Scale),
+            "V" => tweens.parse(
+                &mut parts,
+                commands,
+                |[x, y]: [f32; 2]| Vec2::new(x, y),
+                Command::VectorScale,
+            ),
+            "R" => tweens.parse(&mut parts, commands, |[v]: [f32; 1]| v, Command::Rotate),
+            "C" => tweens.parse(
+                &mut parts,
+                commands,
+                |[r, g, b]: [u8; 3]| Colour(r, g, b),
+                Command::Colour,
+            ),
+            _ => unreachable!(),
+        }
+    }
+
+    /// Writes the command and its nested commands, indented by `depth` spaces.
+    pub(super) fn write(&self, w: &mut impl io::Write, depth: usize) -> io::Result<()> {
+        writeln!(w, "{:depth$}{}", "", self)?;
+
+        for command in self.commands().unwrap_or_default() {
+            command.write(w, depth + 1)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Easing and times shared by the commands a single line expands into.
+struct Tweens {
+    easing: Easing,
+    start_time: Time,
+    end_time: Time,
+}
+
+impl Tweens {
+    /// Parses the remaining fields as groups of `N` values, pushing a command for every pair of
+    /// consecutive groups, or a single constant command if there is only one group.
+    fn parse<V, T, const N: usize>(
+        &self,
+        parts: &mut Fields,
+        commands: &mut Vec<Command>,
+        value: impl Fn([V; N]) -> T,
+        command: impl Fn(Tween<T>) -> Command,
+    ) -> Result<(), Error>
+    where
+        V: FromStr + Copy + Default,
+        Error: From<V::Err>,
+        T: Copy,
+    {
+        let mut values = Vec::new();
+        let mut group = [V::default(); N];
+        let mut len = 0;
+
+        while let Some(field) = parts.next() {
+            group[len] = field.parse().map_err(|e| parts.error("value", e))?;
+            len += 1;
+
+            if len == N {
+                values.push(value(group));
+                len = 0;
+            }
+        }
+
+        if values.is_empty() || len != 0 {
+            return Err(parts.error(
+                "value",
+                Error::InvalidData("expected value while parsing command"),
+            ));
+        }
+
+        if let [value] = values[..] {
+            commands.push(command(Tween {
+                easing: self.easing,
+                start_time: self.start_time,
+                end_time: self.end_time,
+                start: value,
+                end: value,
+            }));
+            return Ok(());
+        }
+
+        let duration = self.end_time - self.start_time;
+
+        for (i, pair) in values.windows(2).enumerate() {
+            let offset = duration * i as f64;
+
+            commands.push(command(Tween {
+                easing: self.easing,
+                start_time: self.start_time + offset,
+                end_time: self.end_time + offset,
+                start: pair[0],
+                end: pair[1],
+            }));
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for Command {
+    /// Formats the command line itself, without indentation nor nested commands.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Command::Fade(t) => write_tween(f, "F", t, |f, v| write!(f, "{}", v)),
+            Command::Move(t) => write_tween(f, "M", t, |f, v| write!(f, "{},{}", v.x, v.y)),
+            Command::MoveX(t) => write_tween(f, "MX", t, |f, v| write!(f, "{}", v)),
+            Command::MoveY(t) => write_tween(f, "MY", t, |f, v| write!(f, "{}", v)),
+            Command::Scale(t) => write_tween(f, "S", t, |f, v| write!(f, "{}", v)),
+            Command::VectorScale(t) => write_tween(f, "V", t, |f, v| write!(f, "{},{}", v.x, v.y)),
+            Command::Rotate(t) => write_tween(f, "R", t, |f, v| write!(f, "{}", v)),
+            Command::Colour(t) => write_tween(f, "C", t, |f, v| write!(f, "{}", v)),
+            Command::Parameter(p) => write!(
+                f,
+                "P,{},{},{},{}",
+                p.easing, p.start_time, p.end_time, p.parameter
+            ),
+            Command::Loop(l) => write!(f, "L,{},{}", l.start_time, l.loop_count),
+            Command::Trigger(t) => {
+                write!(f, "T,{},{},{}", t.trigger, t.start_time, t.end_time)?;
+                if let Some(group) = t.group {
+                    write!(f, ",{}", group)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Writes a tween, leaving out the end value if it does not change.
+fn write_tween<T: PartialEq>(
+    f: &mut fmt::Formatter<'_>,
+    event: &str,
+    tween: &Tween<T>,
+    value: impl Fn(&mut fmt::Formatter<'_>, &T) -> fmt::Result,
+) -> fmt::Result {
+    write!(
+        f,
+        "{},{},{},{},",
+        event, tween.easing, tween.start_time, tween.end_time
+    )?;
+    value(f, &tween.start)?;
+
+    if tween.end != tween.start {
+        f.write_str(",")?;
+        value(f, &tween.end)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,232 @@
+use std::{fmt, str::FromStr};
+
+use crate::Error;
+
+/// Easing function of a storyboard [`Command`][super::command::Command], stored by its number in
+/// storyboard files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Easing {
+    #[default]
+    Linear = 0,
+    /// Starts fast and slows down, same as [`QuadOut`][Easing::QuadOut].
+    Out = 1,
+    /// Starts slow and speeds up, same as [`QuadIn`][Easing::QuadIn].
+    In = 2,
+    QuadIn = 3,
+    QuadOut = 4,
+    QuadInOut = 5,
+    CubicIn = 6,
+    CubicOut = 7,
+    CubicInOut = 8,
+    QuartIn = 9,
+    QuartOut = 10,
+    QuartInOut = 11,
+    QuintIn = 12,
+    QuintOut = 13,
+    QuintInOut = 14,
+    SineIn = 15,
+    SineOut = 16,
+    SineInOut = 17,
+    ExpoIn = 18,
+    ExpoOut = 19,
+    ExpoInOut = 20,
+    CircIn = 21,
+    CircOut = 22,
+    CircInOut = 23,
+    ElasticIn = 24,
+    ElasticOut = 25,
+    ElasticHalfOut = 26,
+    ElasticQuarterOut = 27,
+    ElasticInOut = 28,
+    BackIn = 29,
+    BackOut = 30,
+    BackInOut = 31,
+    BounceIn = 32,
+    BounceOut = 33,
+    BounceInOut = 34,
+}
+
+impl Easing {
+    /// Every easing, indexed by its number.
+    pub const ALL: [Easing; 35] = [
+        Easing::Linear,
+        Easing::Out,
+        Easing::In,
+        Easing::QuadIn,
+        Easing::QuadOut,
+        Easing::QuadInOut,
+        Easing::CubicIn,
+        Easing::CubicOut,
+        Easing::CubicInOut,
+        Easing::QuartIn,
+        Easing::QuartOut,
+        Easing::QuartInOut,
+        Easing::QuintIn,
+        Easing::QuintOut,
+        Easing::QuintInOut,
+        Easing::SineIn,
+        Easing::SineOut,
+        Easing::SineInOut,
+        Easing::ExpoIn,
+        Easing::ExpoOut,
+        Easing::ExpoInOut,
+        Easing::CircIn,
+        Easing::CircOut,
+        Easing::CircInOut,
+        Easing::ElasticIn,
+        Easing::ElasticOut,
+        Easing::ElasticHalfOut,
+        Easing::ElasticQuarterOut,
+        Easing::ElasticInOut,
+        Easing::BackIn,
+        Easing::BackOut,
+        Easing::BackInOut,
+        Easing::BounceIn,
+        Easing::BounceOut,
+        Easing::BounceInOut,
+    ];
+
+    #[inline]
+    pub fn from_i32(value: i32) -> Option<Self> {
+        usize::try_from(value)
+            .ok()
+            .and_then(|i| Self::ALL.get(i))
+            .copied()
+    }
+}
+
+impl Easing {
+    /// Maps the linear progress `t` of a command, from 0 to 1, to the eased progress, using the
+    /// same curves as osu!.
+    ///
+    /// The eased progress can leave the 0–1 range for elastic and back easings.
+    pub fn apply(&self, t: f64) -> f64 {
+        use std::f64::consts::PI;
+
+        const ELASTIC: f64 = 2.0 * PI / 0.3;
+        const ELASTIC_OFFSET: f64 = 0.3 / 4.0;
+        const BACK: f64 = 1.70158;
+        const BACK_IN_OUT: f64 = BACK * 1.525;
+
+        fn bounce_out(t: f64) -> f64 {
+            const B: f64 = 1.0 / 2.75;
+            if t < B {
+                7.5625 * t * t
+            } else if t < 2.0 * B {
+                let t = t - 1.5 * B;
+                7.5625 * t * t + 0.75
+            } else if t < 2.5 * B {
+                let t = t - 2.25 * B;
+                7.5625 * t * t + 0.9375
+            } else {
+                let t = t - 2.625 * B;
+                7.5625 * t * t + 0.984375
+            }
+        }
+
+        let t = t.clamp(0.0, 1.0);
+
+        match self {
+            Easing::Linear => t,
+            Easing::In | Easing::QuadIn => t * t,
+            Easing::Out | Easing::QuadOut => t * (2.0 - t),
+            Easing::QuadInOut if t < 0.5 => 2.0 * t * t,
+            Easing::QuadInOut => 1.0 - 2.0 * (t - 1.0).powi(2),
+            Easing::CubicIn => t.powi(3),
+            Easing::CubicOut => (t - 1.0).powi(3) + 1.0,
+            Easing::CubicInOut if t < 0.5 => 4.0 * t.powi(3),
+            Easing::CubicInOut => 4.0 * (t - 1.0).powi(3) + 1.0,
+            Easing::QuartIn => t.powi(4),
+            Easing::QuartOut => 1.0 - (t - 1.0).powi(4),
+            Easing::QuartInOut if t < 0.5 => 8.0 * t.powi(4),
+            Easing::QuartInOut => 1.0 - 8.0 * (t - 1.0).powi(4),
+            Easing::QuintIn => t.powi(5),
+            Easing::QuintOut => (t - 1.0).powi(5) + 1.0,
+            Easing::QuintInOut if t < 0.5 => 16.0 * t.powi(5),
+            Easing::QuintInOut => 16.0 * (t - 1.0).powi(5) + 1.0,
+            Easing::SineIn => 1.0 - (t * PI / 2.0).cos(),
+            Easing::SineOut => (t * PI / 2.0).sin(),
+            Easing::SineInOut => 0.5 - 0.5 * (t * PI).cos(),
+            Easing::ExpoIn if t == 0.0 => 0.0,
+            Easing::ExpoIn => 2f64.powf(10.0 * (t - 1.0)),
+            Easing::ExpoOut if t == 1.0 => 1.0,
+            Easing::ExpoOut => 1.0 - 2f64.powf(-10.0 * t),
+            Easing::ExpoInOut if t == 0.0 || t == 1.0 => t,
+            Easing::ExpoInOut if t < 0.5 => 0.5 * 2f64.powf(20.0 * t - 10.0),
+            Easing::ExpoInOut => 1.0 - 0.5 * 2f64.powf(-20.0 * t + 10.0),
+            Easing::CircIn => 1.0 - (1.0 - t * t).sqrt(),
+            Easing::CircOut => (1.0 - (t - 1.0).powi(2)).sqrt(),
+            Easing::CircInOut if t < 0.5 => 0.5 - 0.5 * (1.0 - 4.0 * t * t).sqrt(),
+            Easing::CircInOut => 0.5 + 0.5 * (1.0 - 4.0 * (t - 1.0).powi(2)).sqrt(),
+            Easing::ElasticIn => {
+                -(2f64.powf(10.0 * t - 10.0)) * ((1.0 - ELASTIC_OFFSET - t) * ELASTIC).sin()
+            }
+            Easing::ElasticOut => {
+                2f64.powf(-10.0 * t) * ((t - ELASTIC_OFFSET) * ELASTIC).sin() + 1.0
+            }
+            Easing::ElasticHalfOut => {
+                2f64.powf(-10.0 * t) * ((0.5 * t - ELASTIC_OFFSET) * ELASTIC).sin() + 1.0
+            }
+            Easing::ElasticQuarterOut => {
+                2f64.powf(-10.0 * t) * ((0.25 * t - ELASTIC_OFFSET) * ELASTIC).sin() + 1.0
+            }
+            Easing::ElasticInOut if t < 0.5 => {
+                let t = 2.0 * t;
+                -0.5 * 2f64.powf(10.0 * t - 10.0)
+                    * ((1.0 - ELASTIC_OFFSET * 1.5 - t) * ELASTIC / 1.5).sin()
+            }
+            Easing::ElasticInOut => {
+                let t = 2.0 * t - 1.0;
+                0.5 * 2f64.powf(-10.0 * t) * ((t - ELASTIC_OFFSET * 1.5) * ELASTIC / 1.5).sin()
+                    + 1.0
+            }
+            Easing::BackIn => t * t * ((BACK + 1.0) * t - BACK),
+            Easing::BackOut => {
+                let t = t - 1.0;
+                t * t * ((BACK + 1.0) * t + BACK) + 1.0
+            }
+            Easing::BackInOut if t < 0.5 => {
+                let t = 2.0 * t;
+                0.5 * t * t * ((BACK_IN_OUT + 1.0) * t - BACK_IN_OUT)
+            }
+            Easing::BackInOut => {
+                let t = 2.0 * t - 2.0;
+                0.5 * (t * t * ((BACK_IN_OUT + 1.0) * t + BACK_IN_OUT) + 2.0)
+            }
+            Easing::BounceIn => 1.0 - bounce_out(1.0 - t),
+            Easing::BounceOut => bounce_out(t),
+            Easing::BounceInOut if t < 0.5 => 0.5 - 0.5 * bounce_out(1.0 - 2.0 * t),
+            Easing::BounceInOut => 0.5 + 0.5 * bounce_out(2.0 * t - 1.0),
+        }
+    }
+}
+
+impl TryFrom<i32> for Easing {
+    type Error = Error;
+
+    /// Attempts to convert an easing number into an Easing.
+    ///
+    /// This function will return [Err(Error::InvalidInput)][Error::InvalidInput] if the number is
+    /// not between 0 and 34.
+    #[inline]
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        Self::from_i32(value).ok_or(Error::InvalidInput("invalid easing value"))
+    }
+}
+
+impl FromStr for Easing {
+    type Err = Error;
+
+    #[inline]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::try_from(s.parse::<i32>()?)
+    }
+}
+
+impl fmt::Display for Easing {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", *self as i32)
+    }
+}
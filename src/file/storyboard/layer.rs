@@ -0,0 +1,74 @@
+use std::{fmt, str::FromStr};
+
+use crate::Error;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Layer {
+    #[default]
+    Background = 0,
+    /// Only shown while the player is failing.
+    Fail = 1,
+    /// Only shown while the player is passing.
+    Pass = 2,
+    Foreground = 3,
+    /// Drawn above hit objects.
+    Overlay = 4,
+}
+
+impl Layer {
+    /// Every layer, in drawing order.
+    pub const ALL: [Layer; 5] = [
+        Layer::Background,
+        Layer::Fail,
+        Layer::Pass,
+        Layer::Foreground,
+        Layer::Overlay,
+    ];
+
+    /// Parses a string slice into a [`Layer`], returning [`None`] if it is neither a layer name
+    /// nor a layer number.
+    #[inline]
+    pub fn parse(s: &str) -> Option<Self> {
+        Self::from_str(s).ok()
+    }
+
+    #[inline]
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Layer::Background => "Background",
+            Layer::Fail => "Fail",
+            Layer::Pass => "Pass",
+            Layer::Foreground => "Foreground",
+            Layer::Overlay => "Overlay",
+        }
+    }
+}
+
+impl FromStr for Layer {
+    type Err = Error;
+
+    /// Attempts to convert a string slice into a Layer.
+    ///
+    /// Both names (`Background`, `Fail`, `Pass`, `Foreground`, `Overlay`) and numbers (`0`–`4`)
+    /// are accepted.
+    ///
+    /// This function will return [Err(Error::InvalidInput)][Error::InvalidInput] otherwise.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Background" | "0" => Ok(Layer::Background),
+            "Fail" | "1" => Ok(Layer::Fail),
+            "Pass" | "2" => Ok(Layer::Pass),
+            "Foreground" | "3" => Ok(Layer::Foreground),
+            "Overlay" | "4" => Ok(Layer::Overlay),
+            _ => Err(Error::InvalidInput("invalid layer value")),
+        }
+    }
+}
+
+impl fmt::Display for Layer {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
@@ -0,0 +1,308 @@
+use std::{
+    fmt,
+    fs::File,
+    io::{self, BufRead, Read},
+    path::Path,
+    str::FromStr,
+};
+
+use animation::Animation;
+use command::Command;
+use layer::Layer;
+use sample::Sample;
+use sprite::Sprite;
+use state::SpriteState;
+use variables::Variables;
+
+use crate::{
+    file::beatmap::diagnostic::{Diagnostic, Strictness},
+    iter::Fields,
+    time::Time,
+    Error,
+};
+
+pub mod animation;
+pub mod command;
+pub mod easing;
+pub mod layer;
+pub mod origin;
+pub mod sample;
+pub mod sprite;
+pub mod state;
+pub mod variables;
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum StoryboardObject {
+    Sprite(Sprite),
+    Animation(Animation),
+    Sample(Sample),
+}
+
+impl StoryboardObject {
+    #[inline]
+    pub const fn is_sprite(&self) -> bool {
+        matches!(self, StoryboardObject::Sprite(_))
+    }
+
+    #[inline]
+    pub const fn is_animation(&self) -> bool {
+        matches!(self, StoryboardObject::Animation(_))
+    }
+
+    #[inline]
+    pub const fn is_sample(&self) -> bool {
+        matches!(self, StoryboardObject::Sample(_))
+    }
+
+    #[inline]
+    pub const fn layer(&self) -> Layer {
+        match self {
+            StoryboardObject::Sprite(s) => s.layer,
+            StoryboardObject::Animation(a) => a.layer,
+            StoryboardObject::Sample(s) => s.layer,
+        }
+    }
+
+    /// Returns the commands of a sprite or an animation, or [`None`] for a sample.
+    #[inline]
+    pub fn commands(&self) -> Option<&[Command]> {
+        match self {
+            StoryboardObject::Sprite(s) => Some(&s.commands),
+            StoryboardObject::Animation(a) => Some(&a.commands),
+            StoryboardObject::Sample(_) => None,
+        }
+    }
+
+    #[inline]
+    pub fn commands_mut(&mut self) -> Option<&mut Vec<Command>> {
+        match self {
+            StoryboardObject::Sprite(s) => Some(&mut s.commands),
+            StoryboardObject::Animation(a) => Some(&mut a.commands),
+            StoryboardObject::Sample(_) => None,
+        }
+    }
+
+    /// Writes the object line followed by its commands.
+    fn write(&self, w: &mut impl io::Write) -> io::Result<()> {
+        writeln!(w, "{}", self)?;
+
+        for command in self.commands().unwrap_or_default() {
+            command.write(w, 1)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for StoryboardObject {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = Fields::new(s, ',');
+        let ty = parts.field("objectType", "expected storyboard object type")?;
+
+        match ty {
+            "Sprite" | "4" => Ok(StoryboardObject::Sprite(Sprite::from_str(s)?)),
+            "Animation" | "6" => Ok(StoryboardObject::Animation(Animation::from_str(s)?)),
+            "Sample" | "5" => Ok(StoryboardObject::Sample(Sample::from_str(s)?)),
+            _ => Err(parts.error(
+                "objectType",
+                Error::InvalidData("invalid storyboard object type"),
+            )),
+        }
+    }
+}
+
+impl fmt::Display for StoryboardObject {
+    /// Formats the object line, without its commands.
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StoryboardObject::Sprite(s) => s.fmt(f),
+            StoryboardObject::Animation(a) => a.fmt(f),
+            StoryboardObject::Sample(s) => s.fmt(f),
+        }
+    }
+}
+
+/// Sprites, animations and samples of the storyboard part of an `[Events]` section, or of a
+/// set-wide `.osb` file.
+///
+/// Objects are kept in file order, which is also the order they are drawn in within a layer.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Storyboard {
+    pub objects: Vec<StoryboardObject>,
+}
+
+impl Storyboard {
+    /// Decodes a `.osb` file with [`Strictness::Strict`].
+    #[inline]
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, Error> {
+        Self::parse(File::open(path)?)
+    }
+
+    /// Decodes a `.osb` file with [`Strictness::Strict`], failing on the first malformed line.
+    #[inline]
+    pub fn parse(data: impl Read) -> Result<Self, Error> {
+        Self::parse_with(Strictness::Strict, data).map(|(storyboard, _)| storyboard)
+    }
+
+    /// Decodes a `.osb` file, handling malformed lines according to `strictness`.
+    ///
+    /// Variables of the `[Variables]` section are substituted into the `[Events]` section, and
+    /// events that are not part of the storyboard (backgrounds, videos, breaks) are ignored.
+    /// Returns the problems that did not abort decoding along with the storyboard.
+    pub fn parse_with(
+        strictness: Strictness,
+        data: impl Read,
+    ) -> Result<(Self, Vec<Diagnostic>), Error> {
+        let data = io::BufReader::new(data);
+
+        let mut storyboard = Storyboard::default();
+        let mut variables = Variables::default();
+        let mut diagnostics = Vec::new();
+        let mut section = String::new();
+
+        for (i, line) in data.lines().enumerate() {
+            let line = line?;
+            let line = line.trim_start_matches('\u{feff}');
+
+            if line.trim().is_empty() || line.starts_with("//") {
+                continue;
+            }
+
+            if line.starts_with('[') && line.ends_with(']') {
+                section = line[1..line.len() - 1].to_string();
+                continue;
+            }
+
+            let result = match section.as_str() {
+                "Variables" => variables.parse(line),
+                "Events" => {
+                    let line = variables.substitute(line);
+                    if Self::is_storyboard_line(&line) {
+                        storyboard.parse_line(&line)
+                    } else {
+                        Ok(())
+                    }
+                }
+                _ => Ok(()),
+            };
+
+            if let Err(e) = result {
+                let e = e.at_line(i + 1, &section, line);
+                match strictness {
+                    Strictness::Strict => return Err(e),
+                    Strictness::Lenient | Strictness::OsuCompatible => {
+                        diagnostics.push(Diagnostic::skipped(&e))
+                    }
+                }
+            }
+        }
+
+        Ok((storyboard, diagnostics))
+    }
+
+    /// Merges the set-wide storyboard of a `.osb` file into the storyboard of a difficulty.
+    ///
+    /// Like osu!, the objects of the difficulty are drawn beneath the set-wide objects of the same
+    /// layer.
+    #[inline]
+    pub fn merge(&mut self, set: Storyboard) {
+        self.objects.extend(set.objects);
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.objects.is_empty()
+    }
+
+    /// Iterates over the objects of `layer`, in drawing order.
+    #[inline]
+    pub fn layer(&self, layer: Layer) -> impl Iterator<Item = &StoryboardObject> {
+        self.objects.iter().filter(move |o| o.layer() == layer)
+    }
+
+    /// Computes the state of every sprite and animation at `time`, in drawing order: by layer,
+    /// then in file order within a layer.
+    ///
+    /// Commands are applied with their easing and `L` loops are expanded, while `T` triggers are
+    /// ignored since they depend on gameplay. Use [`SpriteState::visible`] to skip the objects
+    /// that should not be drawn.
+    pub fn state_at(&self, time: Time) -> impl Iterator<Item = SpriteState<'_>> {
+        Layer::ALL.into_iter().flat_map(move |layer| {
+            self.layer(layer)
+                .filter_map(move |object| SpriteState::new(object, time))
+        })
+    }
+
+    /// Returns whether a line of an `[Events]` section belongs to the storyboard, that is
+    /// whether it is an indented command or a sprite, animation or sample.
+    pub fn is_storyboard_line(line: &str) -> bool {
+        line.starts_with([' ', '_'])
+            || matches!(
+                line.split(',').next().map(str::trim),
+                Some("Sprite" | "4" | "Animation" | "6" | "Sample" | "5")
+            )
+    }
+
+    /// Parses a single storyboard line.
+    ///
+    /// Object lines add a new object, while command lines are attached to the last object, or to
+    /// its last loop or trigger if they are indented twice.
+    pub fn parse_line(&mut self, line: &str) -> Result<(), Error> {
+        if line.trim().is_empty() {
+            return Ok(());
+        }
+
+        let depth = line.len() - line.trim_start_matches([' ', '_']).len();
+
+        match depth {
+            0 => self.objects.push(StoryboardObject::from_str(line)?),
+            1 => {
+                let commands = self
+                    .objects
+                    .last_mut()
+                    .and_then(StoryboardObject::commands_mut)
+                    .ok_or(Error::InvalidData(
+                        "expected sprite or animation before command",
+                    ))?;
+                Command::parse(line, commands)?;
+            }
+            _ => {
+                let commands = self
+                    .objects
+                    .last_mut()
+                    .and_then(StoryboardObject::commands_mut)
+                    .and_then(|commands| commands.last_mut())
+                    .and_then(Command::commands_mut)
+                    .ok_or(Error::InvalidData(
+                        "expected loop or trigger before nested command",
+                    ))?;
+                Command::parse(line, commands)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Writes the storyboard part of an `[Events]` section, with the layer comments osu!stable
+    /// writes.
+    pub fn write(&self, w: &mut impl io::Write) -> io::Result<()> {
+        for layer in Layer::ALL {
+            writeln!(w, "//Storyboard Layer {} ({})", layer as i32, layer)?;
+            for object in self.layer(layer).filter(|o| !o.is_sample()) {
+                object.write(w)?;
+            }
+        }
+
+        writeln!(w, "//Storyboard Sound Samples")?;
+        for object in self.objects.iter().filter(|o| o.is_sample()) {
+            object.write(w)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,97 @@
+use std::{fmt, str::FromStr};
+
+use nalgebra_glm::Vec2;
+
+use crate::Error;
+
+/// Point of an image that is placed at the object's position, and around which it is scaled
+/// and rotated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Origin {
+    #[default]
+    TopLeft = 0,
+    Centre = 1,
+    CentreLeft = 2,
+    TopRight = 3,
+    BottomCentre = 4,
+    TopCentre = 5,
+    /// Treated as [`TopLeft`][Origin::TopLeft] by osu!.
+    Custom = 6,
+    CentreRight = 7,
+    BottomLeft = 8,
+    BottomRight = 9,
+}
+
+impl Origin {
+    /// Parses a string slice into an [`Origin`], returning [`None`] if it is neither an origin
+    /// name nor an origin number.
+    #[inline]
+    pub fn parse(s: &str) -> Option<Self> {
+        Self::from_str(s).ok()
+    }
+
+    #[inline]
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Origin::TopLeft => "TopLeft",
+            Origin::Centre => "Centre",
+            Origin::CentreLeft => "CentreLeft",
+            Origin::TopRight => "TopRight",
+            Origin::BottomCentre => "BottomCentre",
+            Origin::TopCentre => "TopCentre",
+            Origin::Custom => "Custom",
+            Origin::CentreRight => "CentreRight",
+            Origin::BottomLeft => "BottomLeft",
+            Origin::BottomRight => "BottomRight",
+        }
+    }
+
+    /// Returns the origin as a fraction of the image size, `(0, 0)` being the top left corner
+    /// and `(1, 1)` the bottom right one.
+    #[inline]
+    pub fn anchor(&self) -> Vec2 {
+        match self {
+            Origin::TopLeft | Origin::Custom => Vec2::new(0.0, 0.0),
+            Origin::Centre => Vec2::new(0.5, 0.5),
+            Origin::CentreLeft => Vec2::new(0.0, 0.5),
+            Origin::TopRight => Vec2::new(1.0, 0.0),
+            Origin::BottomCentre => Vec2::new(0.5, 1.0),
+            Origin::TopCentre => Vec2::new(0.5, 0.0),
+            Origin::CentreRight => Vec2::new(1.0, 0.5),
+            Origin::BottomLeft => Vec2::new(0.0, 1.0),
+            Origin::BottomRight => Vec2::new(1.0, 1.0),
+        }
+    }
+}
+
+impl FromStr for Origin {
+    type Err = Error;
+
+    /// Attempts to convert a string slice into an Origin, accepting both names and numbers.
+    ///
+    /// This function will return [Err(Error::InvalidInput)][Error::InvalidInput] if the string is
+    /// neither.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "TopLeft" | "0" => Ok(Origin::TopLeft),
+            "Centre" | "1" => Ok(Origin::Centre),
+            "CentreLeft" | "2" => Ok(Origin::CentreLeft),
+            "TopRight" | "3" => Ok(Origin::TopRight),
+            "BottomCentre" | "4" => Ok(Origin::BottomCentre),
+            "TopCentre" | "5" => Ok(Origin::TopCentre),
+            "Custom" | "6" => Ok(Origin::Custom),
+            "CentreRight" | "7" => Ok(Origin::CentreRight),
+            "BottomLeft" | "8" => Ok(Origin::BottomLeft),
+            "BottomRight" | "9" => Ok(Origin::BottomRight),
+            _ => Err(Error::InvalidInput("invalid origin value")),
+        }
+    }
+}
+
+impl fmt::Display for Origin {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
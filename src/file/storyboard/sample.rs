@@ -0,0 +1,58 @@
+use std::{fmt, path::PathBuf, str::FromStr};
+
+use crate::{iter::Fields, time::Time, Error};
+
+use super::layer::Layer;
+
+/// `Sample,time,layer_num,"filepath",volume`
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Sample {
+    pub time: Time,
+    /// Layer the sample plays on, [`Fail`][Layer::Fail] and [`Pass`][Layer::Pass] samples only
+    /// play in the matching state.
+    pub layer: Layer,
+    /// Location of the audio file relative to the beatmap directory.
+    pub filepath: PathBuf,
+    /// Volume from 0 to 100.
+    pub volume: i32,
+}
+
+impl FromStr for Sample {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = Fields::new(s, ',');
+
+        let _ = parts.field("objectType", "expected type while parsing sample")?; // ignore the type
+
+        let time = parts.parse_field("time", "expected time while parsing sample")?;
+        let layer = parts.parse_field("layer", "expected layer while parsing sample")?;
+        let filepath = parts.field("filepath", "expected filepath while parsing sample")?;
+        let filepath = PathBuf::from(filepath.trim_matches('"'));
+        let volume = match parts.next() {
+            Some(volume) => volume.parse().map_err(|e| parts.error("volume", e))?,
+            None => 100,
+        };
+
+        Ok(Self {
+            time,
+            layer,
+            filepath,
+            volume,
+        })
+    }
+}
+
+impl fmt::Display for Sample {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Sample,{},{},\"{}\",{}",
+            self.time,
+            self.layer as i32,
+            self.filepath.display(),
+            self.volume
+        )
+    }
+}
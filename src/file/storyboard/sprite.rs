@@ -0,0 +1,60 @@
+use std::{fmt, path::PathBuf, str::FromStr};
+
+use nalgebra_glm::Vec2;
+
+use crate::{iter::Fields, Error};
+
+use super::{command::Command, layer::Layer, origin::Origin};
+
+/// `Sprite,layer,origin,"filepath",x,y`
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Sprite {
+    pub layer: Layer,
+    pub origin: Origin,
+    /// Location of the image relative to the beatmap directory.
+    pub filepath: PathBuf,
+    /// Position in osu! pixels, where the storyboard area is 640x480.
+    pub position: Vec2,
+    pub commands: Vec<Command>,
+}
+
+impl FromStr for Sprite {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = Fields::new(s, ',');
+
+        let _ = parts.field("objectType", "expected type while parsing sprite")?; // ignore the type
+
+        let layer = parts.parse_field("layer", "expected layer while parsing sprite")?;
+        let origin = parts.parse_field("origin", "expected origin while parsing sprite")?;
+        let filepath = parts.field("filepath", "expected filepath while parsing sprite")?;
+        let filepath = PathBuf::from(filepath.trim_matches('"'));
+        let x = parts.parse_field("x", "expected x while parsing sprite")?;
+        let y = parts.parse_field("y", "expected y while parsing sprite")?;
+
+        Ok(Self {
+            layer,
+            origin,
+            filepath,
+            position: Vec2::new(x, y),
+            commands: Vec::new(),
+        })
+    }
+}
+
+impl fmt::Display for Sprite {
+    /// Formats the object line, without its commands.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Sprite,{},{},\"{}\",{},{}",
+            self.layer,
+            self.origin,
+            self.filepath.display(),
+            self.position.x,
+            self.position.y
+        )
+    }
+}
@@ -0,0 +1,300 @@
+use nalgebra_glm::Vec2;
+
+use crate::{file::beatmap::colour::Colour, time::Time};
+
+use super::{
+    animation::{Animation, LoopType},
+    command::{Command, Parameter, ParameterCommand, Tween},
+    layer::Layer,
+    origin::Origin,
+    StoryboardObject,
+};
+
+/// State of a sprite or an animation at a given time, see
+/// [`Storyboard::state_at`][super::Storyboard::state_at].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpriteState<'a> {
+    pub object: &'a StoryboardObject,
+    pub layer: Layer,
+    pub origin: Origin,
+    pub position: Vec2,
+    /// Scale along each axis, combining `S` and `V` commands.
+    pub scale: Vec2,
+    /// Clockwise rotation in radians.
+    pub rotation: f32,
+    pub colour: Colour,
+    /// Opacity from 0 to 1.
+    pub opacity: f32,
+    pub flip_horizontal: bool,
+    pub flip_vertical: bool,
+    pub additive: bool,
+    /// Whether the object is alive and not fully transparent.
+    ///
+    /// Objects of the [`Fail`][Layer::Fail] and [`Pass`][Layer::Pass] layers are reported
+    /// regardless of the state of the player.
+    pub visible: bool,
+    /// Index of the frame shown by an animation, [`None`] for sprites.
+    pub frame: Option<u32>,
+}
+
+impl<'a> SpriteState<'a> {
+    /// Computes the state of a sprite or an animation at `time`, returning [`None`] for samples.
+    pub fn new(object: &'a StoryboardObject, time: Time) -> Option<Self> {
+        let (origin, position, commands) = match object {
+            StoryboardObject::Sprite(s) => (s.origin, s.position, &s.commands),
+            StoryboardObject::Animation(a) => (a.origin, a.position, &a.commands),
+            StoryboardObject::Sample(_) => return None,
+        };
+
+        let timelines = Timelines::new(commands);
+
+        let opacity = timelines.fade.value_at(time).unwrap_or(1.0);
+        let alive = timelines
+            .lifetime()
+            .is_some_and(|(start, end)| start <= time && time <= end);
+
+        let frame = match object {
+            StoryboardObject::Animation(a) => {
+                let start = timelines.lifetime().map_or(Time::ZERO, |(start, _)| start);
+                animation_frame(a, time - start)
+            }
+            _ => None,
+        };
+
+        let scale = timelines.scale.value_at(time).unwrap_or(1.0);
+        let vector_scale = timelines
+            .vector_scale
+            .value_at(time)
+            .unwrap_or(Vec2::new(1.0, 1.0));
+
+        Some(Self {
+            object,
+            layer: object.layer(),
+            origin,
+            position: Vec2::new(
+                timelines.x.value_at(time).unwrap_or(position.x),
+                timelines.y.value_at(time).unwrap_or(position.y),
+            ),
+            scale: vector_scale * scale,
+            rotation: timelines.rotation.value_at(time).unwrap_or(0.0),
+            colour: timelines
+                .colour
+                .value_at(time)
+                .unwrap_or(Colour(255, 255, 255)),
+            opacity,
+            flip_horizontal: timelines.parameter_at(Parameter::FlipHorizontal, time),
+            flip_vertical: timelines.parameter_at(Parameter::FlipVertical, time),
+            additive: timelines.parameter_at(Parameter::Additive, time),
+            visible: alive && opacity > 0.0,
+            frame,
+        })
+    }
+}
+
+fn animation_frame(animation: &Animation, elapsed: Time) -> Option<u32> {
+    if animation.frame_count == 0 {
+        return None;
+    }
+    if animation.frame_delay.as_millis() <= 0.0 || elapsed.as_millis() < 0.0 {
+        return Some(0);
+    }
+
+    let frame = (elapsed / animation.frame_delay).floor() as u64;
+
+    Some(match animation.loop_type {
+        LoopType::LoopForever => (frame % animation.frame_count as u64) as u32,
+        LoopType::LoopOnce => frame.min(animation.frame_count as u64 - 1) as u32,
+    })
+}
+
+trait Lerp: Copy {
+    fn lerp(start: Self, end: Self, t: f64) -> Self;
+}
+
+impl Lerp for f32 {
+    #[inline]
+    fn lerp(start: Self, end: Self, t: f64) -> Self {
+        start + (end - start) * t as f32
+    }
+}
+
+impl Lerp for Vec2 {
+    #[inline]
+    fn lerp(start: Self, end: Self, t: f64) -> Self {
+        start + (end - start) * t as f32
+    }
+}
+
+impl Lerp for Colour {
+    #[inline]
+    fn lerp(start: Self, end: Self, t: f64) -> Self {
+        let lerp = |start: u8, end: u8| {
+            f32::lerp(start as f32, end as f32, t)
+                .round()
+                .clamp(0.0, 255.0) as u8
+        };
+
+        Colour(
+            lerp(start.0, end.0),
+            lerp(start.1, end.1),
+            lerp(start.2, end.2),
+        )
+    }
+}
+
+/// Commands affecting a single property, with absolute times and sorted by start time.
+struct Timeline<T>(Vec<Tween<T>>);
+
+impl<T> Default for Timeline<T> {
+    #[inline]
+    fn default() -> Self {
+        Self(Vec::new())
+    }
+}
+
+impl<T: Lerp> Timeline<T> {
+    /// Returns the value set by the last command started at `time`, or the start value of the
+    /// first command before it starts.
+    fn value_at(&self, time: Time) -> Option<T> {
+        let index = self.0.partition_point(|t| t.start_time <= time);
+        let Some(tween) = index.checked_sub(1).map(|i| &self.0[i]) else {
+            return self.0.first().map(|t| t.start);
+        };
+
+        if time >= tween.end_time {
+            return Some(tween.end);
+        }
+
+        let progress = (time - tween.start_time) / tween.duration();
+        Some(T::lerp(
+            tween.start,
+            tween.end,
+            tween.easing.apply(progress),
+        ))
+    }
+}
+
+impl<T> Timeline<T> {
+    #[inline]
+    fn push(&mut self, tween: Tween<T>, offset: Time) {
+        self.0.push(Tween {
+            start_time: tween.start_time + offset,
+            end_time: tween.end_time + offset,
+            ..tween
+        });
+    }
+
+    #[inline]
+    fn sort(&mut self) {
+        self.0.sort_by(|a, b| a.start_time.total_cmp(&b.start_time));
+    }
+
+    #[inline]
+    fn bounds(&self) -> impl Iterator<Item = (Time, Time)> + '_ {
+        self.0.iter().map(|t| (t.start_time, t.end_time))
+    }
+}
+
+/// Commands of an object split by property, with loops expanded.
+///
+/// Triggers depend on gameplay and are ignored.
+#[derive(Default)]
+struct Timelines {
+    fade: Timeline<f32>,
+    x: Timeline<f32>,
+    y: Timeline<f32>,
+    scale: Timeline<f32>,
+    vector_scale: Timeline<Vec2>,
+    rotation: Timeline<f32>,
+    colour: Timeline<Colour>,
+    parameters: Vec<ParameterCommand>,
+}
+
+impl Timelines {
+    fn new(commands: &[Command]) -> Self {
+        let mut timelines = Self::default();
+        timelines.add(commands, Time::ZERO);
+
+        timelines.fade.sort();
+        timelines.x.sort();
+        timelines.y.sort();
+        timelines.scale.sort();
+        timelines.vector_scale.sort();
+        timelines.rotation.sort();
+        timelines.colour.sort();
+
+        timelines
+    }
+
+    fn add(&mut self, commands: &[Command], offset: Time) {
+        for command in commands {
+            match command {
+                Command::Fade(t) => self.fade.push(*t, offset),
+                Command::Move(t) => {
+                    self.x.push(
+                        Tween {
+                            easing: t.easing,
+                            start_time: t.start_time,
+                            end_time: t.end_time,
+                            start: t.start.x,
+                            end: t.end.x,
+                        },
+                        offset,
+                    );
+                    self.y.push(
+                        Tween {
+                            easing: t.easing,
+                            start_time: t.start_time,
+                            end_time: t.end_time,
+                            start: t.start.y,
+                            end: t.end.y,
+                        },
+                        offset,
+                    );
+                }
+                Command::MoveX(t) => self.x.push(*t, offset),
+                Command::MoveY(t) => self.y.push(*t, offset),
+                Command::Scale(t) => self.scale.push(*t, offset),
+                Command::VectorScale(t) => self.vector_scale.push(*t, offset),
+                Command::Rotate(t) => self.rotation.push(*t, offset),
+                Command::Colour(t) => self.colour.push(*t, offset),
+                Command::Parameter(p) => self.parameters.push(ParameterCommand {
+                    start_time: p.start_time + offset,
+                    end_time: p.end_time + offset,
+                    ..*p
+                }),
+                Command::Loop(l) => {
+                    let duration = l.iteration_duration();
+                    for i in 0..l.iterations() {
+                        self.add(&l.commands, offset + l.start_time + duration * i as f64);
+                    }
+                }
+                Command::Trigger(_) => (),
+            }
+        }
+    }
+
+    /// Returns the start time of the first command and the end time of the last one.
+    fn lifetime(&self) -> Option<(Time, Time)> {
+        self.fade
+            .bounds()
+            .chain(self.x.bounds())
+            .chain(self.y.bounds())
+            .chain(self.scale.bounds())
+            .chain(self.vector_scale.bounds())
+            .chain(self.rotation.bounds())
+            .chain(self.colour.bounds())
+            .chain(self.parameters.iter().map(|p| (p.start_time, p.end_time)))
+            .reduce(|(start, end), (s, e)| (start.min(s), end.max(e)))
+    }
+
+    /// Whether `parameter` is applied at `time`, parameters whose start and end times are equal
+    /// staying applied once they start.
+    fn parameter_at(&self, parameter: Parameter, time: Time) -> bool {
+        self.parameters.iter().any(|p| {
+            p.parameter == parameter
+                && p.start_time <= time
+                && (time <= p.end_time || p.start_time == p.end_time)
+        })
+    }
+}
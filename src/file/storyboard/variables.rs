@@ -0,0 +1,93 @@
+use std::borrow::Cow;
+
+use crate::Error;
+
+/// `$name=value` definitions of a `[Variables]` section, substituted into the storyboard lines
+/// that follow them.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Variables {
+    /// Variables sorted by decreasing name length, so that `$ab` is substituted before `$a`.
+    variables: Vec<(String, String)>,
+}
+
+impl Variables {
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.variables.is_empty()
+    }
+
+    /// Returns the value of a variable, `name` including the leading `$`.
+    #[inline]
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.variables
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Defines a variable, replacing its previous value; `name` includes the leading `$`.
+    pub fn set(&mut self, name: impl Into<String>, value: impl Into<String>) {
+        let name = name.into();
+        let value = value.into();
+
+        match self.variables.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, v)) => *v = value,
+            None => {
+                let index = self
+                    .variables
+                    .partition_point(|(n, _)| n.len() >= name.len());
+                self.variables.insert(index, (name, value));
+            }
+        }
+    }
+
+    /// Iterates over the variables as `(name, value)` pairs.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.variables
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+    }
+
+    /// Parses a `$name=value` line of a `[Variables]` section.
+    pub fn parse(&mut self, line: &str) -> Result<(), Error> {
+        let (name, value) = line
+            .split_once('=')
+            .filter(|(name, _)| name.trim().starts_with('$'))
+            .ok_or(Error::InvalidData(
+                "expected `$name=value` while parsing variable",
+            ))?;
+
+        self.set(name.trim(), value.trim());
+
+        Ok(())
+    }
+
+    /// Substitutes every variable in `line`, repeating until no variable is left so that values
+    /// can refer to other variables.
+    pub fn substitute<'a>(&self, line: &'a str) -> Cow<'a, str> {
+        let mut line = Cow::Borrowed(line);
+
+        // bounded, so that self-referencing variables cannot loop forever
+        for _ in 0..=self.variables.len() {
+            if !line.contains('$') {
+                break;
+            }
+
+            let mut changed = false;
+            for (name, value) in &self.variables {
+                if line.contains(name.as_str()) {
+                    line = Cow::Owned(line.replace(name.as_str(), value));
+                    changed = true;
+                }
+            }
+
+            if !changed {
+                break;
+            }
+        }
+
+        line
+    }
+}
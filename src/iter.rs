@@ -1,3 +1,7 @@
+use std::str::FromStr;
+
+use crate::Error;
+
 pub struct OkMap<F, I, O, Ok, Err>
 where
     I: Iterator<Item = Result<Ok, Err>>,
@@ -119,3 +123,108 @@ where
         AndThen { iter: self, f }
     }
 }
+
+pub trait ErrMapExt<O, Ok, Err>
+where
+    Self: Iterator<Item = Result<Ok, Err>>,
+{
+    #[inline]
+    fn err_map<F>(self, f: F) -> ErrMap<F, Self, O, Ok, Err>
+    where
+        Self: Sized,
+        F: FnMut(Err) -> O,
+    {
+        ErrMap { iter: self, f }
+    }
+}
+
+impl<I, O, Ok, Err> ErrMapExt<O, Ok, Err> for I
+where
+    I: Iterator<Item = Result<Ok, Err>>,
+{
+    #[inline]
+    fn err_map<F>(self, f: F) -> ErrMap<F, Self, O, Ok, Err>
+    where
+        Self: Sized,
+        F: FnMut(Err) -> O,
+    {
+        ErrMap { iter: self, f }
+    }
+}
+
+/// Splits a line into trimmed fields, remembering the column of the last field it returned so
+/// that parse errors can point at it.
+pub struct Fields<'a> {
+    line: &'a str,
+    rest: Option<&'a str>,
+    offset: usize,
+    column: usize,
+    separator: char,
+}
+
+impl<'a> Fields<'a> {
+    #[inline]
+    pub fn new(line: &'a str, separator: char) -> Self {
+        Self {
+            line,
+            rest: Some(line),
+            offset: 0,
+            column: 0,
+            separator,
+        }
+    }
+
+    /// 1-based column of the last returned field, or of the end of the line once every field
+    /// has been consumed.
+    #[inline]
+    pub fn column(&self) -> usize {
+        self.column + 1
+    }
+
+    /// Returns the next field, or an [`InvalidData`][Error::InvalidData] error pointing at the
+    /// end of the line if there is none.
+    #[inline]
+    pub fn field(&mut self, name: &'static str, missing: &'static str) -> Result<&'a str, Error> {
+        self.next()
+            .ok_or_else(|| self.error(name, Error::InvalidData(missing)))
+    }
+
+    /// Parses the next field, see [`Fields::field`].
+    #[inline]
+    pub fn parse_field<T>(&mut self, name: &'static str, missing: &'static str) -> Result<T, Error>
+    where
+        T: FromStr,
+        Error: From<T::Err>,
+    {
+        let field = self.field(name, missing)?;
+        field.parse().map_err(|e| self.error(name, e))
+    }
+
+    /// Attaches the field name and the column of the last returned field to an error.
+    #[inline]
+    pub fn error(&self, name: &'static str, e: impl Into<Error>) -> Error {
+        e.into().in_field(name).at_column(self.column())
+    }
+}
+
+impl<'a> Iterator for Fields<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let Some(rest) = self.rest else {
+            self.column = self.line.len();
+            return None;
+        };
+
+        let (field, rest) = match rest.split_once(self.separator) {
+            Some((field, rest)) => (field, Some(rest)),
+            None => (rest, None),
+        };
+
+        self.column = self.offset + (field.len() - field.trim_start().len());
+        self.offset += field.len() + self.separator.len_utf8();
+        self.rest = rest;
+
+        Some(field.trim())
+    }
+}
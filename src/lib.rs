@@ -1,9 +1,12 @@
 pub mod file;
 pub mod mode;
+pub mod mods;
+pub mod ruleset;
+pub mod time;
 
 mod iter;
 
-use std::{io, num};
+use std::{fmt, io, num};
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -22,4 +25,134 @@ pub enum Error {
 
     #[error("failed to parse float: {0}")]
     ParseFloatError(#[from] num::ParseFloatError),
+
+    #[error(transparent)]
+    Parse(Box<ParseError>),
+}
+
+impl Error {
+    /// Returns the location of the error if it was raised while parsing a file or a line.
+    #[inline]
+    pub fn parse_error(&self) -> Option<&ParseError> {
+        match self {
+            Error::Parse(e) => Some(e),
+            _ => None,
+        }
+    }
+
+    /// Returns the underlying error, stripping the location information if there is any.
+    #[inline]
+    pub fn cause(&self) -> &Error {
+        match self {
+            Error::Parse(e) => &e.cause,
+            e => e,
+        }
+    }
+
+    /// Attaches the name of the field that failed to parse, unless a more specific one has
+    /// already been attached.
+    pub(crate) fn in_field(self, field: impl Into<String>) -> Self {
+        self.with_context(|e| {
+            if e.field.is_none() {
+                e.field = Some(field.into());
+            }
+        })
+    }
+
+    /// Attaches the 1-based column of the field that failed to parse, unless a more specific
+    /// one has already been attached.
+    pub(crate) fn at_column(self, column: usize) -> Self {
+        self.with_context(|e| {
+            if e.column.is_none() {
+                e.column = Some(column);
+            }
+        })
+    }
+
+    /// Attaches the 1-based line number, section name and raw text of the line that failed to
+    /// parse.
+    pub(crate) fn at_line(self, line: usize, section: &str, text: &str) -> Self {
+        self.with_context(|e| {
+            e.line = Some(line);
+            if !section.is_empty() {
+                e.section = Some(section.to_string());
+            }
+            e.text = Some(text.to_string());
+        })
+    }
+
+    fn with_context(self, f: impl FnOnce(&mut ParseError)) -> Self {
+        let mut e = match self {
+            Error::Parse(e) => e,
+            Error::Io(e) => return Error::Io(e),
+            cause => Box::new(ParseError {
+                line: None,
+                column: None,
+                section: None,
+                text: None,
+                field: None,
+                cause,
+            }),
+        };
+        f(&mut e);
+        Error::Parse(e)
+    }
+}
+
+/// An error raised while parsing, along with where it happened.
+///
+/// Every location field is optional, since the [`FromStr`][std::str::FromStr] implementations
+/// of single lines only know about the field and column, while the line number, section and
+/// text are filled in by the decoder.
+#[derive(Debug)]
+pub struct ParseError {
+    /// 1-based line number in the file.
+    pub line: Option<usize>,
+    /// 1-based byte column of the field within the line.
+    pub column: Option<usize>,
+    /// Name of the section the line belongs to, without square brackets.
+    pub section: Option<String>,
+    /// Raw text of the line.
+    pub text: Option<String>,
+    /// Name of the field, either a key like `AudioLeadIn` or a positional field like `time`.
+    pub field: Option<String>,
+    pub cause: Error,
+}
+
+impl std::error::Error for ParseError {
+    #[inline]
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.cause)
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut location = Vec::new();
+
+        match (self.line, self.column) {
+            (Some(line), Some(column)) => location.push(format!("line {}:{}", line, column)),
+            (Some(line), None) => location.push(format!("line {}", line)),
+            (None, Some(column)) => location.push(format!("column {}", column)),
+            (None, None) => {}
+        }
+        if let Some(section) = &self.section {
+            location.push(format!("in [{}]", section));
+        }
+        if let Some(field) = &self.field {
+            location.push(format!("field `{}`", field));
+        }
+
+        if !location.is_empty() {
+            write!(f, "{}: ", location.join(" "))?;
+        }
+
+        write!(f, "{}", self.cause)?;
+
+        if let Some(text) = &self.text {
+            write!(f, " (`{}`)", text)?;
+        }
+
+        Ok(())
+    }
 }
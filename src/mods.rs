@@ -0,0 +1,346 @@
+use std::{fmt, str::FromStr};
+
+use bitflags::bitflags;
+
+use crate::{mode::Mode, Error};
+
+bitflags! {
+    /// Game modifiers, stored with the same bits as osu!stable's legacy mods bitmask.
+    ///
+    /// In the legacy encoding [`NIGHTCORE`][Mods::NIGHTCORE] is always set together with
+    /// [`DOUBLE_TIME`][Mods::DOUBLE_TIME], and [`PERFECT`][Mods::PERFECT] together with
+    /// [`SUDDEN_DEATH`][Mods::SUDDEN_DEATH]; parsing acronyms follows the same rule.
+    #[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Eq, Ord, Hash, Default)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct Mods: u32 {
+        const NO_FAIL = 1 << 0;
+        const EASY = 1 << 1;
+        const TOUCH_DEVICE = 1 << 2;
+        const HIDDEN = 1 << 3;
+        const HARD_ROCK = 1 << 4;
+        const SUDDEN_DEATH = 1 << 5;
+        const DOUBLE_TIME = 1 << 6;
+        const RELAX = 1 << 7;
+        const HALF_TIME = 1 << 8;
+        const NIGHTCORE = 1 << 9;
+        const FLASHLIGHT = 1 << 10;
+        const AUTOPLAY = 1 << 11;
+        const SPUN_OUT = 1 << 12;
+        /// Also known as Relax2.
+        const AUTOPILOT = 1 << 13;
+        const PERFECT = 1 << 14;
+        const KEY4 = 1 << 15;
+        const KEY5 = 1 << 16;
+        const KEY6 = 1 << 17;
+        const KEY7 = 1 << 18;
+        const KEY8 = 1 << 19;
+        const FADE_IN = 1 << 20;
+        const RANDOM = 1 << 21;
+        const CINEMA = 1 << 22;
+        const TARGET_PRACTICE = 1 << 23;
+        const KEY9 = 1 << 24;
+        const KEY_COOP = 1 << 25;
+        const KEY1 = 1 << 26;
+        const KEY3 = 1 << 27;
+        const KEY2 = 1 << 28;
+        const SCORE_V2 = 1 << 29;
+        const MIRROR = 1 << 30;
+    }
+}
+
+/// Acronym of every mod, in the order they are displayed.
+const ACRONYMS: [(Mods, &str); 31] = [
+    (Mods::NO_FAIL, "NF"),
+    (Mods::EASY, "EZ"),
+    (Mods::TOUCH_DEVICE, "TD"),
+    (Mods::HIDDEN, "HD"),
+    (Mods::HARD_ROCK, "HR"),
+    (Mods::SUDDEN_DEATH, "SD"),
+    (Mods::DOUBLE_TIME, "DT"),
+    (Mods::RELAX, "RX"),
+    (Mods::HALF_TIME, "HT"),
+    (Mods::NIGHTCORE, "NC"),
+    (Mods::FLASHLIGHT, "FL"),
+    (Mods::AUTOPLAY, "AT"),
+    (Mods::SPUN_OUT, "SO"),
+    (Mods::AUTOPILOT, "AP"),
+    (Mods::PERFECT, "PF"),
+    (Mods::KEY4, "4K"),
+    (Mods::KEY5, "5K"),
+    (Mods::KEY6, "6K"),
+    (Mods::KEY7, "7K"),
+    (Mods::KEY8, "8K"),
+    (Mods::FADE_IN, "FI"),
+    (Mods::RANDOM, "RD"),
+    (Mods::CINEMA, "CN"),
+    (Mods::TARGET_PRACTICE, "TP"),
+    (Mods::KEY9, "9K"),
+    (Mods::KEY_COOP, "CO"),
+    (Mods::KEY1, "1K"),
+    (Mods::KEY3, "3K"),
+    (Mods::KEY2, "2K"),
+    (Mods::SCORE_V2, "V2"),
+    (Mods::MIRROR, "MR"),
+];
+
+/// Pairs of mods that cannot be enabled together.
+const INCOMPATIBLE: [(Mods, Mods); 18] = [
+    (Mods::EASY, Mods::HARD_ROCK),
+    (Mods::HALF_TIME, Mods::DOUBLE_TIME),
+    (Mods::HALF_TIME, Mods::NIGHTCORE),
+    (Mods::NO_FAIL, Mods::SUDDEN_DEATH),
+    (Mods::NO_FAIL, Mods::PERFECT),
+    (Mods::NO_FAIL, Mods::RELAX),
+    (Mods::NO_FAIL, Mods::AUTOPILOT),
+    (Mods::RELAX, Mods::AUTOPILOT),
+    (Mods::RELAX, Mods::SUDDEN_DEATH),
+    (Mods::RELAX, Mods::PERFECT),
+    (Mods::AUTOPILOT, Mods::SUDDEN_DEATH),
+    (Mods::AUTOPILOT, Mods::PERFECT),
+    (Mods::AUTOPILOT, Mods::SPUN_OUT),
+    (Mods::AUTOPLAY, Mods::RELAX.union(Mods::AUTOPILOT)),
+    (Mods::AUTOPLAY, Mods::SUDDEN_DEATH.union(Mods::PERFECT)),
+    (Mods::AUTOPLAY, Mods::CINEMA),
+    (Mods::HIDDEN, Mods::FADE_IN),
+    (Mods::TARGET_PRACTICE, Mods::AUTOPLAY.union(Mods::CINEMA)),
+];
+
+impl Mods {
+    /// Every osu!mania key count mod.
+    pub const KEY_MODS: Mods = Mods::KEY1
+        .union(Mods::KEY2)
+        .union(Mods::KEY3)
+        .union(Mods::KEY4)
+        .union(Mods::KEY5)
+        .union(Mods::KEY6)
+        .union(Mods::KEY7)
+        .union(Mods::KEY8)
+        .union(Mods::KEY9);
+
+    /// Mods that change the playback rate.
+    pub const SPEED_CHANGING: Mods = Mods::DOUBLE_TIME
+        .union(Mods::NIGHTCORE)
+        .union(Mods::HALF_TIME);
+
+    /// Mods available in every game mode.
+    const COMMON: Mods = Mods::NO_FAIL
+        .union(Mods::EASY)
+        .union(Mods::HIDDEN)
+        .union(Mods::HARD_ROCK)
+        .union(Mods::SUDDEN_DEATH)
+        .union(Mods::DOUBLE_TIME)
+        .union(Mods::HALF_TIME)
+        .union(Mods::NIGHTCORE)
+        .union(Mods::FLASHLIGHT)
+        .union(Mods::AUTOPLAY)
+        .union(Mods::PERFECT)
+        .union(Mods::CINEMA)
+        .union(Mods::SCORE_V2);
+
+    /// Converts a legacy mods bitmask, ignoring unknown bits.
+    #[inline]
+    pub const fn from_legacy(bits: u32) -> Self {
+        Self::from_bits_truncate(bits)
+    }
+
+    /// Returns the legacy mods bitmask, with [`DOUBLE_TIME`][Mods::DOUBLE_TIME] set along with
+    /// [`NIGHTCORE`][Mods::NIGHTCORE] and [`SUDDEN_DEATH`][Mods::SUDDEN_DEATH] along with
+    /// [`PERFECT`][Mods::PERFECT].
+    #[inline]
+    pub const fn to_legacy(self) -> u32 {
+        self.normalized().bits()
+    }
+
+    /// Adds the mods implied by others, [`DOUBLE_TIME`][Mods::DOUBLE_TIME] for
+    /// [`NIGHTCORE`][Mods::NIGHTCORE] and [`SUDDEN_DEATH`][Mods::SUDDEN_DEATH] for
+    /// [`PERFECT`][Mods::PERFECT].
+    #[inline]
+    pub const fn normalized(self) -> Self {
+        let mut mods = self;
+        if mods.contains(Mods::NIGHTCORE) {
+            mods = mods.union(Mods::DOUBLE_TIME);
+        }
+        if mods.contains(Mods::PERFECT) {
+            mods = mods.union(Mods::SUDDEN_DEATH);
+        }
+        mods
+    }
+
+    /// Returns the mods that can be selected in `mode`.
+    #[inline]
+    pub const fn available_in(mode: Mode) -> Self {
+        match mode {
+            Mode::Standard => Self::COMMON
+                .union(Mods::TOUCH_DEVICE)
+                .union(Mods::RELAX)
+                .union(Mods::SPUN_OUT)
+                .union(Mods::AUTOPILOT)
+                .union(Mods::TARGET_PRACTICE),
+            Mode::Taiko | Mode::Catch => Self::COMMON.union(Mods::RELAX),
+            Mode::Mania => Self::COMMON
+                .union(Mods::KEY_MODS)
+                .union(Mods::KEY_COOP)
+                .union(Mods::FADE_IN)
+                .union(Mods::RANDOM)
+                .union(Mods::MIRROR),
+        }
+    }
+
+    /// Whether every mod can be selected in `mode`.
+    #[inline]
+    pub const fn is_available_in(self, mode: Mode) -> bool {
+        Self::available_in(mode).contains(self)
+    }
+
+    /// Iterates over the pairs of enabled mods that cannot be enabled together.
+    pub fn conflicts(self) -> impl Iterator<Item = (Mods, Mods)> {
+        let key_mods = self.intersection(Mods::KEY_MODS);
+        let first_key = key_mods.iter().next().unwrap_or(Mods::empty());
+        let extra_keys = key_mods.difference(first_key);
+
+        INCOMPATIBLE
+            .into_iter()
+            .filter_map(move |(a, b)| {
+                let a = self.intersection(a);
+                let b = self.intersection(b);
+                (!a.is_empty() && !b.is_empty()).then_some((a, b))
+            })
+            .chain(extra_keys.iter().map(move |extra| (first_key, extra)))
+    }
+
+    /// Checks that the mods can be enabled together and, if `mode` is given, that they can be
+    /// selected in it.
+    ///
+    /// This function will return [`Err(Error::InvalidInput)`][Error::InvalidInput] otherwise.
+    pub fn validate(self, mode: Option<Mode>) -> Result<(), Error> {
+        if self.conflicts().next().is_some() {
+            return Err(Error::InvalidInput("incompatible mods"));
+        }
+        if mode.is_some_and(|mode| !self.is_available_in(mode)) {
+            return Err(Error::InvalidInput("mods not available in this mode"));
+        }
+
+        Ok(())
+    }
+
+    /// Returns the playback rate, 1.5 with [`DOUBLE_TIME`][Mods::DOUBLE_TIME] or
+    /// [`NIGHTCORE`][Mods::NIGHTCORE], 0.75 with [`HALF_TIME`][Mods::HALF_TIME] and 1 otherwise.
+    #[inline]
+    pub const fn clock_rate(self) -> f64 {
+        if self.intersects(Mods::DOUBLE_TIME.union(Mods::NIGHTCORE)) {
+            1.5
+        } else if self.contains(Mods::HALF_TIME) {
+            0.75
+        } else {
+            1.0
+        }
+    }
+
+    /// Returns the number of keys forced by a key mod, if any.
+    #[inline]
+    pub fn key_count(self) -> Option<u32> {
+        [
+            (Mods::KEY1, 1),
+            (Mods::KEY2, 2),
+            (Mods::KEY3, 3),
+            (Mods::KEY4, 4),
+            (Mods::KEY5, 5),
+            (Mods::KEY6, 6),
+            (Mods::KEY7, 7),
+            (Mods::KEY8, 8),
+            (Mods::KEY9, 9),
+        ]
+        .into_iter()
+        .find(|(key, _)| self.contains(*key))
+        .map(|(_, count)| count)
+    }
+
+    /// Iterates over the acronyms of the enabled mods, leaving out
+    /// [`DOUBLE_TIME`][Mods::DOUBLE_TIME] with [`NIGHTCORE`][Mods::NIGHTCORE] and
+    /// [`SUDDEN_DEATH`][Mods::SUDDEN_DEATH] with [`PERFECT`][Mods::PERFECT].
+    pub fn acronyms(self) -> impl Iterator<Item = &'static str> {
+        let mut mods = self;
+        if mods.contains(Mods::NIGHTCORE) {
+            mods.remove(Mods::DOUBLE_TIME);
+        }
+        if mods.contains(Mods::PERFECT) {
+            mods.remove(Mods::SUDDEN_DEATH);
+        }
+
+        ACRONYMS
+            .into_iter()
+            .filter(move |(m, _)| mods.contains(*m))
+            .map(|(_, acronym)| acronym)
+    }
+
+    /// Returns the mod with the given two-letter acronym, case-insensitively.
+    #[inline]
+    pub fn from_acronym(acronym: &str) -> Option<Self> {
+        ACRONYMS
+            .into_iter()
+            .find(|(_, a)| a.eq_ignore_ascii_case(acronym))
+            .map(|(m, _)| m)
+    }
+}
+
+impl From<u32> for Mods {
+    #[inline]
+    fn from(bits: u32) -> Self {
+        Self::from_legacy(bits)
+    }
+}
+
+impl From<Mods> for u32 {
+    #[inline]
+    fn from(mods: Mods) -> Self {
+        mods.to_legacy()
+    }
+}
+
+impl FromStr for Mods {
+    type Err = Error;
+
+    /// Attempts to convert a string of concatenated acronyms like `HDDTHR` into Mods.
+    ///
+    /// Acronyms are case-insensitive and may be separated by spaces, commas or `+`; an empty
+    /// string, `NM` and `None` parse as no mods.
+    ///
+    /// This function will return [`Err(Error::InvalidInput)`][Error::InvalidInput] if an acronym
+    /// is unknown.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let acronyms = s
+            .chars()
+            .filter(|c| !matches!(c, ' ' | ',' | '+'))
+            .collect::<String>();
+
+        if acronyms.eq_ignore_ascii_case("NM") || acronyms.eq_ignore_ascii_case("None") {
+            return Ok(Mods::empty());
+        }
+        if acronyms.len() % 2 != 0 || !acronyms.is_ascii() {
+            return Err(Error::InvalidInput("invalid mods acronyms"));
+        }
+
+        let mut mods = Mods::empty();
+        for i in (0..acronyms.len()).step_by(2) {
+            mods |= Mods::from_acronym(&acronyms[i..i + 2])
+                .ok_or(Error::InvalidInput("unknown mod acronym"))?;
+        }
+
+        Ok(mods.normalized())
+    }
+}
+
+impl fmt::Display for Mods {
+    /// Formats the mods as concatenated acronyms like `HDDTHR`, or `NM` if there are none.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return f.write_str("NM");
+        }
+
+        for acronym in self.acronyms() {
+            f.write_str(acronym)?;
+        }
+
+        Ok(())
+    }
+}
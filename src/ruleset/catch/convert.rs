@@ -0,0 +1,23 @@
+//! Conversion of osu!standard beatmaps to osu!catch, following the rules of osu!stable.
+
+use crate::{file::beatmap::Beatmap, mode::Mode, mods::Mods, Error};
+
+use super::object::CatchObject;
+
+/// Converts the objects of an osu!standard `beatmap` to osu!catch, played with `mods`.
+///
+/// Circles become fruits, sliders become juice streams of fruits and droplets along their path,
+/// and spinners become banana showers. osu!catch beatmaps are made of the same objects, so the
+/// objects go through the same processing as theirs, see [`CatchObject::from_beatmap`].
+///
+/// This function will return [Err(Error::InvalidInput)][Error::InvalidInput] if `beatmap` is
+/// not an osu!standard beatmap.
+pub fn convert(beatmap: &Beatmap, mods: Mods) -> Result<Vec<CatchObject>, Error> {
+    if beatmap.general.mode != Mode::Standard {
+        return Err(Error::InvalidInput(
+            "only osu!standard beatmaps can be converted",
+        ));
+    }
+
+    Ok(CatchObject::from_beatmap(beatmap, mods))
+}
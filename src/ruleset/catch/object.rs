@@ -0,0 +1,455 @@
+use crate::{
+    file::beatmap::{
+        hit_objects::{
+            slider_events::{SliderEvent, SliderEventKind, SliderEvents},
+            HitObject, HitObjectKind, PLAYFIELD_WIDTH,
+        },
+        Beatmap,
+    },
+    mode::Mode,
+    mods::Mods,
+    ruleset::random::LegacyRandom,
+    time::Time,
+};
+
+/// Seed of the random generator placing bananas and offsetting objects.
+pub const RNG_SEED: i32 = 1337;
+/// Width of the catcher at a circle size of 5, in osu! pixels.
+const BASE_CATCHER_SIZE: f32 = 106.75;
+/// Part of the catcher's width objects can be caught with.
+const ALLOWED_CATCH_RANGE: f32 = 0.8;
+/// Speed of the dashing catcher in osu! pixels per millisecond.
+const BASE_DASH_SPEED: f64 = 1.0;
+
+/// Returns the width of the catcher in osu! pixels for a circle size, excluding the margins
+/// objects cannot be caught with.
+#[inline]
+pub fn catcher_width(cs: f32) -> f32 {
+    let scale = 1.0 - 0.7 * (cs - 5.0) / 5.0;
+    BASE_CATCHER_SIZE * scale.abs() * ALLOWED_CATCH_RANGE
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PalpableKind {
+    Fruit,
+    /// Large droplet on a slider tick.
+    Droplet,
+    /// Small droplet between the other objects of a juice stream, only affecting accuracy.
+    TinyDroplet,
+    Banana,
+}
+
+/// An object to be caught: a fruit, a droplet or a banana.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PalpableObject {
+    pub kind: PalpableKind,
+    pub time: Time,
+    /// Horizontal position in osu! pixels, before the offsets of
+    /// [`apply_position_offsets`].
+    pub x: f32,
+    pub x_offset: f32,
+    /// Whether the catcher has to hyperdash to catch the next object, see [`apply_hyper_dashes`].
+    pub hyper_dash: bool,
+    /// Distance the catcher can still dash before needing a hyperdash to the next object.
+    pub distance_to_hyper_dash: f32,
+}
+
+impl PalpableObject {
+    #[inline]
+    pub fn new(kind: PalpableKind, time: Time, x: f32) -> Self {
+        Self {
+            kind,
+            time,
+            x,
+            x_offset: 0.0,
+            hyper_dash: false,
+            distance_to_hyper_dash: 0.0,
+        }
+    }
+
+    /// Returns the position of the object with its offset applied.
+    #[inline]
+    pub fn effective_x(&self) -> f32 {
+        self.x + self.x_offset
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CatchObjectKind {
+    Fruit(PalpableObject),
+    /// Fruits, droplets and tiny droplets along the path of a slider.
+    JuiceStream {
+        nested: Vec<PalpableObject>,
+        /// Position of the last anchor point of the slider, which osu!stable uses as the end of
+        /// the stream when offsetting objects.
+        last_point_x: f32,
+    },
+    /// Bananas every 100ms or less for the duration of a spinner.
+    BananaShower {
+        bananas: Vec<PalpableObject>,
+    },
+}
+
+/// An osu!catch hit object and the objects nested in it.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CatchObject {
+    pub time: Time,
+    pub end_time: Time,
+    pub x: f32,
+    pub kind: CatchObjectKind,
+}
+
+impl CatchObject {
+    /// Builds the objects of an osu!catch `beatmap` played with `mods`, or of an osu!standard
+    /// beatmap converted to osu!catch, with the position offsets and hyperdashes applied.
+    pub fn from_beatmap(beatmap: &Beatmap, mods: Mods) -> Vec<Self> {
+        let difficulty = &beatmap.difficulty;
+        let control_points = beatmap.control_points();
+        let version = beatmap.file_format_version;
+
+        let mut objects = beatmap
+            .hit_objects
+            .iter()
+            .map(|hit_object| {
+                match hit_object.slider_events(difficulty, &control_points, version) {
+                    Some(events) => Self::juice_stream(hit_object, &events),
+                    None => Self::from_hit_object(hit_object),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        apply_position_offsets(&mut objects, mods.contains(Mods::HARD_ROCK));
+
+        let cs = difficulty.apply_mods(Mode::Catch, mods).cs;
+        apply_hyper_dashes(&mut objects, cs);
+
+        objects
+    }
+
+    /// Returns the fruit or banana shower of a hit object other than a slider.
+    pub fn from_hit_object(hit_object: &HitObject) -> Self {
+        let time = hit_object.time;
+        let end_time = hit_object.end_time();
+
+        let kind = match hit_object.kind {
+            HitObjectKind::Spinner(_) => CatchObjectKind::BananaShower {
+                bananas: bananas(time, end_time),
+            },
+            _ => CatchObjectKind::Fruit(PalpableObject::new(
+                PalpableKind::Fruit,
+                time,
+                hit_object.position.x,
+            )),
+        };
+
+        Self {
+            time,
+            end_time,
+            x: hit_object.position.x,
+            kind,
+        }
+    }
+
+    /// Returns the juice stream of a slider, placing a fruit on its head, repeats and tail, a
+    /// droplet on its ticks and tiny droplets in between.
+    pub fn juice_stream(hit_object: &HitObject, events: &SliderEvents) -> Self {
+        let mut nested = Vec::new();
+        let mut last_event = None;
+
+        for event in &events.events {
+            if let Some(last) = last_event {
+                tiny_droplets(&mut nested, hit_object, events, last, event);
+            }
+            // tiny droplets are placed up to the legacy last tick, even though it is not an object
+            last_event = Some(event);
+
+            let kind = match event.kind {
+                SliderEventKind::Tick => PalpableKind::Droplet,
+                SliderEventKind::LegacyLastTick => continue,
+                _ => PalpableKind::Fruit,
+            };
+            nested.push(PalpableObject::new(kind, event.time, event.position.x));
+        }
+
+        let last_point_x = match &hit_object.kind {
+            HitObjectKind::Slider(slider) => slider.curve.points().last().map(|point| point.x),
+            _ => None,
+        };
+
+        Self {
+            time: hit_object.time,
+            end_time: events.end_time,
+            x: hit_object.position.x,
+            kind: CatchObjectKind::JuiceStream {
+                nested,
+                last_point_x: last_point_x.unwrap_or(hit_object.position.x),
+            },
+        }
+    }
+
+    /// Returns the objects to be caught, in chronological order.
+    #[inline]
+    pub fn palpable(&self) -> &[PalpableObject] {
+        match &self.kind {
+            CatchObjectKind::Fruit(fruit) => std::slice::from_ref(fruit),
+            CatchObjectKind::JuiceStream { nested, .. } => nested,
+            CatchObjectKind::BananaShower { bananas } => bananas,
+        }
+    }
+
+    #[inline]
+    pub fn palpable_mut(&mut self) -> &mut [PalpableObject] {
+        match &mut self.kind {
+            CatchObjectKind::Fruit(fruit) => std::slice::from_mut(fruit),
+            CatchObjectKind::JuiceStream { nested, .. } => nested,
+            CatchObjectKind::BananaShower { bananas } => bananas,
+        }
+    }
+
+    /// Returns the combo the object gives: one per fruit and droplet.
+    #[inline]
+    pub fn max_combo(&self) -> u32 {
+        self.palpable()
+            .iter()
+            .filter(|o| matches!(o.kind, PalpableKind::Fruit | PalpableKind::Droplet))
+            .count() as u32
+    }
+}
+
+/// Returns `true` if `beatmap` is an osu!standard beatmap played in osu!catch.
+#[inline]
+pub fn is_convert(beatmap: &Beatmap) -> bool {
+    beatmap.general.mode != Mode::Catch
+}
+
+/// Places tiny droplets between two events of a slider more than 80ms apart.
+fn tiny_droplets(
+    nested: &mut Vec<PalpableObject>,
+    hit_object: &HitObject,
+    events: &SliderEvents,
+    last: &SliderEvent,
+    event: &SliderEvent,
+) {
+    // osu!stable compares the times as whole milliseconds
+    let since_last_tick = (event.time.as_millis() as i32 - last.time.as_millis() as i32) as f64;
+    if since_last_tick <= 80.0 {
+        return;
+    }
+
+    let mut time_between_tiny = since_last_tick;
+    while time_between_tiny > 100.0 {
+        time_between_tiny /= 2.0;
+    }
+
+    let mut t = time_between_tiny;
+    while t < since_last_tick {
+        let progress =
+            last.path_progress + (t / since_last_tick) * (event.path_progress - last.path_progress);
+        let x = hit_object.position.x + events.path.position_at(progress).x;
+        nested.push(PalpableObject::new(
+            PalpableKind::TinyDroplet,
+            last.time + Time(t),
+            x,
+        ));
+
+        t += time_between_tiny;
+    }
+}
+
+/// Places bananas every 100ms or less between `time` and `end_time`; their positions are
+/// random, see [`apply_position_offsets`].
+fn bananas(time: Time, end_time: Time) -> Vec<PalpableObject> {
+    let mut spacing = (end_time - time).as_millis();
+    while spacing > 100.0 {
+        spacing /= 2.0;
+    }
+
+    let mut bananas = Vec::new();
+    if spacing <= 0.0 {
+        return bananas;
+    }
+
+    let mut t = time;
+    while t <= end_time {
+        bananas.push(PalpableObject::new(PalpableKind::Banana, t, 0.0));
+        t += Time(spacing);
+    }
+
+    bananas
+}
+
+/// Offsets the objects with the same random generator as osu!stable: bananas are placed at
+/// random, tiny droplets are moved randomly by up to 20 pixels and, with
+/// [`HARD_ROCK`][Mods::HARD_ROCK], fruits close to the previous one are moved further away.
+pub fn apply_position_offsets(objects: &mut [CatchObject], hard_rock: bool) {
+    let mut rng = LegacyRandom::new(RNG_SEED);
+    let mut last_position = None;
+    let mut last_time = Time::ZERO;
+
+    for object in objects {
+        let time = object.time;
+
+        match &mut object.kind {
+            CatchObjectKind::Fruit(fruit) => {
+                fruit.x_offset = 0.0;
+                if hard_rock {
+                    apply_hard_rock_offset(fruit, &mut last_position, &mut last_time, &mut rng);
+                }
+            }
+            CatchObjectKind::BananaShower { bananas } => {
+                for banana in bananas {
+                    banana.x_offset = (rng.next_f64() * PLAYFIELD_WIDTH as f64) as f32;
+                    // osu!stable also drew the type, rotation and colour of the banana
+                    rng.next_u32();
+                    rng.next_u32();
+                    rng.next_u32();
+                }
+            }
+            CatchObjectKind::JuiceStream {
+                nested,
+                last_point_x,
+            } => {
+                // osu!stable used the last anchor point instead of the end of the path, and the
+                // start time of the stream instead of its end time
+                last_position = Some(*last_point_x);
+                last_time = time;
+
+                for object in nested {
+                    object.x_offset = 0.0;
+                    match object.kind {
+                        PalpableKind::TinyDroplet => {
+                            let offset = rng.next_in_range(-20.0, 20.0) as f32;
+                            object.x_offset = offset.clamp(-object.x, PLAYFIELD_WIDTH - object.x);
+                        }
+                        // osu!stable drew the rotation of the droplet
+                        PalpableKind::Droplet => {
+                            rng.next_u32();
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn apply_hard_rock_offset(
+    fruit: &mut PalpableObject,
+    last_position: &mut Option<f32>,
+    last_time: &mut Time,
+    rng: &mut LegacyRandom,
+) {
+    let mut position = fruit.x;
+    let time = fruit.time;
+
+    // objects at 0 are mistaken for the first object, as osu!stable does
+    let Some(last) = last_position.filter(|&last| last != 0.0) else {
+        *last_position = Some(position);
+        *last_time = time;
+        return;
+    };
+
+    let position_diff = position - last;
+    // osu!stable computed the time difference as an integer
+    let time_diff = (time - *last_time).as_millis() as i32;
+
+    if time_diff > 1000 {
+        *last_position = Some(position);
+        *last_time = time;
+        return;
+    }
+
+    if position_diff == 0.0 {
+        // the last position is kept, so that stacked fruits are all offset from the first one
+        let right = rng.next_bool();
+        let offset = rng
+            .next_in_range(0.0, (time_diff as f64 / 4.0).max(0.0))
+            .min(20) as f32;
+
+        if right {
+            if position + offset <= PLAYFIELD_WIDTH {
+                position += offset;
+            } else {
+                position -= offset;
+            }
+        } else if position - offset >= 0.0 {
+            position -= offset;
+        } else {
+            position += offset;
+        }
+
+        fruit.x_offset = position - fruit.x;
+        return;
+    }
+
+    if position_diff.abs() < (time_diff / 3) as f32 {
+        if position_diff > 0.0 {
+            if position + position_diff < PLAYFIELD_WIDTH {
+                position += position_diff;
+            }
+        } else if position + position_diff > 0.0 {
+            position += position_diff;
+        }
+    }
+
+    fruit.x_offset = position - fruit.x;
+
+    *last_position = Some(position);
+    *last_time = time;
+}
+
+/// Marks the fruits and droplets the catcher cannot reach in time by dashing, for a catcher of
+/// circle size `cs`.
+pub fn apply_hyper_dashes(objects: &mut [CatchObject], cs: f32) {
+    let mut palpable = objects
+        .iter_mut()
+        .filter(|o| !matches!(o.kind, CatchObjectKind::BananaShower { .. }))
+        .flat_map(|o| o.palpable_mut())
+        .filter(|o| o.kind != PalpableKind::TinyDroplet)
+        .collect::<Vec<_>>();
+    palpable.sort_by(|a, b| a.time.total_cmp(&b.time));
+
+    // osu!stable used the full width of the catcher, not only the part objects can be caught with
+    let half_catcher_width = (catcher_width(cs) / 2.0 / ALLOWED_CATCH_RANGE) as f64;
+
+    let mut last_direction = 0;
+    let mut last_excess = half_catcher_width;
+
+    for i in 0..palpable.len().saturating_sub(1) {
+        let next_x = palpable[i + 1].effective_x();
+        let next_time = palpable[i + 1].time;
+        let current = &mut palpable[i];
+
+        current.hyper_dash = false;
+        current.distance_to_hyper_dash = 0.0;
+
+        let direction = if next_x > current.effective_x() {
+            1
+        } else {
+            -1
+        };
+        // a quarter of a frame of leniency
+        let time_to_next = (next_time - current.time).as_millis() - 1000.0 / 60.0 / 4.0;
+        let distance_to_next = (next_x - current.effective_x()).abs() as f64
+            - if last_direction == direction {
+                last_excess
+            } else {
+                half_catcher_width
+            };
+        let distance_to_hyper = (time_to_next * BASE_DASH_SPEED - distance_to_next) as f32;
+
+        if distance_to_hyper < 0.0 {
+            current.hyper_dash = true;
+            last_excess = half_catcher_width;
+        } else {
+            current.distance_to_hyper_dash = distance_to_hyper;
+            last_excess = (distance_to_hyper as f64).clamp(0.0, half_catcher_width);
+        }
+
+        last_direction = direction;
+    }
+}
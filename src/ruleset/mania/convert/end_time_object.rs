@@ -0,0 +1,61 @@
+use crate::{file::beatmap::hit_sound::HitSound, time::Time};
+
+use super::pattern::{Pattern, PatternGenerator};
+
+/// Generates the note or hold note of a spinner.
+pub(super) struct EndTimeObjectPatternGenerator<'a> {
+    inner: PatternGenerator<'a>,
+    /// End time truncated to whole milliseconds, as osu!stable does.
+    end_time: Time,
+    /// Whether the note avoids the columns of the previous pattern.
+    force_not_stack: bool,
+}
+
+impl<'a> EndTimeObjectPatternGenerator<'a> {
+    pub fn new(inner: PatternGenerator<'a>, end_time: Time) -> Self {
+        let force_not_stack = inner.previous_pattern.column_count() != inner.total_columns;
+
+        Self {
+            inner,
+            end_time: Time(end_time.as_millis().trunc()),
+            force_not_stack,
+        }
+    }
+
+    pub fn generate(mut self) -> Pattern {
+        let hit_object = self.inner.hit_object;
+        let duration = self.end_time - hit_object.time;
+
+        let column = if self.inner.total_columns == 8 {
+            if hit_object.hit_sound.contains(HitSound::FINISH) && duration.as_millis() < 1000.0 {
+                0
+            } else {
+                self.random_column(self.inner.random_start)
+            }
+        } else {
+            self.random_column(0)
+        };
+
+        let end_time = if duration.as_millis() >= 100.0 {
+            self.end_time
+        } else {
+            hit_object.time
+        };
+
+        let mut pattern = Pattern::default();
+        pattern.add(column, hit_object.time, end_time);
+        pattern
+    }
+
+    fn random_column(&mut self, lower: i32) -> i32 {
+        let upper = self.inner.total_columns;
+        let previous_pattern = self.inner.previous_pattern;
+        let force_not_stack = self.force_not_stack;
+
+        let initial = self.inner.random_column(lower, upper);
+        self.inner
+            .find_available_column(initial, lower, upper, |c| {
+                !force_not_stack || !previous_pattern.has_column(c)
+            })
+    }
+}
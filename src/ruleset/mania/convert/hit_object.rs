@@ -0,0 +1,406 @@
+use nalgebra_glm::Vec2;
+
+use crate::{file::beatmap::hit_sound::HitSound, ruleset::random::LegacyRandom};
+
+use super::pattern::{Pattern, PatternGenerator, PatternType};
+
+/// Generates the notes of a circle, from its distance to the previous object in time and space.
+pub(super) struct HitObjectPatternGenerator<'a> {
+    inner: PatternGenerator<'a>,
+    convert_type: PatternType,
+    /// Direction of the stairs of fast streams, [`PatternType::STAIR`] or
+    /// [`PatternType::REVERSE_STAIR`].
+    stair_type: PatternType,
+}
+
+impl<'a> HitObjectPatternGenerator<'a> {
+    pub fn new(
+        inner: PatternGenerator<'a>,
+        previous_time: f64,
+        previous_position: Vec2,
+        density: f64,
+        last_stair: PatternType,
+    ) -> Self {
+        let hit_object = inner.hit_object;
+        let beat_length = inner.control_points.beat_length_at(hit_object.time);
+        let kiai = inner.control_points.kiai_at(hit_object.time);
+
+        let position_separation = (hit_object.position - previous_position).norm();
+        let time_separation = hit_object.time.as_millis() - previous_time;
+
+        let mut convert_type = PatternType::empty();
+        if time_separation <= 80.0 {
+            // more than 187 BPM
+            convert_type |= PatternType::FORCE_NOT_STACK | PatternType::KEEP_SINGLE;
+        } else if time_separation <= 95.0 {
+            // more than 157 BPM
+            convert_type |= PatternType::FORCE_NOT_STACK | PatternType::KEEP_SINGLE | last_stair;
+        } else if time_separation <= 105.0 {
+            // more than 140 BPM
+            convert_type |= PatternType::FORCE_NOT_STACK | PatternType::LOW_PROBABILITY;
+        } else if time_separation <= 125.0 {
+            // more than 120 BPM
+            convert_type |= PatternType::FORCE_NOT_STACK;
+        } else if time_separation <= 135.0 && position_separation < 20.0 {
+            // more than 111 BPM stream
+            convert_type |= PatternType::CYCLE | PatternType::KEEP_SINGLE;
+        } else if time_separation <= 150.0 && position_separation < 20.0 {
+            // more than 100 BPM stream
+            convert_type |= PatternType::FORCE_STACK | PatternType::LOW_PROBABILITY;
+        } else if position_separation < 20.0 && density >= beat_length / 2.5 {
+            // low density stream
+            convert_type |= PatternType::REVERSE | PatternType::LOW_PROBABILITY;
+        } else if density < beat_length / 2.5 || kiai {
+            // high density
+        } else {
+            convert_type |= PatternType::LOW_PROBABILITY;
+        }
+
+        if !convert_type.contains(PatternType::KEEP_SINGLE) {
+            if hit_object.hit_sound.contains(HitSound::FINISH) && inner.total_columns != 8 {
+                convert_type |= PatternType::MIRROR;
+            } else if hit_object.hit_sound.contains(HitSound::CLAP) {
+                convert_type |= PatternType::GATHERED;
+            }
+        }
+
+        Self {
+            inner,
+            convert_type,
+            stair_type: last_stair,
+        }
+    }
+
+    /// Returns the notes of the circle, and the direction of the stairs for the next circle:
+    /// they go back once they reach the last column, and forward again once they reach the first
+    /// one.
+    pub fn generate(mut self) -> (Pattern, PatternType) {
+        let pattern = self.generate_pattern();
+
+        let (total_columns, random_start) = (self.inner.total_columns, self.inner.random_start);
+        for column in pattern.objects.iter().map(|o| o.column as i32) {
+            if self.convert_type.contains(PatternType::STAIR) && column == total_columns - 1 {
+                self.stair_type = PatternType::REVERSE_STAIR;
+            }
+            if self.convert_type.contains(PatternType::REVERSE_STAIR) && column == random_start {
+                self.stair_type = PatternType::STAIR;
+            }
+        }
+
+        (pattern, self.stair_type)
+    }
+
+    fn generate_pattern(&mut self) -> Pattern {
+        let total_columns = self.inner.total_columns;
+        let random_start = self.inner.random_start;
+        let previous_pattern = self.inner.previous_pattern;
+        let convert_type = self.convert_type;
+
+        if total_columns == 1 {
+            let mut pattern = Pattern::default();
+            self.add_to_pattern(&mut pattern, 0);
+            return pattern;
+        }
+
+        let last_column = previous_pattern.first_column();
+
+        if convert_type.contains(PatternType::REVERSE) && !previous_pattern.is_empty() {
+            // mirror the previous pattern
+            let mut pattern = Pattern::default();
+            for column in random_start..total_columns {
+                if previous_pattern.has_column(column) {
+                    self.add_to_pattern(&mut pattern, random_start + total_columns - column - 1);
+                }
+            }
+            return pattern;
+        }
+
+        if convert_type.contains(PatternType::CYCLE)
+            && previous_pattern.len() == 1
+            // don't overload the special column of 7K+1
+            && (total_columns != 8 || last_column != 0)
+            // nor play the centre column twice in a row
+            && (total_columns % 2 == 0 || last_column != total_columns / 2)
+        {
+            // mirror the single note of the previous pattern
+            let mut pattern = Pattern::default();
+            self.add_to_pattern(&mut pattern, random_start + total_columns - last_column - 1);
+            return pattern;
+        }
+
+        if convert_type.contains(PatternType::FORCE_STACK) && !previous_pattern.is_empty() {
+            // place notes on the columns of the previous pattern
+            let mut pattern = Pattern::default();
+            for column in random_start..total_columns {
+                if previous_pattern.has_column(column) {
+                    self.add_to_pattern(&mut pattern, column);
+                }
+            }
+            return pattern;
+        }
+
+        if previous_pattern.len() == 1 {
+            if convert_type.contains(PatternType::STAIR) {
+                // place a note on the next column, wrapping around to the first one
+                let mut column = last_column + 1;
+                if column == total_columns {
+                    column = random_start;
+                }
+
+                let mut pattern = Pattern::default();
+                self.add_to_pattern(&mut pattern, column);
+                return pattern;
+            }
+
+            if convert_type.contains(PatternType::REVERSE_STAIR) {
+                // place a note on the previous column, wrapping around to the last one
+                let mut column = last_column - 1;
+                if column == random_start - 1 {
+                    column = total_columns - 1;
+                }
+
+                let mut pattern = Pattern::default();
+                self.add_to_pattern(&mut pattern, column);
+                return pattern;
+            }
+        }
+
+        if convert_type.contains(PatternType::KEEP_SINGLE) {
+            return self.random_notes(1);
+        }
+
+        let conversion_difficulty = self.inner.conversion_difficulty;
+        let low_probability = convert_type.contains(PatternType::LOW_PROBABILITY);
+
+        if convert_type.contains(PatternType::MIRROR) {
+            return if conversion_difficulty > 6.5 {
+                self.random_pattern_with_mirrored(0.12, 0.38, 0.12)
+            } else if conversion_difficulty > 4.0 {
+                self.random_pattern_with_mirrored(0.12, 0.17, 0.0)
+            } else {
+                self.random_pattern_with_mirrored(0.12, 0.0, 0.0)
+            };
+        }
+
+        if conversion_difficulty > 6.5 {
+            if low_probability {
+                self.random_pattern(0.78, 0.42, 0.0, 0.0)
+            } else {
+                self.random_pattern(1.0, 0.62, 0.0, 0.0)
+            }
+        } else if conversion_difficulty > 4.0 {
+            if low_probability {
+                self.random_pattern(0.35, 0.08, 0.0, 0.0)
+            } else {
+                self.random_pattern(0.52, 0.15, 0.0, 0.0)
+            }
+        } else if conversion_difficulty > 2.0 {
+            if low_probability {
+                self.random_pattern(0.18, 0.0, 0.0, 0.0)
+            } else {
+                self.random_pattern(0.45, 0.0, 0.0, 0.0)
+            }
+        } else {
+            self.random_pattern(0.0, 0.0, 0.0, 0.0)
+        }
+    }
+
+    /// Places `note_count` notes on random columns, or adjacent ones for gathered patterns.
+    fn random_notes(&mut self, mut note_count: i32) -> Pattern {
+        let total_columns = self.inner.total_columns;
+        let random_start = self.inner.random_start;
+        let previous_pattern = self.inner.previous_pattern;
+
+        let mut pattern = Pattern::default();
+
+        let allow_stacking = !self.convert_type.contains(PatternType::FORCE_NOT_STACK);
+        if !allow_stacking {
+            note_count =
+                note_count.min(total_columns - random_start - previous_pattern.column_count());
+        }
+
+        let gathered = self.convert_type.contains(PatternType::GATHERED);
+        let next_column = |random: &mut LegacyRandom, last: i32| {
+            if gathered {
+                let next = last + 1;
+                if next == total_columns {
+                    random_start
+                } else {
+                    next
+                }
+            } else {
+                random.next_in_range(random_start as f64, total_columns as f64)
+            }
+        };
+
+        let mut column = self.inner.column(self.inner.hit_object.position.x, true);
+        for _ in 0..note_count {
+            column = self.inner.find_column_with(
+                column,
+                random_start,
+                total_columns,
+                next_column,
+                |c| !pattern.has_column(c) && (allow_stacking || !previous_pattern.has_column(c)),
+            );
+            self.add_to_pattern(&mut pattern, column);
+        }
+
+        pattern
+    }
+
+    /// Returns `true` if the special column of 7K+1 should be played along with the pattern.
+    #[inline]
+    fn has_special_column(&self) -> bool {
+        self.inner
+            .hit_object
+            .hit_sound
+            .contains(HitSound::CLAP | HitSound::FINISH)
+    }
+
+    fn random_pattern(&mut self, p2: f64, p3: f64, p4: f64, p5: f64) -> Pattern {
+        let note_count = self.random_note_count(p2, p3, p4, p5);
+        let mut pattern = self.random_notes(note_count);
+
+        if self.inner.random_start > 0 && self.has_special_column() {
+            self.add_to_pattern(&mut pattern, 0);
+        }
+
+        pattern
+    }
+
+    /// Places notes on the left half of the stage and mirrors them on the right half.
+    fn random_pattern_with_mirrored(
+        &mut self,
+        centre_probability: f64,
+        p2: f64,
+        p3: f64,
+    ) -> Pattern {
+        if self.convert_type.contains(PatternType::FORCE_NOT_STACK) {
+            return self.random_pattern(0.5 + p2 / 2.0, p2, (p2 + p3) / 2.0, p3);
+        }
+
+        let total_columns = self.inner.total_columns;
+        let random_start = self.inner.random_start;
+
+        let mut pattern = Pattern::default();
+
+        let (note_count, add_to_centre) =
+            self.random_note_count_mirrored(centre_probability, p2, p3);
+
+        let column_limit = (if total_columns % 2 == 0 {
+            total_columns
+        } else {
+            total_columns - 1
+        }) / 2;
+
+        let mut column = self.inner.random_column(random_start, column_limit);
+        for _ in 0..note_count {
+            column = self
+                .inner
+                .find_available_column(column, random_start, column_limit, |c| {
+                    !pattern.has_column(c)
+                });
+
+            self.add_to_pattern(&mut pattern, column);
+            self.add_to_pattern(&mut pattern, random_start + total_columns - column - 1);
+        }
+
+        if add_to_centre {
+            self.add_to_pattern(&mut pattern, total_columns / 2);
+        }
+
+        if random_start > 0 && self.has_special_column() {
+            self.add_to_pattern(&mut pattern, 0);
+        }
+
+        pattern
+    }
+
+    fn random_note_count(&mut self, mut p2: f64, mut p3: f64, mut p4: f64, mut p5: f64) -> i32 {
+        match self.inner.total_columns {
+            2 => {
+                p2 = 0.0;
+                p3 = 0.0;
+                p4 = 0.0;
+                p5 = 0.0;
+            }
+            3 => {
+                p2 = p2.min(0.1);
+                p3 = 0.0;
+                p4 = 0.0;
+                p5 = 0.0;
+            }
+            4 => {
+                p2 = p2.min(0.23);
+                p3 = p3.min(0.04);
+                p4 = 0.0;
+                p5 = 0.0;
+            }
+            5 => {
+                p3 = p3.min(0.15);
+                p4 = p4.min(0.03);
+                p5 = 0.0;
+            }
+            _ => {}
+        }
+
+        if self.inner.hit_object.hit_sound.contains(HitSound::CLAP) {
+            p2 = 1.0;
+        }
+
+        self.inner.random_note_count(p2, p3, p4, p5, 0.0)
+    }
+
+    /// Returns the number of mirrored note pairs, and whether a note is added to the centre.
+    fn random_note_count_mirrored(
+        &mut self,
+        mut centre_probability: f64,
+        mut p2: f64,
+        mut p3: f64,
+    ) -> (i32, bool) {
+        let total_columns = self.inner.total_columns;
+
+        // osu!stable compares to inverse probabilities, which are doubled for 4K and 6K
+        match total_columns {
+            2 => {
+                centre_probability = 0.0;
+                p2 = 0.0;
+                p3 = 0.0;
+            }
+            3 => {
+                centre_probability = centre_probability.min(0.03);
+                p2 = 0.0;
+                p3 = 0.0;
+            }
+            4 => {
+                centre_probability = 0.0;
+                p2 = 1.0 - ((1.0 - p2) * 2.0).max(0.8);
+                p3 = 0.0;
+            }
+            5 => {
+                centre_probability = centre_probability.min(0.03);
+                p3 = 0.0;
+            }
+            6 => {
+                centre_probability = 0.0;
+                p2 = 1.0 - ((1.0 - p2) * 2.0).max(0.5);
+                p3 = (p3 * 2.0).min(0.2);
+            }
+            _ => {}
+        }
+
+        let centre_value = self.inner.random.next_f64();
+        let note_count = self.inner.random_note_count(p2, p3, 0.0, 0.0, 0.0);
+
+        let add_to_centre =
+            total_columns % 2 != 0 && note_count != 3 && centre_value > 1.0 - centre_probability;
+
+        (note_count, add_to_centre)
+    }
+
+    #[inline]
+    fn add_to_pattern(&self, pattern: &mut Pattern, column: i32) {
+        let time = self.inner.hit_object.time;
+        pattern.add(column, time, time);
+    }
+}
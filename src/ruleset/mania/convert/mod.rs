@@ -0,0 +1,273 @@
+//! Conversion of osu!standard beatmaps to osu!mania, following the rules of osu!stable.
+//!
+//! Every object is turned into a pattern of notes by one of osu!stable's pattern generators,
+//! depending on its kind and on the object before it. The generators draw from a random
+//! generator seeded from the beatmap's difficulty settings, so a beatmap is always converted the
+//! same way.
+
+mod end_time_object;
+mod hit_object;
+mod path_object;
+mod pattern;
+
+use std::collections::VecDeque;
+
+use nalgebra_glm::Vec2;
+
+use crate::{
+    file::beatmap::{
+        event::EventKind, hit_objects::HitObjectKind, timing_point::control_points::ControlPoints,
+        Beatmap,
+    },
+    mode::Mode,
+    mods::Mods,
+    ruleset::random::LegacyRandom,
+    Error,
+};
+
+use self::{
+    end_time_object::EndTimeObjectPatternGenerator,
+    hit_object::HitObjectPatternGenerator,
+    path_object::PathObjectPatternGenerator,
+    pattern::{Pattern, PatternGenerator, PatternType},
+};
+
+use super::object::{key_count, ManiaObject};
+
+/// Number of previous notes the density of a beatmap is measured over.
+const MAX_NOTES_FOR_DENSITY: usize = 7;
+
+/// Converts the objects of an osu!standard `beatmap` to osu!mania, played with `mods`.
+///
+/// The key count is the one of a key mod in `mods`, or else is picked from the beatmap's
+/// difficulty settings and its proportion of sliders and spinners, see [`target_columns`].
+/// The objects are sorted by time.
+///
+/// This function will return [Err(Error::InvalidInput)][Error::InvalidInput] if `beatmap` is
+/// not an osu!standard beatmap.
+pub fn convert(beatmap: &Beatmap, mods: Mods) -> Result<Vec<ManiaObject>, Error> {
+    if beatmap.general.mode != Mode::Standard {
+        return Err(Error::InvalidInput(
+            "only osu!standard beatmaps can be converted",
+        ));
+    }
+
+    Ok(convert_objects(beatmap, key_count(beatmap, mods)))
+}
+
+/// Returns the key count osu!stable converts an osu!standard `beatmap` to without key mods.
+///
+/// Beatmaps with few sliders and spinners get more keys, as do those with a high OD or CS.
+pub fn target_columns(beatmap: &Beatmap) -> u32 {
+    let difficulty = &beatmap.difficulty;
+    let rounded_cs = difficulty.cs.round_ties_even();
+    let rounded_od = difficulty.od.round_ties_even();
+
+    let long_objects = beatmap
+        .hit_objects
+        .iter()
+        .filter(|h| matches!(h.kind, HitObjectKind::Slider(_) | HitObjectKind::Spinner(_)))
+        .count();
+    let percent_long_objects = long_objects as f32 / beatmap.hit_objects.len() as f32;
+
+    if percent_long_objects < 0.2 {
+        7
+    } else if percent_long_objects < 0.3 || rounded_cs >= 5.0 {
+        if rounded_od > 5.0 {
+            7
+        } else {
+            6
+        }
+    } else if percent_long_objects > 0.6 {
+        if rounded_od > 4.0 {
+            5
+        } else {
+            4
+        }
+    } else {
+        (rounded_od as u32 + 1).clamp(4, 7)
+    }
+}
+
+pub(super) fn convert_objects(beatmap: &Beatmap, key_count: u32) -> Vec<ManiaObject> {
+    let mut converter = Converter::new(beatmap, key_count);
+
+    let mut objects = Vec::with_capacity(beatmap.hit_objects.len());
+    for i in 0..beatmap.hit_objects.len() {
+        converter.convert(i, &mut objects);
+    }
+
+    objects.sort_by(|a, b| a.time.total_cmp(&b.time));
+    objects
+}
+
+/// State carried from one object to the next while converting a beatmap.
+struct Converter<'a> {
+    beatmap: &'a Beatmap,
+    control_points: ControlPoints,
+    total_columns: i32,
+    conversion_difficulty: f64,
+    random: LegacyRandom,
+    previous_pattern: Pattern,
+    previous_note_times: VecDeque<f64>,
+    /// Average time between the last notes.
+    density: f64,
+    last_time: f64,
+    last_position: Vec2,
+    /// Direction of the stairs of fast streams, kept from one circle to the next.
+    last_stair: PatternType,
+}
+
+impl<'a> Converter<'a> {
+    fn new(beatmap: &'a Beatmap, key_count: u32) -> Self {
+        let difficulty = &beatmap.difficulty;
+        let seed = (difficulty.hp + difficulty.cs).round_ties_even() as i32 * 20
+            + (difficulty.od as f64 * 41.2) as i32
+            + difficulty.ar.round_ties_even() as i32;
+
+        Self {
+            beatmap,
+            control_points: beatmap.control_points(),
+            total_columns: key_count as i32,
+            conversion_difficulty: conversion_difficulty(beatmap),
+            random: LegacyRandom::new(seed),
+            previous_pattern: Pattern::default(),
+            previous_note_times: VecDeque::with_capacity(MAX_NOTES_FOR_DENSITY),
+            density: i32::MAX as f64,
+            last_time: 0.0,
+            last_position: Vec2::zeros(),
+            last_stair: PatternType::STAIR,
+        }
+    }
+
+    /// Generates the patterns of the `i`th object and adds their notes to `objects`.
+    fn convert(&mut self, i: usize, objects: &mut Vec<ManiaObject>) {
+        let beatmap = self.beatmap;
+        let hit_object = &beatmap.hit_objects[i];
+
+        let patterns = match &hit_object.kind {
+            HitObjectKind::Slider(slider) => {
+                let generator = PathObjectPatternGenerator::new(self.generator(i), slider);
+                let (span_count, segment_duration) =
+                    (generator.span_count, generator.segment_duration);
+                let patterns = generator.generate();
+
+                for span in 0..=span_count {
+                    let time = hit_object.time.as_millis() + (segment_duration * span) as f64;
+                    self.record_note(time, hit_object.position);
+                    self.compute_density(time);
+                }
+
+                patterns
+            }
+            HitObjectKind::Spinner(_) | HitObjectKind::Hold(_) => {
+                let end_time = hit_object.end_time();
+                let pattern =
+                    EndTimeObjectPatternGenerator::new(self.generator(i), end_time).generate();
+
+                self.record_note(end_time.as_millis(), Vec2::new(256.0, 192.0));
+                self.compute_density(end_time.as_millis());
+
+                // spinners do not affect the patterns of the next objects
+                objects.extend(pattern.objects);
+                return;
+            }
+            HitObjectKind::HitCircle => {
+                self.compute_density(hit_object.time.as_millis());
+
+                let (last_time, last_position, density, last_stair) = (
+                    self.last_time,
+                    self.last_position,
+                    self.density,
+                    self.last_stair,
+                );
+                let (pattern, stair_type) = HitObjectPatternGenerator::new(
+                    self.generator(i),
+                    last_time,
+                    last_position,
+                    density,
+                    last_stair,
+                )
+                .generate();
+                self.last_stair = stair_type;
+
+                self.record_note(hit_object.time.as_millis(), hit_object.position);
+                vec![pattern]
+            }
+        };
+
+        for pattern in patterns {
+            objects.extend_from_slice(&pattern.objects);
+            self.previous_pattern = pattern;
+        }
+    }
+
+    fn generator(&mut self, i: usize) -> PatternGenerator<'_> {
+        PatternGenerator {
+            hit_object: &self.beatmap.hit_objects[i],
+            beatmap: self.beatmap,
+            control_points: &self.control_points,
+            previous_pattern: &self.previous_pattern,
+            random: &mut self.random,
+            total_columns: self.total_columns,
+            random_start: (self.total_columns == 8) as i32,
+            conversion_difficulty: self.conversion_difficulty,
+        }
+    }
+
+    #[inline]
+    fn record_note(&mut self, time: f64, position: Vec2) {
+        self.last_time = time;
+        self.last_position = position;
+    }
+
+    fn compute_density(&mut self, time: f64) {
+        if self.previous_note_times.len() == MAX_NOTES_FOR_DENSITY {
+            self.previous_note_times.pop_front();
+        }
+        self.previous_note_times.push_back(time);
+
+        if let (Some(first), Some(last)) = (
+            self.previous_note_times.front(),
+            self.previous_note_times.back(),
+        ) {
+            if self.previous_note_times.len() >= 2 {
+                self.density = (last - first) / self.previous_note_times.len() as f64;
+            }
+        }
+    }
+}
+
+/// Returns how hard osu!stable considers `beatmap`, from its HP, AR and object density; harder
+/// beatmaps are converted to patterns with more notes.
+fn conversion_difficulty(beatmap: &Beatmap) -> f64 {
+    let hit_objects = &beatmap.hit_objects;
+    let difficulty = &beatmap.difficulty;
+
+    let (Some(first), Some(last)) = (hit_objects.first(), hit_objects.last()) else {
+        return 0.0;
+    };
+
+    let break_time: f64 = beatmap
+        .events
+        .iter()
+        .filter_map(|event| match event {
+            EventKind::Break(b) => Some((b.end_time - b.start_time).as_millis()),
+            _ => None,
+        })
+        .sum();
+
+    // drain time in seconds
+    let mut drain_time = (((last.time - first.time).as_millis() - break_time) / 1000.0) as i32;
+    if drain_time == 0 {
+        drain_time = 10000;
+    }
+
+    let conversion_difficulty = ((difficulty.hp + difficulty.ar.clamp(4.0, 7.0)) as f64 / 1.5
+        + hit_objects.len() as f64 / drain_time as f64 * 9.0)
+        / 38.0
+        * 5.0
+        / 1.15;
+
+    conversion_difficulty.min(12.0)
+}
@@ -0,0 +1,453 @@
+use crate::{
+    file::beatmap::{
+        hit_objects::{slider::Slider, slider_path::SliderPath},
+        hit_sound::HitSound,
+    },
+    time::Time,
+};
+
+use super::pattern::{Pattern, PatternGenerator, PatternType};
+
+/// Generates the notes and hold notes of a slider, from its duration and number of spans.
+pub(super) struct PathObjectPatternGenerator<'a> {
+    inner: PatternGenerator<'a>,
+    slider: &'a Slider,
+    convert_type: PatternType,
+    /// Start time rounded to whole milliseconds, as osu!stable does.
+    start_time: i32,
+    end_time: i32,
+    pub segment_duration: i32,
+    pub span_count: i32,
+}
+
+impl<'a> PathObjectPatternGenerator<'a> {
+    pub fn new(inner: PatternGenerator<'a>, slider: &'a Slider) -> Self {
+        let hit_object = inner.hit_object;
+        let control_points = inner.control_points;
+
+        let convert_type = if control_points.kiai_at(hit_object.time) {
+            PatternType::empty()
+        } else {
+            PatternType::LOW_PROBABILITY
+        };
+
+        let beat_length = control_points.legacy_beat_length_at(hit_object.time);
+        let distance = SliderPath::from_slider(hit_object.position, slider).length();
+
+        let span_count = slider.slides.max(1) as i32;
+        let start_time = hit_object.time.as_millis().round() as i32;
+        let end_time = (start_time as f64
+            + distance * beat_length * span_count as f64 * 0.01
+                / inner.beatmap.difficulty.slider_multiplier as f64)
+            .floor() as i32;
+        let segment_duration = (end_time - start_time) / span_count;
+
+        Self {
+            inner,
+            slider,
+            convert_type,
+            start_time,
+            end_time,
+            segment_duration,
+            span_count,
+        }
+    }
+
+    /// Generates the patterns of the slider: the notes that do not end with it, then those that
+    /// do, which the next object's pattern is built from.
+    pub fn generate(mut self) -> Vec<Pattern> {
+        let pattern = self.generate_pattern();
+        if pattern.len() == 1 {
+            return vec![pattern];
+        }
+
+        let end_time = Time(self.end_time as f64);
+        let (end_time_objects, intermediate_objects) = pattern
+            .objects
+            .into_iter()
+            .partition(|o| o.end_time == end_time);
+
+        vec![
+            Pattern {
+                objects: intermediate_objects,
+            },
+            Pattern {
+                objects: end_time_objects,
+            },
+        ]
+    }
+
+    fn generate_pattern(&mut self) -> Pattern {
+        let total_columns = self.inner.total_columns;
+        let random_start = self.inner.random_start;
+        let start_time = self.start_time;
+
+        if total_columns == 1 {
+            let mut pattern = Pattern::default();
+            self.add_to_pattern(&mut pattern, 0, start_time, self.end_time);
+            return pattern;
+        }
+
+        let conversion_difficulty = self.inner.conversion_difficulty;
+
+        if self.span_count > 1 {
+            if self.segment_duration <= 90 {
+                return self.random_hold_notes(start_time, 1);
+            }
+
+            if self.segment_duration <= 120 {
+                self.convert_type |= PatternType::FORCE_NOT_STACK;
+                return self.random_notes(start_time, self.span_count + 1);
+            }
+
+            if self.segment_duration <= 160 {
+                return self.stair(start_time);
+            }
+
+            if self.segment_duration <= 200 && conversion_difficulty > 3.0 {
+                return self.random_multiple_notes(start_time);
+            }
+
+            if self.end_time - start_time >= 4000 {
+                return self.n_random_notes(start_time, 0.23, 0.0, 0.0);
+            }
+
+            if self.segment_duration > 400 && self.span_count < total_columns - 1 - random_start {
+                return self.tiled_hold_notes(start_time);
+            }
+
+            return self.hold_and_normal_notes(start_time);
+        }
+
+        if self.segment_duration <= 110 {
+            if self.inner.previous_pattern.column_count() < total_columns {
+                self.convert_type |= PatternType::FORCE_NOT_STACK;
+            } else {
+                self.convert_type &= !PatternType::FORCE_NOT_STACK;
+            }
+
+            let note_count = if self.segment_duration < 80 { 1 } else { 2 };
+            return self.random_notes(start_time, note_count);
+        }
+
+        let low_probability = self.convert_type.contains(PatternType::LOW_PROBABILITY);
+
+        if conversion_difficulty > 6.5 {
+            if low_probability {
+                self.n_random_notes(start_time, 0.78, 0.3, 0.0)
+            } else {
+                self.n_random_notes(start_time, 0.85, 0.36, 0.03)
+            }
+        } else if conversion_difficulty > 4.0 {
+            if low_probability {
+                self.n_random_notes(start_time, 0.43, 0.08, 0.0)
+            } else {
+                self.n_random_notes(start_time, 0.56, 0.18, 0.0)
+            }
+        } else if conversion_difficulty > 2.5 {
+            if low_probability {
+                self.n_random_notes(start_time, 0.3, 0.0, 0.0)
+            } else {
+                self.n_random_notes(start_time, 0.37, 0.08, 0.0)
+            }
+        } else if low_probability {
+            self.n_random_notes(start_time, 0.17, 0.0, 0.0)
+        } else {
+            self.n_random_notes(start_time, 0.27, 0.0, 0.0)
+        }
+    }
+
+    /// Hold notes lasting the whole slider on random columns, avoiding the previous pattern
+    /// while there is room.
+    fn random_hold_notes(&mut self, start_time: i32, note_count: i32) -> Pattern {
+        let total_columns = self.inner.total_columns;
+        let random_start = self.inner.random_start;
+        let previous_pattern = self.inner.previous_pattern;
+
+        let mut pattern = Pattern::default();
+
+        let usable_columns = total_columns - random_start - previous_pattern.column_count();
+        let mut column = self.inner.random_column(random_start, total_columns);
+
+        for _ in 0..usable_columns.min(note_count) {
+            column = self
+                .inner
+                .find_available_column(column, random_start, total_columns, |c| {
+                    !pattern.has_column(c) && !previous_pattern.has_column(c)
+                });
+            self.add_to_pattern(&mut pattern, column, start_time, self.end_time);
+        }
+
+        for _ in 0..note_count - usable_columns {
+            column = self
+                .inner
+                .find_available_column(column, random_start, total_columns, |c| {
+                    !pattern.has_column(c)
+                });
+            self.add_to_pattern(&mut pattern, column, start_time, self.end_time);
+        }
+
+        pattern
+    }
+
+    /// A note on a different column at every node of the slider.
+    fn random_notes(&mut self, mut start_time: i32, note_count: i32) -> Pattern {
+        let total_columns = self.inner.total_columns;
+        let random_start = self.inner.random_start;
+        let previous_pattern = self.inner.previous_pattern;
+
+        let mut pattern = Pattern::default();
+
+        let mut column = self.inner.column(self.inner.hit_object.position.x, true);
+        if self.convert_type.contains(PatternType::FORCE_NOT_STACK)
+            && previous_pattern.column_count() < total_columns
+        {
+            column = self
+                .inner
+                .find_available_column(column, random_start, total_columns, |c| {
+                    !previous_pattern.has_column(c)
+                });
+        }
+
+        for _ in 0..note_count {
+            self.add_to_pattern(&mut pattern, column, start_time, start_time);
+
+            let last_column = column;
+            column = self
+                .inner
+                .find_available_column(column, random_start, total_columns, |c| c != last_column);
+            start_time += self.segment_duration;
+        }
+
+        pattern
+    }
+
+    /// A note at every node of the slider, moving one column at a time and bouncing off the
+    /// sides of the stage.
+    fn stair(&mut self, mut start_time: i32) -> Pattern {
+        let total_columns = self.inner.total_columns;
+        let random_start = self.inner.random_start;
+
+        let mut pattern = Pattern::default();
+
+        let mut column = self.inner.column(self.inner.hit_object.position.x, true);
+        let mut increasing = self.inner.random.next_f64() > 0.5;
+
+        for _ in 0..=self.span_count {
+            self.add_to_pattern(&mut pattern, column, start_time, start_time);
+            start_time += self.segment_duration;
+
+            if increasing {
+                if column >= total_columns - 1 {
+                    increasing = false;
+                    column -= 1;
+                } else {
+                    column += 1;
+                }
+            } else if column <= random_start {
+                increasing = true;
+                column += 1;
+            } else {
+                column -= 1;
+            }
+        }
+
+        pattern
+    }
+
+    /// One or two notes at every node of the slider.
+    fn random_multiple_notes(&mut self, mut start_time: i32) -> Pattern {
+        let total_columns = self.inner.total_columns;
+        let random_start = self.inner.random_start;
+
+        let mut pattern = Pattern::default();
+
+        let legacy = (4..=8).contains(&total_columns);
+        let interval = self.inner.random_column(1, total_columns - legacy as i32);
+
+        let mut column = self.inner.column(self.inner.hit_object.position.x, true);
+        for _ in 0..=self.span_count {
+            self.add_to_pattern(&mut pattern, column, start_time, start_time);
+
+            column += interval;
+            if column >= total_columns - random_start {
+                column = column - total_columns - random_start + legacy as i32;
+            }
+            column += random_start;
+
+            // avoid consecutive doubles in 2K
+            if total_columns > 2 {
+                self.add_to_pattern(&mut pattern, column, start_time, start_time);
+            }
+
+            column = self.inner.random_column(random_start, total_columns);
+            start_time += self.segment_duration;
+        }
+
+        pattern
+    }
+
+    /// Hold notes lasting the whole slider, more of them with a clap or finish.
+    fn n_random_notes(
+        &mut self,
+        start_time: i32,
+        mut p2: f64,
+        mut p3: f64,
+        mut p4: f64,
+    ) -> Pattern {
+        match self.inner.total_columns {
+            2 => {
+                p2 = 0.0;
+                p3 = 0.0;
+                p4 = 0.0;
+            }
+            3 => {
+                p2 = p2.min(0.1);
+                p3 = 0.0;
+                p4 = 0.0;
+            }
+            4 => {
+                p2 = p2.min(0.3);
+                p3 = p3.min(0.04);
+                p4 = 0.0;
+            }
+            5 => {
+                p2 = p2.min(0.34);
+                p3 = p3.min(0.1);
+                p4 = p4.min(0.03);
+            }
+            _ => {}
+        }
+
+        let is_double_sample =
+            |hit_sound: HitSound| hit_sound.intersects(HitSound::CLAP | HitSound::FINISH);
+
+        let can_generate_two_notes = !self.convert_type.contains(PatternType::LOW_PROBABILITY)
+            && (is_double_sample(self.inner.hit_object.hit_sound)
+                || is_double_sample(self.hit_sound_at(self.start_time)));
+
+        if can_generate_two_notes {
+            p2 = 1.0;
+        }
+
+        let note_count = self.inner.random_note_count(p2, p3, p4, 0.0, 0.0);
+        self.random_hold_notes(start_time, note_count)
+    }
+
+    /// Hold notes starting at every node of the slider on different columns, all ending with it.
+    fn tiled_hold_notes(&mut self, mut start_time: i32) -> Pattern {
+        let total_columns = self.inner.total_columns;
+        let random_start = self.inner.random_start;
+        let previous_pattern = self.inner.previous_pattern;
+
+        let mut pattern = Pattern::default();
+
+        let column_repeat = self.span_count.min(total_columns);
+        // not always the end time of the slider because of rounding
+        let end_time = start_time + self.segment_duration * self.span_count;
+
+        let mut column = self.inner.column(self.inner.hit_object.position.x, true);
+        if self.convert_type.contains(PatternType::FORCE_NOT_STACK)
+            && previous_pattern.column_count() < total_columns
+        {
+            column = self
+                .inner
+                .find_available_column(column, random_start, total_columns, |c| {
+                    !previous_pattern.has_column(c)
+                });
+        }
+
+        for _ in 0..column_repeat {
+            column = self
+                .inner
+                .find_available_column(column, random_start, total_columns, |c| {
+                    !pattern.has_column(c)
+                });
+            self.add_to_pattern(&mut pattern, column, start_time, end_time);
+            start_time += self.segment_duration;
+        }
+
+        pattern
+    }
+
+    /// A hold note lasting the whole slider, with notes on other columns at its nodes.
+    fn hold_and_normal_notes(&mut self, mut start_time: i32) -> Pattern {
+        let total_columns = self.inner.total_columns;
+        let random_start = self.inner.random_start;
+        let previous_pattern = self.inner.previous_pattern;
+        let conversion_difficulty = self.inner.conversion_difficulty;
+
+        let mut pattern = Pattern::default();
+
+        let mut hold_column = self.inner.column(self.inner.hit_object.position.x, true);
+        if self.convert_type.contains(PatternType::FORCE_NOT_STACK)
+            && previous_pattern.column_count() < total_columns
+        {
+            hold_column =
+                self.inner
+                    .find_available_column(hold_column, random_start, total_columns, |c| {
+                        !previous_pattern.has_column(c)
+                    });
+        }
+
+        self.add_to_pattern(&mut pattern, hold_column, start_time, self.end_time);
+
+        let mut column = self.inner.random_column(random_start, total_columns);
+        let note_count = if conversion_difficulty > 6.5 {
+            self.inner.random_note_count(0.63, 0.0, 0.0, 0.0, 0.0)
+        } else if conversion_difficulty > 4.0 {
+            let p2 = if total_columns < 6 { 0.12 } else { 0.45 };
+            self.inner.random_note_count(p2, 0.0, 0.0, 0.0, 0.0)
+        } else if conversion_difficulty > 2.5 {
+            let p2 = if total_columns < 6 { 0.0 } else { 0.24 };
+            self.inner.random_note_count(p2, 0.0, 0.0, 0.0, 0.0)
+        } else {
+            0
+        };
+        let note_count = note_count.min(total_columns - 1);
+
+        let ignore_head = !self
+            .hit_sound_at(start_time)
+            .intersects(HitSound::WHISTLE | HitSound::FINISH | HitSound::CLAP);
+
+        for _ in 0..=self.span_count {
+            if !(ignore_head && start_time == self.start_time) {
+                let mut row = Pattern::default();
+                for _ in 0..note_count {
+                    column = self.inner.find_available_column(
+                        column,
+                        random_start,
+                        total_columns,
+                        |c| c != hold_column && !row.has_column(c),
+                    );
+                    self.add_to_pattern(&mut row, column, start_time, start_time);
+                }
+                pattern.objects.append(&mut row.objects);
+            }
+
+            start_time += self.segment_duration;
+        }
+
+        pattern
+    }
+
+    /// Returns the hit sound of the node of the slider at `time`.
+    fn hit_sound_at(&self, time: i32) -> HitSound {
+        let index = if self.segment_duration == 0 {
+            0
+        } else {
+            (time - self.start_time) / self.segment_duration
+        };
+
+        self.slider
+            .edge_sounds
+            .get(index as usize)
+            .copied()
+            .unwrap_or(self.inner.hit_object.hit_sound)
+    }
+
+    #[inline]
+    fn add_to_pattern(&self, pattern: &mut Pattern, column: i32, start_time: i32, end_time: i32) {
+        pattern.add(column, Time(start_time as f64), Time(end_time as f64));
+    }
+}
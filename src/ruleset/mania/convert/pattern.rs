@@ -0,0 +1,192 @@
+use bitflags::bitflags;
+
+use crate::{
+    file::beatmap::{
+        hit_objects::{HitObject, PLAYFIELD_WIDTH},
+        timing_point::control_points::ControlPoints,
+        Beatmap,
+    },
+    ruleset::{
+        mania::object::{ManiaObject, ManiaObjectKind},
+        random::LegacyRandom,
+    },
+    time::Time,
+};
+
+bitflags! {
+    /// Hints on how to place the notes generated for an object.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+    pub(super) struct PatternType: u16 {
+        /// Notes are placed on the columns of the previous pattern.
+        const FORCE_STACK = 1 << 0;
+        /// Notes avoid the columns of the previous pattern.
+        const FORCE_NOT_STACK = 1 << 1;
+        /// A single note is generated.
+        const KEEP_SINGLE = 1 << 2;
+        /// Fewer notes are generated.
+        const LOW_PROBABILITY = 1 << 3;
+        /// Notes are placed on adjacent columns.
+        const GATHERED = 1 << 4;
+        /// Notes are mirrored around the centre of the stage.
+        const MIRROR = 1 << 5;
+        /// Notes mirror the previous pattern.
+        const REVERSE = 1 << 6;
+        /// A note mirrors the single note of the previous pattern.
+        const CYCLE = 1 << 7;
+        /// A note is placed right of the single note of the previous pattern.
+        const STAIR = 1 << 8;
+        /// A note is placed left of the single note of the previous pattern.
+        const REVERSE_STAIR = 1 << 9;
+    }
+}
+
+/// Notes generated for an object, or part of one.
+#[derive(Debug, Clone, Default)]
+pub(super) struct Pattern {
+    pub objects: Vec<ManiaObject>,
+}
+
+impl Pattern {
+    /// Adds a note, or a hold note if `end_time` is after `time`.
+    #[inline]
+    pub fn add(&mut self, column: i32, time: Time, end_time: Time) {
+        let kind = if end_time > time {
+            ManiaObjectKind::Hold
+        } else {
+            ManiaObjectKind::Note
+        };
+
+        self.objects.push(ManiaObject {
+            time,
+            end_time,
+            column: column as u32,
+            kind,
+        });
+    }
+
+    #[inline]
+    pub fn has_column(&self, column: i32) -> bool {
+        self.objects.iter().any(|o| o.column as i32 == column)
+    }
+
+    /// Returns the number of distinct columns the pattern has notes in.
+    pub fn column_count(&self) -> i32 {
+        let mut columns = self.objects.iter().map(|o| o.column).collect::<Vec<_>>();
+        columns.sort_unstable();
+        columns.dedup();
+        columns.len() as i32
+    }
+
+    /// Returns the column of the first note, or 0 if there is none.
+    #[inline]
+    pub fn first_column(&self) -> i32 {
+        self.objects.first().map_or(0, |o| o.column as i32)
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.objects.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.objects.is_empty()
+    }
+}
+
+/// State shared by the pattern generators converting an object.
+pub(super) struct PatternGenerator<'a> {
+    pub hit_object: &'a HitObject,
+    pub beatmap: &'a Beatmap,
+    pub control_points: &'a ControlPoints,
+    pub previous_pattern: &'a Pattern,
+    pub random: &'a mut LegacyRandom,
+    pub total_columns: i32,
+    /// The first column notes are placed on at random, skipping the special column of 7K+1.
+    pub random_start: i32,
+    pub conversion_difficulty: f64,
+}
+
+impl PatternGenerator<'_> {
+    /// Returns the column an object at `x` is placed on, keeping the special column of 7K+1 free
+    /// if `allow_special` is set.
+    pub fn column(&self, x: f32, allow_special: bool) -> i32 {
+        if allow_special && self.total_columns == 8 {
+            let divisor = PLAYFIELD_WIDTH / 7.0;
+            return ((x / divisor).floor() as i32).clamp(0, 6) + 1;
+        }
+
+        let divisor = PLAYFIELD_WIDTH / self.total_columns as f32;
+        ((x / divisor).floor() as i32).clamp(0, self.total_columns - 1)
+    }
+
+    /// Returns a random number of notes from 1 to 6, where `p2` to `p6` are the probabilities of
+    /// at least 2 to 6 notes.
+    pub fn random_note_count(&mut self, p2: f64, p3: f64, p4: f64, p5: f64, p6: f64) -> i32 {
+        let value = self.random.next_f64();
+
+        if value >= 1.0 - p6 {
+            6
+        } else if value >= 1.0 - p5 {
+            5
+        } else if value >= 1.0 - p4 {
+            4
+        } else if value >= 1.0 - p3 {
+            3
+        } else if value >= 1.0 - p2 {
+            2
+        } else {
+            1
+        }
+    }
+
+    /// Returns a random column between `lower` (inclusive) and `upper` (exclusive).
+    #[inline]
+    pub fn random_column(&mut self, lower: i32, upper: i32) -> i32 {
+        self.random.next_in_range(lower as f64, upper as f64)
+    }
+
+    /// Returns `initial` if it is valid, or else the first valid column of random ones between
+    /// `lower` and `upper`.
+    #[inline]
+    pub fn find_available_column(
+        &mut self,
+        initial: i32,
+        lower: i32,
+        upper: i32,
+        is_valid: impl Fn(i32) -> bool,
+    ) -> i32 {
+        self.find_column_with(
+            initial,
+            lower,
+            upper,
+            |random, _| random.next_in_range(lower as f64, upper as f64),
+            is_valid,
+        )
+    }
+
+    /// Returns `initial` if it is valid, or else the first valid column `next_column` moves to.
+    ///
+    /// osu!stable gives up on the object if no column between `lower` and `upper` is valid; the
+    /// initial column is kept instead.
+    pub fn find_column_with(
+        &mut self,
+        initial: i32,
+        lower: i32,
+        upper: i32,
+        mut next_column: impl FnMut(&mut LegacyRandom, i32) -> i32,
+        is_valid: impl Fn(i32) -> bool,
+    ) -> i32 {
+        if is_valid(initial) || !(lower..upper).any(&is_valid) {
+            return initial;
+        }
+
+        let mut column = initial;
+        loop {
+            column = next_column(self.random, column);
+            if is_valid(column) {
+                return column;
+            }
+        }
+    }
+}
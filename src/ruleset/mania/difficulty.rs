@@ -0,0 +1,296 @@
+//! osu!mania star rating, following the current osu! difficulty algorithm.
+
+use crate::{
+    file::beatmap::Beatmap,
+    mods::Mods,
+    ruleset::strain::{weighted_sum, StrainPeaks},
+};
+
+use super::object::{is_convert, key_count, ManiaObject};
+
+/// Length of the sections strain peaks are taken from, in milliseconds adjusted by the clock rate.
+pub const SECTION_LENGTH: f64 = 400.0;
+
+const DIFFICULTY_MULTIPLIER: f64 = 0.018;
+const DECAY_WEIGHT: f64 = 0.9;
+
+const INDIVIDUAL_DECAY_BASE: f64 = 0.125;
+const OVERALL_DECAY_BASE: f64 = 0.3;
+/// Difference between the releases of two hold notes under which they are released together.
+const RELEASE_THRESHOLD: f64 = 24.0;
+
+/// Difficulty of an osu!mania beatmap played with mods.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ManiaDifficultyAttributes {
+    pub mods: Mods,
+    pub stars: f64,
+    /// Half-width of the 300 hit window in milliseconds, the way osu!stable computes it.
+    pub great_hit_window: f64,
+    pub key_count: u32,
+    pub max_combo: u32,
+    pub n_notes: u32,
+    pub n_holds: u32,
+    /// Whether the beatmap is an osu!standard beatmap converted to osu!mania.
+    pub is_convert: bool,
+}
+
+/// Strain peaks of every [`SECTION_LENGTH`] section of an osu!mania beatmap, for graphing.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ManiaStrains {
+    pub strains: Vec<f64>,
+}
+
+/// Computes the star rating of an osu!mania beatmap.
+#[derive(Debug, Clone)]
+pub struct ManiaDifficultyCalculator<'a> {
+    beatmap: &'a Beatmap,
+    mods: Mods,
+    passed_objects: Option<usize>,
+}
+
+impl<'a> ManiaDifficultyCalculator<'a> {
+    #[inline]
+    pub fn new(beatmap: &'a Beatmap) -> Self {
+        Self {
+            beatmap,
+            mods: Mods::empty(),
+            passed_objects: None,
+        }
+    }
+
+    #[inline]
+    pub fn mods(mut self, mods: Mods) -> Self {
+        self.mods = mods;
+        self
+    }
+
+    /// Only evaluates the first `passed_objects` hit objects, for scores that failed or quit
+    /// before the end of the beatmap.
+    #[inline]
+    pub fn passed_objects(mut self, passed_objects: usize) -> Self {
+        self.passed_objects = Some(passed_objects);
+        self
+    }
+
+    /// Computes the difficulty attributes of the beatmap.
+    pub fn calculate(&self) -> ManiaDifficultyAttributes {
+        let objects = self.objects();
+        let key_count = key_count(self.beatmap, self.mods);
+        let strain = self.strain(&objects, key_count);
+
+        let mods = self.mods;
+        let is_convert = is_convert(self.beatmap);
+
+        let stars = weighted_sum(strain.peaks.peaks(), DECAY_WEIGHT) * DIFFICULTY_MULTIPLIER;
+
+        // osu!stable does not scale the hit windows of osu!mania with the clock rate, but
+        // rounds them in a way that makes them depend on it slightly
+        let clock_rate = mods.clock_rate();
+        let great_hit_window =
+            (((self.great_window() * clock_rate) as i32) as f64 / clock_rate).ceil();
+
+        ManiaDifficultyAttributes {
+            mods,
+            stars,
+            great_hit_window,
+            key_count,
+            max_combo: objects.iter().map(ManiaObject::max_combo).sum(),
+            n_notes: objects.iter().filter(|o| !o.is_hold()).count() as u32,
+            n_holds: objects.iter().filter(|o| o.is_hold()).count() as u32,
+            is_convert,
+        }
+    }
+
+    /// Computes the strain peaks of the beatmap.
+    pub fn strains(&self) -> ManiaStrains {
+        let objects = self.objects();
+        let strain = self.strain(&objects, key_count(self.beatmap, self.mods));
+
+        ManiaStrains {
+            strains: strain.peaks.peaks(),
+        }
+    }
+
+    fn objects(&self) -> Vec<ManiaObject> {
+        let mut objects = ManiaObject::from_beatmap(self.beatmap, self.mods);
+        if let Some(passed_objects) = self.passed_objects {
+            objects.truncate(passed_objects);
+        }
+        objects
+    }
+
+    fn strain(&self, objects: &[ManiaObject], key_count: u32) -> Strain {
+        let clock_rate = self.mods.clock_rate();
+
+        // osu!stable sorts the objects by their start times rounded to even, but with an unstable
+        // sort whose order of objects with the same rounded time is not reproduced here: they are
+        // kept in beatmap order, which can change the strain of chords
+        let mut sorted = objects.iter().collect::<Vec<_>>();
+        sorted.sort_by_key(|o| o.time.as_millis().round_ties_even() as i64);
+
+        let mut strain = Strain::new(key_count);
+        let mut previous_start_time = None;
+
+        for window in sorted.windows(2) {
+            let [previous, current] = window else {
+                unreachable!()
+            };
+
+            let object = StrainObject {
+                column: current.column as usize,
+                start_time: current.time.as_millis() / clock_rate,
+                end_time: current.end_time.as_millis() / clock_rate,
+                delta_time: (current.time - previous.time).as_millis() / clock_rate,
+            };
+            strain.process(&object, previous_start_time);
+            previous_start_time = Some(object.start_time);
+        }
+
+        strain
+    }
+
+    /// Returns the half-width of the 300 hit window in milliseconds, which osu!stable computes
+    /// from the unmodified OD for osu!mania beatmaps, and from a fixed value for converts. It is
+    /// then multiplied by the clock rate, before [`calculate`][Self::calculate] rounds it.
+    fn great_window(&self) -> f64 {
+        let od = self.beatmap.difficulty.od as f64;
+
+        let mut window = if !is_convert(self.beatmap) {
+            34.0 + 3.0 * (10.0 - od).clamp(0.0, 10.0)
+        } else if od.round_ties_even() > 4.0 {
+            34.0
+        } else {
+            47.0
+        };
+
+        if self.mods.contains(Mods::HARD_ROCK) {
+            window /= 1.4;
+        } else if self.mods.contains(Mods::EASY) {
+            window *= 1.4;
+        }
+
+        if self.mods.intersects(Mods::DOUBLE_TIME | Mods::NIGHTCORE) {
+            window *= 1.5;
+        } else if self.mods.contains(Mods::HALF_TIME) {
+            window *= 0.75;
+        }
+
+        window
+    }
+}
+
+struct StrainObject {
+    column: usize,
+    start_time: f64,
+    end_time: f64,
+    delta_time: f64,
+}
+
+/// Difficulty of pressing notes in each column and overall, and of holding and releasing hold
+/// notes.
+struct Strain {
+    start_times: Vec<f64>,
+    end_times: Vec<f64>,
+    individual_strains: Vec<f64>,
+    individual_strain: f64,
+    overall_strain: f64,
+    peaks: StrainPeaks,
+}
+
+impl Strain {
+    fn new(key_count: u32) -> Self {
+        let key_count = key_count as usize;
+
+        Self {
+            start_times: vec![0.0; key_count],
+            end_times: vec![0.0; key_count],
+            individual_strains: vec![0.0; key_count],
+            individual_strain: 0.0,
+            overall_strain: 1.0,
+            peaks: StrainPeaks::new(SECTION_LENGTH),
+        }
+    }
+
+    fn process(&mut self, current: &StrainObject, previous_start_time: Option<f64>) {
+        let individual_strain = self.individual_strain;
+        let overall_strain = self.overall_strain;
+
+        match previous_start_time {
+            Some(previous_start_time) => self.peaks.advance(current.start_time, |time| {
+                apply_decay(
+                    individual_strain,
+                    time - previous_start_time,
+                    INDIVIDUAL_DECAY_BASE,
+                ) + apply_decay(
+                    overall_strain,
+                    time - previous_start_time,
+                    OVERALL_DECAY_BASE,
+                )
+            }),
+            None => self.peaks.advance(current.start_time, |_| 0.0),
+        }
+
+        let strain = self.evaluate(current);
+        self.peaks.update(strain);
+    }
+
+    fn evaluate(&mut self, current: &StrainObject) -> f64 {
+        let start_time = current.start_time;
+        let end_time = current.end_time;
+        let column = current.column.min(self.end_times.len() - 1);
+
+        let mut is_overlapping = false;
+        // the lowest value that can be assumed with the current information
+        let mut closest_end_time = (end_time - start_time).abs();
+        // bonus to everything while something else is held
+        let mut hold_factor = 1.0;
+        // bonus to hold notes released awkwardly
+        let mut hold_addition = 0.0;
+
+        for &other_end_time in &self.end_times {
+            // the hold note is overlapped if another note or release happens during its body
+            is_overlapping |= other_end_time - start_time > 1.0 && end_time - other_end_time > 1.0;
+
+            if other_end_time - end_time > 1.0 {
+                hold_factor = 1.25;
+            }
+
+            closest_end_time = closest_end_time.min((end_time - other_end_time).abs());
+        }
+
+        // releasing several hold notes together is as easy as releasing one
+        if is_overlapping {
+            hold_addition = 1.0 / (1.0 + (0.5 * (RELEASE_THRESHOLD - closest_end_time)).exp());
+        }
+
+        self.individual_strains[column] = apply_decay(
+            self.individual_strains[column],
+            start_time - self.start_times[column],
+            INDIVIDUAL_DECAY_BASE,
+        );
+        self.individual_strains[column] += 2.0 * hold_factor;
+
+        // the notes of a chord are as hard as the hardest of their columns
+        self.individual_strain = if current.delta_time <= 1.0 {
+            self.individual_strain.max(self.individual_strains[column])
+        } else {
+            self.individual_strains[column]
+        };
+
+        self.overall_strain =
+            apply_decay(self.overall_strain, current.delta_time, OVERALL_DECAY_BASE);
+        self.overall_strain += (1.0 + hold_addition) * hold_factor;
+
+        self.start_times[column] = start_time;
+        self.end_times[column] = end_time;
+
+        self.individual_strain + self.overall_strain
+    }
+}
+
+#[inline]
+fn apply_decay(value: f64, delta_time: f64, decay_base: f64) -> f64 {
+    value * decay_base.powf(delta_time / 1000.0)
+}
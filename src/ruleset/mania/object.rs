@@ -0,0 +1,94 @@
+use crate::{
+    file::beatmap::{hit_objects::HitObjectKind, Beatmap},
+    mode::Mode,
+    mods::Mods,
+    time::Time,
+};
+
+use super::convert::{convert_objects, target_columns};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ManiaObjectKind {
+    Note,
+    /// Long note, held until its end time.
+    Hold,
+}
+
+/// An osu!mania note or hold note.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ManiaObject {
+    pub time: Time,
+    /// The same as [`time`][ManiaObject::time] for notes.
+    pub end_time: Time,
+    /// 0-based column, from the left.
+    pub column: u32,
+    pub kind: ManiaObjectKind,
+}
+
+impl ManiaObject {
+    /// Builds the objects of an osu!mania `beatmap` played with `mods`, whose columns are spread
+    /// over its [`key_count`], or of an osu!standard beatmap converted to osu!mania with
+    /// [`convert`][super::convert::convert].
+    pub fn from_beatmap(beatmap: &Beatmap, mods: Mods) -> Vec<Self> {
+        let key_count = key_count(beatmap, mods);
+
+        if beatmap.general.mode == Mode::Standard {
+            return convert_objects(beatmap, key_count);
+        }
+
+        beatmap
+            .hit_objects
+            .iter()
+            .map(|hit_object| {
+                let kind = match hit_object.kind {
+                    HitObjectKind::Hold(_) => ManiaObjectKind::Hold,
+                    _ => ManiaObjectKind::Note,
+                };
+
+                Self {
+                    time: hit_object.time,
+                    end_time: hit_object.end_time(),
+                    column: hit_object.mania_column(key_count),
+                    kind,
+                }
+            })
+            .collect()
+    }
+
+    #[inline]
+    pub fn is_hold(&self) -> bool {
+        self.kind == ManiaObjectKind::Hold
+    }
+
+    /// Returns the combo the object gives: one for notes, and one plus one per 100ms held for
+    /// hold notes.
+    #[inline]
+    pub fn max_combo(&self) -> u32 {
+        match self.kind {
+            ManiaObjectKind::Note => 1,
+            ManiaObjectKind::Hold => 1 + ((self.end_time - self.time).as_millis() / 100.0) as u32,
+        }
+    }
+}
+
+/// Returns `true` if `beatmap` is an osu!standard beatmap played in osu!mania.
+#[inline]
+pub fn is_convert(beatmap: &Beatmap) -> bool {
+    beatmap.general.mode != Mode::Mania
+}
+
+/// Returns the number of columns `beatmap` is played with.
+///
+/// osu!standard beatmaps are converted to the key count of the key mod in `mods`, or else to
+/// their [`target_columns`]; other beatmaps keep the key count of their
+/// [`Difficulty`][crate::file::beatmap::difficulty::Difficulty].
+#[inline]
+pub fn key_count(beatmap: &Beatmap, mods: Mods) -> u32 {
+    if beatmap.general.mode != Mode::Standard {
+        return beatmap.difficulty.key_count();
+    }
+
+    mods.key_count().unwrap_or_else(|| target_columns(beatmap))
+}
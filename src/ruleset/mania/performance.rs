@@ -0,0 +1,98 @@
+//! osu!mania performance points, following the current osu! performance algorithm.
+
+use crate::{file::beatmap::Beatmap, mods::Mods};
+
+use super::difficulty::{ManiaDifficultyAttributes, ManiaDifficultyCalculator};
+
+/// Judgements of an osu!mania score; hold notes count as a single judgement.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ManiaScore {
+    pub mods: Mods,
+    /// MAX (rainbow 300) judgements.
+    pub n320: u32,
+    pub n300: u32,
+    pub n200: u32,
+    pub n100: u32,
+    pub n50: u32,
+    pub n_miss: u32,
+}
+
+impl ManiaScore {
+    /// Returns the number of judged notes and hold notes.
+    #[inline]
+    pub fn total_hits(&self) -> u32 {
+        self.n320 + self.n300 + self.n200 + self.n100 + self.n50 + self.n_miss
+    }
+
+    /// Returns the accuracy between 0 and 1, with MAX judgements worth slightly more than 300s.
+    #[inline]
+    pub fn accuracy(&self) -> f64 {
+        let total_hits = self.total_hits();
+        if total_hits == 0 {
+            return 0.0;
+        }
+
+        let points =
+            320 * self.n320 + 300 * self.n300 + 200 * self.n200 + 100 * self.n100 + 50 * self.n50;
+        points as f64 / (320 * total_hits) as f64
+    }
+
+    /// Computes the difficulty of the beatmap, then the performance of the score.
+    ///
+    /// Scores with fewer judgements than the beatmap has objects are treated as failed, and only
+    /// the objects played before failing are taken into account.
+    pub fn performance(&self, beatmap: &Beatmap) -> ManiaPerformanceAttributes {
+        let attributes = ManiaDifficultyCalculator::new(beatmap)
+            .mods(self.mods)
+            .passed_objects(self.total_hits() as usize)
+            .calculate();
+
+        ManiaPerformanceCalculator::new(&attributes).calculate(self)
+    }
+}
+
+/// Performance points of an osu!mania score.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ManiaPerformanceAttributes {
+    pub difficulty: ManiaDifficultyAttributes,
+    pub difficulty_value: f64,
+    pub pp: f64,
+}
+
+/// Computes the performance of osu!mania scores from the difficulty of a beatmap.
+#[derive(Debug, Clone)]
+pub struct ManiaPerformanceCalculator<'a> {
+    attributes: &'a ManiaDifficultyAttributes,
+}
+
+impl<'a> ManiaPerformanceCalculator<'a> {
+    /// `attributes` must have been computed with the mods of the scores.
+    #[inline]
+    pub fn new(attributes: &'a ManiaDifficultyAttributes) -> Self {
+        Self { attributes }
+    }
+
+    pub fn calculate(&self, score: &ManiaScore) -> ManiaPerformanceAttributes {
+        let mods = score.mods;
+
+        let mut multiplier = 8.0;
+        if mods.contains(Mods::NO_FAIL) {
+            multiplier *= 0.75;
+        }
+        if mods.contains(Mods::EASY) {
+            multiplier *= 0.5;
+        }
+
+        let difficulty_value = (self.attributes.stars - 0.15).max(0.05).powf(2.2)
+            * (5.0 * score.accuracy() - 4.0).max(0.0)
+            * (1.0 + 0.1 * (score.total_hits() as f64 / 1500.0).min(1.0));
+
+        ManiaPerformanceAttributes {
+            difficulty: self.attributes.clone(),
+            difficulty_value,
+            pp: difficulty_value * multiplier,
+        }
+    }
+}
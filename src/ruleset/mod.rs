@@ -0,0 +1,9 @@
+//! Gameplay rules of each game mode: object models, difficulty and performance calculation.
+
+pub mod catch;
+pub mod mania;
+pub mod standard;
+pub mod taiko;
+
+mod random;
+mod strain;
@@ -0,0 +1,69 @@
+/// The xorshift random number generator of osu!stable, which converters and beatmap processing
+/// rely on to place objects exactly as it does.
+#[derive(Debug, Clone)]
+pub(crate) struct LegacyRandom {
+    x: u32,
+    y: u32,
+    z: u32,
+    w: u32,
+    bit_buffer: u32,
+    bit_index: u32,
+}
+
+impl LegacyRandom {
+    const INT_TO_REAL: f64 = 1.0 / (i32::MAX as f64 + 1.0);
+
+    #[inline]
+    pub fn new(seed: i32) -> Self {
+        Self {
+            x: seed as u32,
+            y: 842_502_087,
+            z: 3_579_807_591,
+            w: 273_326_509,
+            bit_buffer: 0,
+            bit_index: 32,
+        }
+    }
+
+    #[inline]
+    pub fn next_u32(&mut self) -> u32 {
+        let t = self.x ^ (self.x << 11);
+        self.x = self.y;
+        self.y = self.z;
+        self.z = self.w;
+        self.w = self.w ^ (self.w >> 19) ^ t ^ (t >> 8);
+        self.w
+    }
+
+    /// Returns a random integer between 0 and [`i32::MAX`].
+    #[inline]
+    pub fn next_i32(&mut self) -> i32 {
+        (self.next_u32() & 0x7FFF_FFFF) as i32
+    }
+
+    /// Returns a random number between 0 (inclusive) and 1 (exclusive).
+    #[inline]
+    pub fn next_f64(&mut self) -> f64 {
+        Self::INT_TO_REAL * self.next_i32() as f64
+    }
+
+    /// Returns a random integer between `lower` (inclusive) and `upper` (exclusive).
+    #[inline]
+    pub fn next_in_range(&mut self, lower: f64, upper: f64) -> i32 {
+        (lower + self.next_f64() * (upper - lower)) as i32
+    }
+
+    /// Returns a random boolean, drawing the bits of a random integer one at a time.
+    #[inline]
+    pub fn next_bool(&mut self) -> bool {
+        if self.bit_index == 32 {
+            self.bit_buffer = self.next_u32();
+            self.bit_index = 1;
+        } else {
+            self.bit_index += 1;
+            self.bit_buffer >>= 1;
+        }
+
+        self.bit_buffer & 1 == 1
+    }
+}
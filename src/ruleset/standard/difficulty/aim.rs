@@ -0,0 +1,271 @@
+use std::f64::consts::PI;
+
+use crate::ruleset::strain::StrainPeaks;
+
+use super::{object::DifficultyObject, SECTION_LENGTH};
+
+const WIDE_ANGLE_MULTIPLIER: f64 = 1.5;
+const ACUTE_ANGLE_MULTIPLIER: f64 = 1.95;
+const SLIDER_MULTIPLIER: f64 = 1.35;
+const VELOCITY_CHANGE_MULTIPLIER: f64 = 0.75;
+
+const SKILL_MULTIPLIER: f64 = 23.55;
+const STRAIN_DECAY_BASE: f64 = 0.15;
+
+/// Difficulty of moving the cursor between objects and along sliders.
+pub(super) struct Aim {
+    with_sliders: bool,
+    strain: f64,
+    pub peaks: StrainPeaks,
+}
+
+impl Aim {
+    pub fn new(with_sliders: bool) -> Self {
+        Self {
+            with_sliders,
+            strain: 0.0,
+            peaks: StrainPeaks::new(SECTION_LENGTH),
+        }
+    }
+
+    pub fn process(&mut self, objects: &[DifficultyObject], i: usize) {
+        let current = &objects[i];
+
+        if let Some(previous) = i.checked_sub(1).map(|j| &objects[j]) {
+            let strain = self.strain;
+            self.peaks.advance(current.start_time, |time| {
+                strain * strain_decay(time - previous.start_time)
+            });
+        } else {
+            self.peaks.advance(current.start_time, |_| 0.0);
+        }
+
+        self.strain *= strain_decay(current.delta_time);
+        self.strain += evaluate(objects, i, self.with_sliders) * SKILL_MULTIPLIER;
+
+        self.peaks.update(self.strain);
+    }
+}
+
+#[inline]
+fn strain_decay(ms: f64) -> f64 {
+    STRAIN_DECAY_BASE.powf(ms / 1000.0)
+}
+
+fn evaluate(objects: &[DifficultyObject], i: usize, with_sliders: bool) -> f64 {
+    let current = &objects[i];
+    if current.base.is_spinner() || i <= 1 || objects[i - 1].base.is_spinner() {
+        return 0.0;
+    }
+
+    let last = &objects[i - 1];
+    let last_last = &objects[i - 2];
+
+    let radius = super::object::NORMALISED_RADIUS;
+    let diameter = radius * 2.0;
+
+    // velocity of the jump from the previous object, or of the slider before it if faster
+    let mut curr_velocity = current.lazy_jump_distance / current.strain_time;
+    if last.base.is_slider() && with_sliders {
+        let travel_velocity = last.travel_distance / last.travel_time;
+        let movement_velocity = current.minimum_jump_distance / current.minimum_jump_time;
+        curr_velocity = curr_velocity.max(movement_velocity + travel_velocity);
+    }
+
+    let mut prev_velocity = last.lazy_jump_distance / last.strain_time;
+    if last_last.base.is_slider() && with_sliders {
+        let travel_velocity = last_last.travel_distance / last_last.travel_time;
+        let movement_velocity = last.minimum_jump_distance / last.minimum_jump_time;
+        prev_velocity = prev_velocity.max(movement_velocity + travel_velocity);
+    }
+
+    let mut wide_angle_bonus = 0.0;
+    let mut acute_angle_bonus = 0.0;
+    let mut velocity_change_bonus = 0.0;
+
+    let mut aim_strain = curr_velocity;
+
+    // angle bonuses only apply to a constant rhythm
+    if current.strain_time.max(last.strain_time) < 1.25 * current.strain_time.min(last.strain_time)
+    {
+        if let (Some(curr_angle), Some(last_angle), Some(last_last_angle)) =
+            (current.angle, last.angle, last_last.angle)
+        {
+            let angle_bonus = curr_velocity.min(prev_velocity);
+
+            wide_angle_bonus = calc_wide_angle_bonus(curr_angle);
+            acute_angle_bonus = calc_acute_angle_bonus(curr_angle);
+
+            // only the acute angles of streams at 300 bpm 1/2 or faster are rewarded
+            if current.strain_time > 100.0 {
+                acute_angle_bonus = 0.0;
+            } else {
+                acute_angle_bonus *= calc_acute_angle_bonus(last_angle)
+                    * angle_bonus.min(diameter * 1.25 / current.strain_time)
+                    * (PI / 2.0 * ((100.0 - current.strain_time) / 25.0).min(1.0))
+                        .sin()
+                        .powi(2)
+                    * (PI / 2.0 * (current.lazy_jump_distance.clamp(radius, diameter) - radius)
+                        / radius)
+                        .sin()
+                        .powi(2);
+            }
+
+            // repeated angles are easier
+            wide_angle_bonus *= angle_bonus
+                * (1.0 - wide_angle_bonus.min(calc_wide_angle_bonus(last_angle).powi(3)));
+            acute_angle_bonus *= 0.5
+                + 0.5
+                    * (1.0
+                        - acute_angle_bonus.min(calc_acute_angle_bonus(last_last_angle).powi(3)));
+        }
+    }
+
+    if prev_velocity.max(curr_velocity) != 0.0 {
+        // sliders count as part of the jump when looking at changes of velocity
+        let prev_velocity =
+            (last.lazy_jump_distance + last_last.travel_distance) / last.strain_time;
+        let curr_velocity =
+            (current.lazy_jump_distance + last.travel_distance) / current.strain_time;
+
+        let dist_ratio = (PI / 2.0 * (prev_velocity - curr_velocity).abs()
+            / prev_velocity.max(curr_velocity))
+        .sin()
+        .powi(2);
+
+        // overlapping objects do not change velocity as much as their distance suggests
+        let overlap_velocity_buff = (diameter * 1.25 / current.strain_time.min(last.strain_time))
+            .min((prev_velocity - curr_velocity).abs());
+
+        velocity_change_bonus = overlap_velocity_buff
+            * dist_ratio
+            * (current.strain_time.min(last.strain_time)
+                / current.strain_time.max(last.strain_time))
+            .powi(2);
+    }
+
+    let slider_bonus = if last.base.is_slider() {
+        last.travel_distance / last.travel_time
+    } else {
+        0.0
+    };
+
+    aim_strain += (acute_angle_bonus * ACUTE_ANGLE_MULTIPLIER).max(
+        wide_angle_bonus * WIDE_ANGLE_MULTIPLIER
+            + velocity_change_bonus * VELOCITY_CHANGE_MULTIPLIER,
+    );
+
+    if with_sliders {
+        aim_strain += slider_bonus * SLIDER_MULTIPLIER;
+    }
+
+    aim_strain
+}
+
+#[inline]
+fn calc_wide_angle_bonus(angle: f64) -> f64 {
+    (3.0 / 4.0 * ((5.0 / 6.0 * PI).min(angle.max(PI / 6.0)) - PI / 6.0))
+        .sin()
+        .powi(2)
+}
+
+#[inline]
+fn calc_acute_angle_bonus(angle: f64) -> f64 {
+    1.0 - calc_wide_angle_bonus(angle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::object::circles, *};
+
+    /// Aim difficulty of the last circle, at 50 osu! pixels of radius so that distances are not
+    /// scaled.
+    fn evaluate_last(positions: &[(f32, f32, f64)]) -> f64 {
+        let objects = circles(positions);
+        let objects = DifficultyObject::new_all(&objects, 50.0, 50.0, 1.0);
+        evaluate(&objects, objects.len() - 1, true)
+    }
+
+    #[track_caller]
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-12,
+            "expected {expected}, got {actual}"
+        );
+    }
+
+    #[test]
+    fn needs_two_previous_objects() {
+        let objects = circles(&[(0.0, 0.0, 0.0), (100.0, 0.0, 100.0), (200.0, 0.0, 200.0)]);
+        let objects = DifficultyObject::new_all(&objects, 50.0, 50.0, 1.0);
+        assert_eq!(evaluate(&objects, 0, true), 0.0);
+        assert_eq!(evaluate(&objects, 1, true), 0.0);
+    }
+
+    #[test]
+    fn straight_line() {
+        // the first jump has no angle, so only the velocity of 100px per 100ms counts
+        let strain = evaluate_last(&[
+            (0.0, 0.0, 0.0),
+            (100.0, 0.0, 100.0),
+            (200.0, 0.0, 200.0),
+            (300.0, 0.0, 300.0),
+        ]);
+        assert_close(strain, 1.0);
+    }
+
+    #[test]
+    fn wide_angle_bonus() {
+        // right angles, with a wide angle bonus of sin²(π/4) = 0.5 reduced by the one before by
+        // 1 - 0.5³; at 100ms the acute angle bonus is still 0
+        let strain = evaluate_last(&[
+            (0.0, 0.0, 0.0),
+            (100.0, 0.0, 100.0),
+            (100.0, 100.0, 200.0),
+            (200.0, 100.0, 300.0),
+            (200.0, 200.0, 400.0),
+        ]);
+        assert_close(strain, 1.0 + 1.5 * 0.5 * (1.0 - 0.125));
+    }
+
+    #[test]
+    fn acute_angle_bonus() {
+        // back and forth at 75ms, with the acute angle bonus capped by the velocity and halved as
+        // the angle repeats the one before
+        let velocity = 100.0 / 75.0;
+        let strain = evaluate_last(&[
+            (0.0, 0.0, 0.0),
+            (100.0, 0.0, 75.0),
+            (0.0, 0.0, 150.0),
+            (100.0, 0.0, 225.0),
+            (0.0, 0.0, 300.0),
+        ]);
+        assert_close(strain, velocity + 1.95 * velocity * 0.5);
+    }
+
+    #[test]
+    fn velocity_change_bonus() {
+        // the velocity halves, with a distance ratio of sin²(π/4) = 0.5 and a buff of 0.5
+        let strain = evaluate_last(&[
+            (0.0, 0.0, 0.0),
+            (100.0, 0.0, 100.0),
+            (200.0, 0.0, 200.0),
+            (250.0, 0.0, 300.0),
+        ]);
+        assert_close(strain, 0.5 + 0.75 * 0.5 * 0.5);
+    }
+
+    #[test]
+    fn rhythm_change_removes_angle_bonus() {
+        // 150ms after 100ms: no angle bonus, and the velocity change is scaled by (100/150)²
+        let strain = evaluate_last(&[
+            (0.0, 0.0, 0.0),
+            (100.0, 0.0, 100.0),
+            (100.0, 100.0, 200.0),
+            (200.0, 100.0, 300.0),
+            (200.0, 200.0, 450.0),
+        ]);
+        let velocity_change = 0.25 * (1.0 / 3.0) * (4.0 / 9.0);
+        assert_close(strain, 100.0 / 150.0 + 0.75 * velocity_change);
+    }
+}
@@ -0,0 +1,222 @@
+use crate::ruleset::{standard::object::StandardObjectKind, strain::StrainPeaks};
+
+use super::{object::DifficultyObject, SECTION_LENGTH};
+
+const MAX_OPACITY_BONUS: f64 = 0.4;
+const HIDDEN_BONUS: f64 = 0.2;
+const MIN_VELOCITY: f64 = 0.5;
+const SLIDER_MULTIPLIER: f64 = 1.3;
+const MIN_ANGLE_MULTIPLIER: f64 = 0.2;
+
+const SKILL_MULTIPLIER: f64 = 0.052;
+const STRAIN_DECAY_BASE: f64 = 0.15;
+
+/// Difficulty of memorising and reading objects hidden by the flashlight.
+pub(super) struct Flashlight {
+    hidden: bool,
+    radius: f64,
+    preempt: f64,
+    fade_in: f64,
+    strain: f64,
+    pub peaks: StrainPeaks,
+}
+
+impl Flashlight {
+    /// `preempt` is in beatmap time, not adjusted by the clock rate.
+    pub fn new(hidden: bool, radius: f64, preempt: f64) -> Self {
+        Self {
+            hidden,
+            radius,
+            preempt,
+            // hidden fades objects in over a fraction of the preempt instead
+            fade_in: if hidden {
+                preempt * 0.4
+            } else {
+                400.0 * (preempt / 450.0).min(1.0)
+            },
+            strain: 0.0,
+            peaks: StrainPeaks::new(SECTION_LENGTH),
+        }
+    }
+
+    pub fn process(&mut self, objects: &[DifficultyObject], i: usize) {
+        let current = &objects[i];
+
+        if let Some(previous) = i.checked_sub(1).map(|j| &objects[j]) {
+            let strain = self.strain;
+            self.peaks.advance(current.start_time, |time| {
+                strain * strain_decay(time - previous.start_time)
+            });
+        } else {
+            self.peaks.advance(current.start_time, |_| 0.0);
+        }
+
+        self.strain *= strain_decay(current.delta_time);
+        self.strain += self.evaluate(objects, i) * SKILL_MULTIPLIER;
+
+        self.peaks.update(self.strain);
+    }
+
+    /// Returns the opacity of the object `current` at `time`, in beatmap time.
+    fn opacity_at(&self, current: &DifficultyObject, time: f64) -> f64 {
+        let start_time = current.base.time.as_millis();
+        if time > start_time {
+            return 0.0;
+        }
+
+        let fade_in_start_time = start_time - self.preempt;
+        let fade_in = ((time - fade_in_start_time) / self.fade_in).clamp(0.0, 1.0);
+
+        if self.hidden {
+            let fade_out_start_time = fade_in_start_time + self.fade_in;
+            let fade_out_duration = self.preempt * 0.3;
+            fade_in.min(1.0 - ((time - fade_out_start_time) / fade_out_duration).clamp(0.0, 1.0))
+        } else {
+            fade_in
+        }
+    }
+
+    fn evaluate(&self, objects: &[DifficultyObject], i: usize) -> f64 {
+        let current = &objects[i];
+        if current.base.is_spinner() {
+            return 0.0;
+        }
+
+        let scaling_factor = 52.0 / self.radius;
+
+        let mut small_dist_nerf = 1.0;
+        let mut cumulative_strain_time = 0.0;
+        let mut result = 0.0;
+        let mut last = current;
+        let mut angle_repeat_count = 0.0;
+
+        for n in 0..i.min(10) {
+            let previous = &objects[i - (n + 1)];
+
+            if !previous.base.is_spinner() {
+                let jump_distance = (current.base.stacked_position()
+                    - previous.base.stacked_end_position())
+                .norm() as f64;
+
+                cumulative_strain_time += last.strain_time;
+
+                // the closest object is visible even under the flashlight
+                if n == 0 {
+                    small_dist_nerf = (jump_distance / 75.0).min(1.0);
+                }
+
+                // stacked objects are easy to find
+                let stack_nerf = ((previous.lazy_jump_distance / scaling_factor) / 25.0).min(1.0);
+
+                // objects that are not visible yet are harder to remember
+                let opacity_bonus = 1.0
+                    + MAX_OPACITY_BONUS
+                        * (1.0 - self.opacity_at(current, previous.base.time.as_millis()));
+
+                result += stack_nerf * opacity_bonus * scaling_factor * jump_distance
+                    / cumulative_strain_time;
+
+                if let (Some(previous_angle), Some(current_angle)) = (previous.angle, current.angle)
+                {
+                    // repeated angles are easier to follow
+                    if (previous_angle - current_angle).abs() < 0.02 {
+                        angle_repeat_count += (1.0 - 0.1 * n as f64).max(0.0);
+                    }
+                }
+            }
+
+            last = previous;
+        }
+
+        result = (small_dist_nerf * result).powi(2);
+
+        if self.hidden {
+            result *= 1.0 + HIDDEN_BONUS;
+        }
+
+        result *= MIN_ANGLE_MULTIPLIER + (1.0 - MIN_ANGLE_MULTIPLIER) / (angle_repeat_count + 1.0);
+
+        let mut slider_bonus = 0.0;
+        if let StandardObjectKind::Slider(slider) = &current.base.kind {
+            let repeats = slider.repeat_count() as f64;
+            let pixel_travel_distance = current.lazy_travel_distance / scaling_factor;
+
+            slider_bonus = (pixel_travel_distance / current.travel_time - MIN_VELOCITY)
+                .max(0.0)
+                .sqrt()
+                * pixel_travel_distance;
+
+            if repeats > 0.0 {
+                slider_bonus /= repeats + 1.0;
+            }
+        }
+
+        result + slider_bonus * SLIDER_MULTIPLIER
+    }
+}
+
+#[inline]
+fn strain_decay(ms: f64) -> f64 {
+    STRAIN_DECAY_BASE.powf(ms / 1000.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::object::circles, *};
+
+    /// Flashlight difficulty of the last circle at `(x, y, time)`, at 52 osu! pixels of radius so
+    /// that distances are not scaled, with a preempt of 1200ms.
+    fn evaluate_last(positions: &[(f32, f32, f64)], hidden: bool) -> f64 {
+        let objects = circles(positions);
+        let objects = DifficultyObject::new_all(&objects, 52.0, 50.0, 1.0);
+        Flashlight::new(hidden, 52.0, 1200.0).evaluate(&objects, objects.len() - 1)
+    }
+
+    #[track_caller]
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-12,
+            "expected {expected}, got {actual}"
+        );
+    }
+
+    #[test]
+    fn previous_objects() {
+        // 100px over 100ms and 200px over 200ms, with the repeated straight angle of the
+        // previous jump
+        let strain = evaluate_last(
+            &[
+                (0.0, 0.0, 0.0),
+                (100.0, 0.0, 100.0),
+                (200.0, 0.0, 200.0),
+                (300.0, 0.0, 300.0),
+            ],
+            false,
+        );
+        assert_close(strain, (1.0 + 1.0f64).powi(2) * (0.2 + 0.8 / 2.0));
+    }
+
+    #[test]
+    fn small_distance() {
+        // the object 30px away is visible through the flashlight
+        let strain = evaluate_last(
+            &[(0.0, 0.0, 0.0), (200.0, 0.0, 500.0), (230.0, 0.0, 1000.0)],
+            false,
+        );
+        assert_close(strain, (30.0 / 75.0 * 30.0 / 500.0f64).powi(2));
+    }
+
+    #[test]
+    fn hidden() {
+        let positions = [(0.0, 0.0, 0.0), (200.0, 0.0, 500.0), (200.0, 150.0, 1000.0)];
+        assert_close(evaluate_last(&positions, false), 0.3f64.powi(2));
+
+        // the circle fades in from -200ms to 280ms, then out over 360ms, so it is not fully
+        // visible when the previous one is hit
+        let opacity_bonus = 1.0 + 0.4 * (220.0 / 360.0);
+        assert_close(
+            evaluate_last(&positions, true),
+            (opacity_bonus * 0.3f64).powi(2) * 1.2,
+        );
+    }
+}
@@ -0,0 +1,332 @@
+//! osu!standard star rating, following the current osu! difficulty algorithm.
+
+mod aim;
+mod flashlight;
+mod object;
+mod speed;
+
+use aim::Aim;
+use flashlight::Flashlight;
+use object::DifficultyObject;
+use speed::Speed;
+
+use crate::{
+    file::beatmap::{
+        difficulty::attributes::{difficulty_range, HitWindows},
+        Beatmap,
+    },
+    mode::Mode,
+    mods::Mods,
+    ruleset::strain::weighted_sum,
+};
+
+use super::object::StandardObject;
+
+/// Length of the sections strain peaks are taken from, in milliseconds adjusted by the clock rate.
+pub const SECTION_LENGTH: f64 = 400.0;
+
+const DIFFICULTY_MULTIPLIER: f64 = 0.0675;
+const DECAY_WEIGHT: f64 = 0.9;
+const REDUCED_STRAIN_BASELINE: f64 = 0.75;
+const SKILL_DIFFICULTY_MULTIPLIER: f64 = 1.06;
+const SPEED_DIFFICULTY_MULTIPLIER: f64 = 1.04;
+/// Multiplier of the total performance, used to scale the star rating.
+pub(super) const PERFORMANCE_BASE_MULTIPLIER: f64 = 1.14;
+
+/// Difficulty of an osu!standard beatmap played with mods.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StandardDifficultyAttributes {
+    pub mods: Mods,
+    pub stars: f64,
+    pub aim: f64,
+    pub speed: f64,
+    pub flashlight: f64,
+    /// Ratio of the aim difficulty without and with sliders, 1 for beatmaps without sliders.
+    pub slider_factor: f64,
+    /// Number of notes weighted by their contribution to the speed difficulty.
+    pub speed_note_count: f64,
+    /// AR adjusted by the mods and the clock rate.
+    pub ar: f64,
+    /// OD adjusted by the mods and the clock rate.
+    pub od: f64,
+    /// HP adjusted by the mods.
+    pub hp: f64,
+    pub max_combo: u32,
+    pub n_circles: u32,
+    pub n_sliders: u32,
+    pub n_spinners: u32,
+}
+
+/// Strain peaks of every [`SECTION_LENGTH`] section of an osu!standard beatmap, for graphing.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StandardStrains {
+    pub aim: Vec<f64>,
+    pub aim_no_sliders: Vec<f64>,
+    pub speed: Vec<f64>,
+    pub flashlight: Vec<f64>,
+}
+
+/// Computes the star rating of an osu!standard beatmap.
+#[derive(Debug, Clone)]
+pub struct StandardDifficultyCalculator<'a> {
+    beatmap: &'a Beatmap,
+    mods: Mods,
+    passed_objects: Option<usize>,
+}
+
+impl<'a> StandardDifficultyCalculator<'a> {
+    #[inline]
+    pub fn new(beatmap: &'a Beatmap) -> Self {
+        Self {
+            beatmap,
+            mods: Mods::empty(),
+            passed_objects: None,
+        }
+    }
+
+    #[inline]
+    pub fn mods(mut self, mods: Mods) -> Self {
+        self.mods = mods;
+        self
+    }
+
+    /// Only evaluates the first `passed_objects` hit objects, for scores that failed or quit
+    /// before the end of the beatmap.
+    #[inline]
+    pub fn passed_objects(mut self, passed_objects: usize) -> Self {
+        self.passed_objects = Some(passed_objects);
+        self
+    }
+
+    /// Computes the difficulty attributes of the beatmap.
+    pub fn calculate(&self) -> StandardDifficultyAttributes {
+        let objects = self.objects();
+        let skills = self.skills(&objects);
+
+        let mods = self.mods;
+        let difficulty = self.beatmap.difficulty.apply_mods(Mode::Standard, mods);
+        let clock_rate = mods.clock_rate();
+
+        let aim_value =
+            osu_difficulty_value(skills.aim.peaks.peaks(), 10, SKILL_DIFFICULTY_MULTIPLIER);
+        let aim_no_sliders_value = osu_difficulty_value(
+            skills.aim_no_sliders.peaks.peaks(),
+            10,
+            SKILL_DIFFICULTY_MULTIPLIER,
+        );
+        let speed_value =
+            osu_difficulty_value(skills.speed.peaks.peaks(), 5, SPEED_DIFFICULTY_MULTIPLIER);
+        let flashlight_value =
+            skills.flashlight.peaks.peaks().iter().sum::<f64>() * SKILL_DIFFICULTY_MULTIPLIER;
+
+        let mut aim = aim_value.sqrt() * DIFFICULTY_MULTIPLIER;
+        let aim_no_sliders = aim_no_sliders_value.sqrt() * DIFFICULTY_MULTIPLIER;
+        let mut speed = speed_value.sqrt() * DIFFICULTY_MULTIPLIER;
+        let speed_note_count = skills.speed.relevant_note_count();
+        let mut flashlight = if mods.contains(Mods::FLASHLIGHT) {
+            flashlight_value.sqrt() * DIFFICULTY_MULTIPLIER
+        } else {
+            0.0
+        };
+
+        let slider_factor = if aim > 0.0 { aim_no_sliders / aim } else { 1.0 };
+
+        if mods.contains(Mods::RELAX) {
+            aim *= 0.9;
+            speed = 0.0;
+            flashlight *= 0.7;
+        }
+
+        let stars = stars(aim, speed, flashlight);
+
+        let preempt = difficulty_range(difficulty.ar as f64, 1800.0, 1200.0, 450.0) / clock_rate;
+        let ar = if preempt > 1200.0 {
+            (1800.0 - preempt) / 120.0
+        } else {
+            (1200.0 - preempt) / 150.0 + 5.0
+        };
+        let great_window = great_window(difficulty.od) / clock_rate;
+
+        StandardDifficultyAttributes {
+            mods,
+            stars,
+            aim,
+            speed,
+            flashlight,
+            slider_factor,
+            speed_note_count,
+            ar,
+            od: (80.0 - great_window) / 6.0,
+            hp: difficulty.hp as f64,
+            max_combo: objects.iter().map(StandardObject::max_combo).sum(),
+            n_circles: objects.iter().filter(|o| o.is_circle()).count() as u32,
+            n_sliders: objects.iter().filter(|o| o.is_slider()).count() as u32,
+            n_spinners: objects.iter().filter(|o| o.is_spinner()).count() as u32,
+        }
+    }
+
+    /// Computes the strain peaks of the beatmap.
+    pub fn strains(&self) -> StandardStrains {
+        let objects = self.objects();
+        let skills = self.skills(&objects);
+
+        StandardStrains {
+            aim: skills.aim.peaks.peaks(),
+            aim_no_sliders: skills.aim_no_sliders.peaks.peaks(),
+            speed: skills.speed.peaks.peaks(),
+            flashlight: skills.flashlight.peaks.peaks(),
+        }
+    }
+
+    /// Builds the objects of the whole beatmap, so that stacking is not affected by the objects
+    /// that were not passed, then keeps the passed ones.
+    fn objects(&self) -> Vec<StandardObject> {
+        let mut objects = StandardObject::from_beatmap(self.beatmap, self.mods);
+        if let Some(passed_objects) = self.passed_objects {
+            objects.truncate(passed_objects);
+        }
+        objects
+    }
+
+    fn skills(&self, objects: &[StandardObject]) -> Skills {
+        let mods = self.mods;
+        let difficulty = self.beatmap.difficulty.apply_mods(Mode::Standard, mods);
+        let attributes = difficulty.attributes(Mode::Standard);
+        let radius = attributes.radius.unwrap_or_default();
+        let preempt = attributes.preempt.unwrap_or_default().as_millis();
+
+        let difficulty_objects = DifficultyObject::new_all(
+            objects,
+            radius,
+            great_window(difficulty.od),
+            mods.clock_rate(),
+        );
+
+        let mut skills = Skills {
+            aim: Aim::new(true),
+            aim_no_sliders: Aim::new(false),
+            speed: Speed::new(),
+            flashlight: Flashlight::new(mods.contains(Mods::HIDDEN), radius, preempt),
+        };
+
+        for i in 0..difficulty_objects.len() {
+            skills.aim.process(&difficulty_objects, i);
+            skills.aim_no_sliders.process(&difficulty_objects, i);
+            skills.speed.process(&difficulty_objects, i);
+            skills.flashlight.process(&difficulty_objects, i);
+        }
+
+        skills
+    }
+}
+
+struct Skills {
+    aim: Aim,
+    aim_no_sliders: Aim,
+    speed: Speed,
+    flashlight: Flashlight,
+}
+
+/// Returns the half-width of the 300 hit window in beatmap time.
+#[inline]
+fn great_window(od: f32) -> f64 {
+    HitWindows::new(Mode::Standard, od).map_or(0.0, |windows| windows.great.as_millis())
+}
+
+#[inline]
+fn base_performance(rating: f64) -> f64 {
+    (5.0 * (rating / DIFFICULTY_MULTIPLIER).max(1.0) - 4.0).powi(3) / 100_000.0
+}
+
+/// Combines the skill ratings into the star rating.
+fn stars(aim: f64, speed: f64, flashlight: f64) -> f64 {
+    let base_aim_performance = base_performance(aim);
+    let base_speed_performance = base_performance(speed);
+    let base_flashlight_performance = flashlight.powi(2) * 25.0;
+
+    let base_performance = (base_aim_performance.powf(1.1)
+        + base_speed_performance.powf(1.1)
+        + base_flashlight_performance.powf(1.1))
+    .powf(1.0 / 1.1);
+
+    if base_performance > 0.00001 {
+        PERFORMANCE_BASE_MULTIPLIER.cbrt()
+            * 0.027
+            * ((100_000.0 / 2f64.powf(1.0 / 1.1) * base_performance).cbrt() + 4.0)
+    } else {
+        0.0
+    }
+}
+
+/// Weighted sum of the strain peaks, reducing the `reduced_section_count` highest ones so that a
+/// few isolated difficult sections do not dominate.
+fn osu_difficulty_value(peaks: Vec<f64>, reduced_section_count: usize, multiplier: f64) -> f64 {
+    let mut peaks = peaks.into_iter().filter(|&p| p > 0.0).collect::<Vec<_>>();
+    peaks.sort_by(|a, b| b.total_cmp(a));
+
+    for (i, peak) in peaks.iter_mut().take(reduced_section_count).enumerate() {
+        let t = (i as f64 / reduced_section_count as f64).clamp(0.0, 1.0);
+        let scale = (1.0 + 9.0 * t).log10();
+        *peak *= REDUCED_STRAIN_BASELINE + (1.0 - REDUCED_STRAIN_BASELINE) * scale;
+    }
+
+    weighted_sum(peaks, DECAY_WEIGHT) * multiplier
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[track_caller]
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-12,
+            "expected {expected}, got {actual}"
+        );
+    }
+
+    #[test]
+    fn base_performance_floor() {
+        // ratings below the difficulty multiplier count as the multiplier
+        assert_close(base_performance(0.0), 1.0 / 100_000.0);
+        assert_close(base_performance(DIFFICULTY_MULTIPLIER), 1.0 / 100_000.0);
+        assert_close(
+            base_performance(2.0 * DIFFICULTY_MULTIPLIER),
+            216.0 / 100_000.0,
+        );
+    }
+
+    #[test]
+    fn reduced_peaks() {
+        // the highest peak is reduced to the baseline, the next one by log10(1.9), and empty
+        // sections are ignored
+        let value = osu_difficulty_value(vec![1.0, 0.0, 2.0], 10, 1.0);
+        assert_close(value, 2.0 * 0.75 + 0.9 * (0.75 + 0.25 * 1.9f64.log10()));
+
+        // past the reduced sections, the peaks are only weighted
+        let value = osu_difficulty_value(vec![2.0, 1.0, 1.0], 1, 1.06);
+        assert_close(value, (2.0 * 0.75 + 0.9 + 0.81) * 1.06);
+    }
+
+    #[test]
+    fn star_rating() {
+        let multiplier = 1.14f64.cbrt() * 0.027;
+        // a base performance of 6³ / 10⁵ per skill
+        let rating = 2.0 * DIFFICULTY_MULTIPLIER;
+
+        // equal aim and speed add up to 2^(1 / 1.1) times the performance of one skill
+        assert_close(
+            stars(rating, rating, 0.0),
+            multiplier * (216f64.cbrt() + 4.0),
+        );
+
+        // as does flashlight, whose performance is 25 times its squared rating
+        let flashlight = (216.0 / 100_000.0 / 25.0f64).sqrt();
+        assert_close(
+            stars(rating, rating, flashlight),
+            multiplier * ((216.0 * 1.5f64.powf(1.0 / 1.1)).cbrt() + 4.0),
+        );
+    }
+}
@@ -0,0 +1,232 @@
+use nalgebra_glm::Vec2;
+
+use crate::{
+    file::beatmap::hit_objects::slider_events::{SliderEventKind, LEGACY_LAST_TICK_OFFSET},
+    ruleset::standard::object::{StandardObject, StandardObjectKind, StandardSlider},
+};
+
+/// Radius every distance is scaled to, so that difficulty does not depend on the circle size.
+pub(super) const NORMALISED_RADIUS: f64 = 50.0;
+/// Shortest time between two objects, capping the difficulty of extremely close objects.
+pub(super) const MIN_DELTA_TIME: f64 = 25.0;
+const MAXIMUM_SLIDER_RADIUS: f64 = NORMALISED_RADIUS * 2.4;
+const ASSUMED_SLIDER_RADIUS: f64 = NORMALISED_RADIUS * 1.8;
+
+/// Path a cursor lazily following a slider takes, moving only as much as needed to stay within
+/// the follow circle.
+#[derive(Debug, Clone, Copy, Default)]
+struct LazySlider {
+    travel_distance: f64,
+    travel_time: f64,
+    end_position: Vec2,
+}
+
+impl LazySlider {
+    fn new(object: &StandardObject, slider: &StandardSlider, scaling_factor: f64) -> Self {
+        let events = &slider.events;
+        let duration = (events.end_time - object.time).as_millis();
+
+        // the follow circle only needs to be held until the legacy last tick
+        let travel_time = (duration - LEGACY_LAST_TICK_OFFSET.as_millis()).max(duration / 2.0);
+
+        let mut end_time_min = travel_time / events.span_duration.as_millis();
+        if !end_time_min.is_finite() {
+            end_time_min = 0.0;
+        }
+        end_time_min = if end_time_min % 2.0 >= 1.0 {
+            1.0 - end_time_min % 1.0
+        } else {
+            end_time_min % 1.0
+        };
+
+        let lazy_end_position = object.stacked_position() + events.path.position_at(end_time_min);
+
+        let mut cursor = object.stacked_position();
+        let mut travel_distance = 0.0;
+        let last = slider.nested.len().saturating_sub(1);
+
+        for (i, nested) in slider.nested.iter().enumerate().skip(1) {
+            let mut movement = nested.position - cursor;
+            let mut required_movement = ASSUMED_SLIDER_RADIUS;
+
+            if i == last {
+                // the cursor only needs to be within the follow circle at the end of the slider
+                let lazy_movement = lazy_end_position - cursor;
+                if lazy_movement.norm() < movement.norm() {
+                    movement = lazy_movement;
+                }
+            } else if nested.kind == SliderEventKind::Repeat {
+                required_movement = NORMALISED_RADIUS;
+            }
+
+            let mut movement_length = scaling_factor * movement.norm() as f64;
+            if movement_length > required_movement {
+                let ratio = (movement_length - required_movement) / movement_length;
+                cursor += movement * ratio as f32;
+                movement_length *= ratio;
+                travel_distance += movement_length;
+            }
+        }
+
+        Self {
+            travel_distance,
+            travel_time,
+            end_position: cursor,
+        }
+    }
+}
+
+/// An osu!standard object preprocessed for difficulty calculation, relative to the object before
+/// it.
+#[derive(Debug, Clone)]
+pub(super) struct DifficultyObject<'a> {
+    pub base: &'a StandardObject,
+    /// Start time adjusted by the clock rate.
+    pub start_time: f64,
+    /// Time since the previous object, adjusted by the clock rate.
+    pub delta_time: f64,
+    /// [`delta_time`][DifficultyObject::delta_time] capped to [`MIN_DELTA_TIME`].
+    pub strain_time: f64,
+    /// Normalised distance from the end of the previous object's cursor path.
+    pub lazy_jump_distance: f64,
+    /// Normalised distance the cursor has to travel from the previous object, at least.
+    pub minimum_jump_distance: f64,
+    pub minimum_jump_time: f64,
+    /// Normalised distance travelled by a cursor lazily following the object, if it is a slider.
+    pub lazy_travel_distance: f64,
+    /// [`lazy_travel_distance`][DifficultyObject::lazy_travel_distance] with a bonus for repeats.
+    pub travel_distance: f64,
+    pub travel_time: f64,
+    /// Angle between the previous object, this object and the one before the previous.
+    pub angle: Option<f64>,
+    /// Width of the 300 hit window, adjusted by the clock rate.
+    pub hit_window_great: f64,
+}
+
+impl<'a> DifficultyObject<'a> {
+    /// Builds the difficulty objects of `objects`, skipping the first object.
+    pub fn new_all(
+        objects: &'a [StandardObject],
+        radius: f64,
+        great_window: f64,
+        clock_rate: f64,
+    ) -> Vec<Self> {
+        let mut scaling_factor = NORMALISED_RADIUS / radius;
+        if radius < 30.0 {
+            // small circles are harder to hit than their size suggests
+            let small_circle_bonus = (30.0 - radius).min(5.0) / 50.0;
+            scaling_factor *= 1.0 + small_circle_bonus;
+        }
+
+        let lazy = objects
+            .iter()
+            .map(|object| match &object.kind {
+                StandardObjectKind::Slider(slider) => {
+                    LazySlider::new(object, slider, NORMALISED_RADIUS / radius)
+                }
+                _ => LazySlider {
+                    end_position: object.stacked_position(),
+                    ..Default::default()
+                },
+            })
+            .collect::<Vec<_>>();
+
+        (1..objects.len())
+            .map(|i| {
+                let base = &objects[i];
+                let last = &objects[i - 1];
+                let last_last = i.checked_sub(2).map(|j| &objects[j]);
+
+                let delta_time = (base.time - last.time).as_millis() / clock_rate;
+                let strain_time = delta_time.max(MIN_DELTA_TIME);
+
+                let mut object = Self {
+                    base,
+                    start_time: base.time.as_millis() / clock_rate,
+                    delta_time,
+                    strain_time,
+                    lazy_jump_distance: 0.0,
+                    minimum_jump_distance: 0.0,
+                    minimum_jump_time: 0.0,
+                    lazy_travel_distance: lazy[i].travel_distance,
+                    travel_distance: 0.0,
+                    travel_time: 0.0,
+                    angle: None,
+                    hit_window_great: 2.0 * great_window / clock_rate,
+                };
+
+                if let StandardObjectKind::Slider(slider) = &base.kind {
+                    let repeats = slider.repeat_count() as f64;
+                    object.travel_distance =
+                        lazy[i].travel_distance * (1.0 + repeats / 2.5).powf(1.0 / 2.5);
+                    object.travel_time = (lazy[i].travel_time / clock_rate).max(MIN_DELTA_TIME);
+                }
+
+                if base.is_spinner() || last.is_spinner() {
+                    return object;
+                }
+
+                let last_cursor = lazy[i - 1].end_position;
+                object.lazy_jump_distance = ((base.stacked_position() * scaling_factor as f32)
+                    - (last_cursor * scaling_factor as f32))
+                    .norm() as f64;
+                object.minimum_jump_time = strain_time;
+                object.minimum_jump_distance = object.lazy_jump_distance;
+
+                if last.is_slider() {
+                    let last_travel_time =
+                        (lazy[i - 1].travel_time / clock_rate).max(MIN_DELTA_TIME);
+                    object.minimum_jump_time = (strain_time - last_travel_time).max(MIN_DELTA_TIME);
+
+                    // the cursor can leave the follow circle before the tail and still hit it
+                    let tail_jump_distance = (last.stacked_end_position() - base.stacked_position())
+                        .norm() as f64
+                        * scaling_factor;
+                    object.minimum_jump_distance = (object.lazy_jump_distance
+                        - (MAXIMUM_SLIDER_RADIUS - ASSUMED_SLIDER_RADIUS))
+                        .min(tail_jump_distance - MAXIMUM_SLIDER_RADIUS)
+                        .max(0.0);
+                }
+
+                if last_last.is_some_and(|o| !o.is_spinner()) {
+                    let last_last_cursor = lazy[i - 2].end_position;
+
+                    let v1 = last_last_cursor - last.stacked_position();
+                    let v2 = base.stacked_position() - last_cursor;
+
+                    let dot = v1.dot(&v2) as f64;
+                    let det = (v1.x * v2.y - v1.y * v2.x) as f64;
+
+                    object.angle = Some(det.atan2(dot).abs());
+                }
+
+                object
+            })
+            .collect()
+    }
+}
+
+/// Builds unstacked circles from `(x, y, time)`, for the tests of the skills.
+#[cfg(test)]
+pub(super) fn circles(circles: &[(f32, f32, f64)]) -> Vec<StandardObject> {
+    use crate::{file::beatmap::hit_objects::stacking::Stacking, time::Time};
+
+    circles
+        .iter()
+        .map(|&(x, y, time)| {
+            let position = Vec2::new(x, y);
+            StandardObject {
+                time: Time(time),
+                end_time: Time(time),
+                position,
+                stacking: Stacking {
+                    height: 0,
+                    offset: Vec2::zeros(),
+                    position,
+                    end_position: position,
+                },
+                kind: StandardObjectKind::Circle,
+            }
+        })
+        .collect()
+}
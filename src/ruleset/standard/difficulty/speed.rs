@@ -0,0 +1,307 @@
+use std::f64::consts::PI;
+
+use crate::ruleset::strain::StrainPeaks;
+
+use super::{object::DifficultyObject, SECTION_LENGTH};
+
+const SINGLE_SPACING_THRESHOLD: f64 = 125.0;
+const MIN_SPEED_BONUS: f64 = 75.0;
+const SPEED_BALANCING_FACTOR: f64 = 40.0;
+
+const HISTORY_TIME_MAX: f64 = 5000.0;
+const RHYTHM_MULTIPLIER: f64 = 0.75;
+
+const SKILL_MULTIPLIER: f64 = 1375.0;
+const STRAIN_DECAY_BASE: f64 = 0.3;
+
+/// Difficulty of tapping objects quickly and in complex rhythms.
+pub(super) struct Speed {
+    strain: f64,
+    rhythm: f64,
+    pub peaks: StrainPeaks,
+    /// Strain of every object, to count the notes relevant to the difficulty.
+    pub object_strains: Vec<f64>,
+}
+
+impl Speed {
+    pub fn new() -> Self {
+        Self {
+            strain: 0.0,
+            rhythm: 0.0,
+            peaks: StrainPeaks::new(SECTION_LENGTH),
+            object_strains: Vec::new(),
+        }
+    }
+
+    pub fn process(&mut self, objects: &[DifficultyObject], i: usize) {
+        let current = &objects[i];
+
+        if let Some(previous) = i.checked_sub(1).map(|j| &objects[j]) {
+            let strain = self.strain * self.rhythm;
+            self.peaks.advance(current.start_time, |time| {
+                strain * strain_decay(time - previous.start_time)
+            });
+        } else {
+            self.peaks.advance(current.start_time, |_| 0.0);
+        }
+
+        self.strain *= strain_decay(current.strain_time);
+        self.strain += evaluate(objects, i) * SKILL_MULTIPLIER;
+        self.rhythm = evaluate_rhythm(objects, i);
+
+        let total = self.strain * self.rhythm;
+        self.object_strains.push(total);
+        self.peaks.update(total);
+    }
+
+    /// Returns the number of notes weighted by how close their strain is to the highest one.
+    pub fn relevant_note_count(&self) -> f64 {
+        let max = self.object_strains.iter().copied().fold(0.0, f64::max);
+        if max == 0.0 {
+            return 0.0;
+        }
+
+        self.object_strains
+            .iter()
+            .map(|strain| 1.0 / (1.0 + (-(strain / max * 12.0 - 6.0)).exp()))
+            .sum()
+    }
+}
+
+#[inline]
+fn strain_decay(ms: f64) -> f64 {
+    STRAIN_DECAY_BASE.powf(ms / 1000.0)
+}
+
+fn evaluate(objects: &[DifficultyObject], i: usize) -> f64 {
+    let current = &objects[i];
+    if current.base.is_spinner() {
+        return 0.0;
+    }
+
+    let previous = i.checked_sub(1).map(|j| &objects[j]);
+    let next = objects.get(i + 1);
+
+    let mut strain_time = current.strain_time;
+    let great_window_full = current.hit_window_great;
+
+    // notes that can be double tapped are easier
+    let mut doubletapness = 1.0;
+    if let Some(next) = next {
+        let curr_delta_time = current.delta_time.max(1.0);
+        let next_delta_time = next.delta_time.max(1.0);
+        let delta_difference = (next_delta_time - curr_delta_time).abs();
+        let speed_ratio = curr_delta_time / curr_delta_time.max(delta_difference);
+        let window_ratio = (curr_delta_time / great_window_full).min(1.0).powi(2);
+        doubletapness = speed_ratio.powf(1.0 - window_ratio);
+    }
+
+    // objects closer than the hit window can be hit in a single tap
+    strain_time /= (strain_time / great_window_full / 0.93).clamp(0.92, 1.0);
+
+    let mut speed_bonus = 1.0;
+    if strain_time < MIN_SPEED_BONUS {
+        speed_bonus += 0.75 * ((MIN_SPEED_BONUS - strain_time) / SPEED_BALANCING_FACTOR).powi(2);
+    }
+
+    let travel_distance = previous.map_or(0.0, |p| p.travel_distance);
+    let distance = (travel_distance + current.minimum_jump_distance).min(SINGLE_SPACING_THRESHOLD);
+
+    (speed_bonus + speed_bonus * (distance / SINGLE_SPACING_THRESHOLD).powf(3.5)) * doubletapness
+        / strain_time
+}
+
+/// Returns a multiplier for the complexity of the rhythm leading to the object.
+fn evaluate_rhythm(objects: &[DifficultyObject], i: usize) -> f64 {
+    let current = &objects[i];
+    if current.base.is_spinner() {
+        return 0.0;
+    }
+
+    // `previous(n)` is the object `n + 1` objects before the current one
+    let previous = |n: usize| &objects[i - (n + 1)];
+
+    let mut previous_island_size = 0;
+    let mut rhythm_complexity_sum = 0.0;
+    let mut island_size = 1;
+    let mut start_ratio = 0.0;
+    let mut first_delta_switch = false;
+
+    let historical_note_count = i.min(32);
+
+    let mut rhythm_start = 0;
+    while rhythm_start + 2 < historical_note_count
+        && current.start_time - previous(rhythm_start).start_time < HISTORY_TIME_MAX
+    {
+        rhythm_start += 1;
+    }
+
+    for n in (1..=rhythm_start).rev() {
+        let curr = previous(n - 1);
+        let prev = previous(n);
+        let last = previous(n + 1);
+
+        let mut curr_historical_decay =
+            (HISTORY_TIME_MAX - (current.start_time - curr.start_time)) / HISTORY_TIME_MAX;
+        // the most recent notes are the most important
+        curr_historical_decay = ((historical_note_count - n) as f64 / historical_note_count as f64)
+            .min(curr_historical_decay);
+
+        let curr_delta = curr.strain_time;
+        let prev_delta = prev.strain_time;
+        let last_delta = last.strain_time;
+
+        let curr_ratio = 1.0
+            + 6.0
+                * (PI / (prev_delta.min(curr_delta) / prev_delta.max(curr_delta)))
+                    .sin()
+                    .powi(2)
+                    .min(0.5);
+
+        let window_penalty = (((prev_delta - curr_delta).abs() - curr.hit_window_great * 0.3)
+            .max(0.0)
+            / (curr.hit_window_great * 0.3))
+            .min(1.0);
+
+        let mut effective_ratio = window_penalty * curr_ratio;
+
+        if first_delta_switch {
+            if !(prev_delta > 1.25 * curr_delta || prev_delta * 1.25 < curr_delta) {
+                // the island of notes with the same rhythm grows
+                if island_size < 7 {
+                    island_size += 1;
+                }
+            } else {
+                if curr.base.is_slider() {
+                    effective_ratio *= 0.125;
+                }
+                if prev.base.is_slider() {
+                    effective_ratio *= 0.25;
+                }
+                if previous_island_size == island_size {
+                    effective_ratio *= 0.25;
+                }
+                if previous_island_size % 2 == island_size % 2 {
+                    effective_ratio *= 0.5;
+                }
+                if last_delta > prev_delta + 10.0 && prev_delta > curr_delta + 10.0 {
+                    // slowing down is easy
+                    effective_ratio *= 0.125;
+                }
+
+                rhythm_complexity_sum += (effective_ratio * start_ratio).sqrt()
+                    * curr_historical_decay
+                    * ((4 + island_size) as f64).sqrt()
+                    / 2.0
+                    * ((4 + previous_island_size) as f64).sqrt()
+                    / 2.0;
+
+                start_ratio = effective_ratio;
+                previous_island_size = island_size;
+
+                if prev_delta * 1.25 < curr_delta {
+                    first_delta_switch = false;
+                }
+                island_size = 1;
+            }
+        } else if prev_delta > 1.25 * curr_delta {
+            // the rhythm speeds up, starting a new island
+            first_delta_switch = true;
+            start_ratio = effective_ratio;
+            island_size = 1;
+        }
+    }
+
+    (4.0 + rhythm_complexity_sum * RHYTHM_MULTIPLIER).sqrt() / 2.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::object::circles, *};
+
+    /// Speed difficulty of the object `i` among circles at `(x, y, time)`, at 50 osu! pixels of
+    /// radius with a 300 hit window of 100ms.
+    fn evaluate_at(positions: &[(f32, f32, f64)], i: usize) -> f64 {
+        let objects = circles(positions);
+        evaluate(&DifficultyObject::new_all(&objects, 50.0, 50.0, 1.0), i)
+    }
+
+    fn evaluate_rhythm_at(times: &[f64], i: usize) -> f64 {
+        let positions = times.iter().map(|&t| (0.0, 0.0, t)).collect::<Vec<_>>();
+        let objects = circles(&positions);
+        evaluate_rhythm(&DifficultyObject::new_all(&objects, 50.0, 50.0, 1.0), i)
+    }
+
+    #[track_caller]
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-12,
+            "expected {expected}, got {actual}"
+        );
+    }
+
+    /// Strain of a 50ms stream, which is shorter than the hit window and counts as 50 / 0.92ms.
+    fn stream_strain() -> f64 {
+        let strain_time: f64 = 50.0 / 0.92;
+        (1.0 + 0.75 * ((75.0 - strain_time) / 40.0).powi(2)) / strain_time
+    }
+
+    #[test]
+    fn spacing() {
+        // jumps are capped at 125px, doubling the strain
+        let strain = evaluate_at(
+            &[(0.0, 0.0, 0.0), (200.0, 0.0, 200.0), (400.0, 0.0, 400.0)],
+            0,
+        );
+        assert_close(strain, 2.0 / 200.0);
+
+        let strain = evaluate_at(
+            &[(0.0, 0.0, 0.0), (62.5, 0.0, 200.0), (125.0, 0.0, 400.0)],
+            0,
+        );
+        assert_close(strain, (1.0 + 0.5f64.powf(3.5)) / 200.0);
+    }
+
+    #[test]
+    fn stream() {
+        let strain = evaluate_at(&[(0.0, 0.0, 0.0), (0.0, 0.0, 50.0), (0.0, 0.0, 100.0)], 0);
+        assert_close(strain, stream_strain());
+    }
+
+    #[test]
+    fn doubletap() {
+        // a 1/4 followed by a 1/1 can be double tapped, with a speed ratio of 50 / 150 weighted
+        // by 1 - (50 / 100)²
+        let strain = evaluate_at(
+            &[
+                (0.0, 0.0, 0.0),
+                (0.0, 0.0, 200.0),
+                (0.0, 0.0, 250.0),
+                (0.0, 0.0, 450.0),
+            ],
+            1,
+        );
+        assert_close(strain, (1.0f64 / 3.0).powf(0.75) * stream_strain());
+    }
+
+    #[test]
+    fn constant_rhythm() {
+        let times = (0..10).map(|i| i as f64 * 100.0).collect::<Vec<_>>();
+        for i in 0..times.len() - 1 {
+            assert_close(evaluate_rhythm_at(&times, i), 1.0);
+        }
+    }
+
+    #[test]
+    fn rhythm_change() {
+        // 1/1, an island of two 1/2 and back to 1/1: the change back is halved as both islands
+        // have an even size, and decays as the 4th of 5 previous notes
+        let times = [0.0, 200.0, 400.0, 500.0, 600.0, 800.0, 1000.0];
+
+        let complexity = 0.5f64.sqrt() * 0.8 * 6f64.sqrt() / 2.0;
+        assert_close(
+            evaluate_rhythm_at(&times, 5),
+            (4.0 + complexity * 0.75).sqrt() / 2.0,
+        );
+    }
+}
@@ -0,0 +1,3 @@
+pub mod difficulty;
+pub mod object;
+pub mod performance;
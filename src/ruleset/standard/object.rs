@@ -0,0 +1,173 @@
+use nalgebra_glm::Vec2;
+
+use crate::{
+    file::beatmap::{
+        hit_objects::{
+            slider_events::{SliderEventKind, SliderEvents},
+            stacking::{stacking, Stacking},
+        },
+        Beatmap,
+    },
+    mode::Mode,
+    mods::Mods,
+    time::Time,
+};
+
+/// A judgement point of a slider, see [`StandardSlider::nested`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NestedObject {
+    /// [`Head`][SliderEventKind::Head], [`Tick`][SliderEventKind::Tick],
+    /// [`Repeat`][SliderEventKind::Repeat] or [`Tail`][SliderEventKind::Tail].
+    pub kind: SliderEventKind,
+    pub time: Time,
+    /// Position with the stack offset applied.
+    pub position: Vec2,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StandardSlider {
+    pub events: SliderEvents,
+    /// Judgement points in chronological order; the tail is judged at the legacy last tick, but
+    /// placed at the end of the slider.
+    pub nested: Vec<NestedObject>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum StandardObjectKind {
+    Circle,
+    Slider(StandardSlider),
+    Spinner,
+}
+
+/// An osu!standard hit object, with the mods and stacking applied.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StandardObject {
+    pub time: Time,
+    pub end_time: Time,
+    /// Position, flipped by [`HARD_ROCK`][Mods::HARD_ROCK].
+    pub position: Vec2,
+    pub stacking: Stacking,
+    pub kind: StandardObjectKind,
+}
+
+impl StandardObject {
+    /// Builds the objects of an osu!standard `beatmap` played with `mods`.
+    pub fn from_beatmap(beatmap: &Beatmap, mods: Mods) -> Vec<Self> {
+        let difficulty = beatmap.difficulty.apply_mods(Mode::Standard, mods);
+        let control_points = beatmap.control_points();
+        let version = beatmap.file_format_version;
+
+        let mut hit_objects = beatmap.hit_objects.clone();
+        if mods.contains(Mods::HARD_ROCK) {
+            hit_objects.iter_mut().for_each(|h| h.flip_vertically());
+        }
+
+        let stacks = stacking(
+            &hit_objects,
+            &difficulty,
+            beatmap.general.stack_leniency,
+            &control_points,
+            version,
+        );
+
+        hit_objects
+            .iter()
+            .zip(stacks)
+            .map(|(hit_object, stacking)| {
+                let events = hit_object.slider_events(&difficulty, &control_points, version);
+                let kind = match events {
+                    Some(events) => {
+                        StandardObjectKind::Slider(StandardSlider::new(events, stacking.offset))
+                    }
+                    None if hit_object.kind.is_spinner() => StandardObjectKind::Spinner,
+                    None => StandardObjectKind::Circle,
+                };
+
+                let end_time = match &kind {
+                    StandardObjectKind::Slider(slider) => slider.events.end_time,
+                    _ => hit_object.end_time(),
+                };
+
+                Self {
+                    time: hit_object.time,
+                    end_time,
+                    position: hit_object.position,
+                    stacking,
+                    kind,
+                }
+            })
+            .collect()
+    }
+
+    #[inline]
+    pub fn is_circle(&self) -> bool {
+        matches!(self.kind, StandardObjectKind::Circle)
+    }
+
+    #[inline]
+    pub fn is_slider(&self) -> bool {
+        matches!(self.kind, StandardObjectKind::Slider(_))
+    }
+
+    #[inline]
+    pub fn is_spinner(&self) -> bool {
+        matches!(self.kind, StandardObjectKind::Spinner)
+    }
+
+    #[inline]
+    pub fn stacked_position(&self) -> Vec2 {
+        self.stacking.position
+    }
+
+    #[inline]
+    pub fn stacked_end_position(&self) -> Vec2 {
+        self.stacking.end_position
+    }
+
+    /// Returns the combo the object gives: one for circles and spinners, and one per judgement
+    /// point for sliders.
+    #[inline]
+    pub fn max_combo(&self) -> u32 {
+        match &self.kind {
+            StandardObjectKind::Slider(slider) => slider.nested.len() as u32,
+            _ => 1,
+        }
+    }
+}
+
+impl StandardSlider {
+    fn new(events: SliderEvents, offset: Vec2) -> Self {
+        let tail = events.tail().map(|tail| tail.position);
+
+        let mut nested = events
+            .events
+            .iter()
+            .filter_map(|event| {
+                let position = match event.kind {
+                    SliderEventKind::Tail => return None,
+                    SliderEventKind::LegacyLastTick => tail.unwrap_or(event.position),
+                    _ => event.position,
+                };
+                let kind = match event.kind {
+                    SliderEventKind::LegacyLastTick => SliderEventKind::Tail,
+                    kind => kind,
+                };
+
+                Some(NestedObject {
+                    kind,
+                    time: event.time,
+                    position: position + offset,
+                })
+            })
+            .collect::<Vec<_>>();
+        nested.sort_by(|a, b| a.time.total_cmp(&b.time));
+
+        Self { events, nested }
+    }
+
+    #[inline]
+    pub fn repeat_count(&self) -> u32 {
+        self.events.spans - 1
+    }
+}
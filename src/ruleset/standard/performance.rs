@@ -0,0 +1,369 @@
+//! osu!standard performance points, following the current osu! performance algorithm.
+
+use crate::{file::beatmap::Beatmap, mods::Mods};
+
+use super::difficulty::{
+    StandardDifficultyAttributes, StandardDifficultyCalculator, PERFORMANCE_BASE_MULTIPLIER,
+};
+
+/// Judgements and combo of an osu!standard score.
+///
+/// The number of judgements is the number of hit objects that were played, so a score that
+/// failed or quit early covers only a prefix of the beatmap's hit objects.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StandardScore {
+    pub mods: Mods,
+    pub max_combo: u32,
+    pub n300: u32,
+    pub n100: u32,
+    pub n50: u32,
+    pub n_miss: u32,
+}
+
+impl StandardScore {
+    /// Returns the number of judged hit objects.
+    #[inline]
+    pub fn total_hits(&self) -> u32 {
+        self.n300 + self.n100 + self.n50 + self.n_miss
+    }
+
+    /// Returns the accuracy between 0 and 1.
+    #[inline]
+    pub fn accuracy(&self) -> f64 {
+        let total_hits = self.total_hits();
+        if total_hits == 0 {
+            return 0.0;
+        }
+
+        (300 * self.n300 + 100 * self.n100 + 50 * self.n50) as f64 / (300 * total_hits) as f64
+    }
+
+    /// Computes the difficulty of the hit objects covered by the score, then its performance.
+    pub fn performance(&self, beatmap: &Beatmap) -> StandardPerformanceAttributes {
+        let attributes = StandardDifficultyCalculator::new(beatmap)
+            .mods(self.mods)
+            .passed_objects(self.total_hits() as usize)
+            .calculate();
+
+        StandardPerformanceCalculator::new(&attributes).calculate(self)
+    }
+}
+
+/// Performance points of an osu!standard score.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StandardPerformanceAttributes {
+    pub difficulty: StandardDifficultyAttributes,
+    pub aim: f64,
+    pub speed: f64,
+    pub accuracy: f64,
+    pub flashlight: f64,
+    /// Misses, including the ones estimated from combo breaks.
+    pub effective_miss_count: f64,
+    pub pp: f64,
+}
+
+/// Computes the performance of osu!standard scores from the difficulty of a beatmap.
+#[derive(Debug, Clone)]
+pub struct StandardPerformanceCalculator<'a> {
+    attributes: &'a StandardDifficultyAttributes,
+}
+
+/// Values of a score shared by every part of the performance.
+struct Score {
+    mods: Mods,
+    max_combo: f64,
+    n300: f64,
+    n100: f64,
+    n50: f64,
+    n_miss: f64,
+    total_hits: f64,
+    accuracy: f64,
+    effective_miss_count: f64,
+}
+
+impl<'a> StandardPerformanceCalculator<'a> {
+    /// `attributes` must have been computed with the mods of the scores and, for partial scores,
+    /// with as many passed objects as the scores have judgements.
+    #[inline]
+    pub fn new(attributes: &'a StandardDifficultyAttributes) -> Self {
+        Self { attributes }
+    }
+
+    pub fn calculate(&self, score: &StandardScore) -> StandardPerformanceAttributes {
+        let attributes = self.attributes;
+        let mods = score.mods;
+
+        let mut s = Score {
+            mods,
+            max_combo: score.max_combo as f64,
+            n300: score.n300 as f64,
+            n100: score.n100 as f64,
+            n50: score.n50 as f64,
+            n_miss: score.n_miss as f64,
+            total_hits: score.total_hits() as f64,
+            accuracy: score.accuracy(),
+            effective_miss_count: 0.0,
+        };
+        s.effective_miss_count = self.effective_miss_count(&s);
+
+        let mut multiplier = PERFORMANCE_BASE_MULTIPLIER;
+
+        if mods.contains(Mods::NO_FAIL) {
+            multiplier *= (1.0 - 0.02 * s.effective_miss_count).max(0.9);
+        }
+        if mods.contains(Mods::SPUN_OUT) && s.total_hits > 0.0 {
+            multiplier *= 1.0 - (attributes.n_spinners as f64 / s.total_hits).powf(0.85);
+        }
+        if mods.contains(Mods::RELAX) {
+            // 100s and 50s are likely misses with relax, more so on lower OD
+            let od = attributes.od;
+            let ok_multiplier = if od > 0.0 {
+                (1.0 - (od / 13.33).powf(1.8)).max(0.0)
+            } else {
+                1.0
+            };
+            let meh_multiplier = if od > 0.0 {
+                (1.0 - (od / 13.33).powi(5)).max(0.0)
+            } else {
+                1.0
+            };
+            s.effective_miss_count =
+                (s.effective_miss_count + s.n100 * ok_multiplier + s.n50 * meh_multiplier)
+                    .min(s.total_hits);
+        }
+
+        let aim = self.aim_value(&s);
+        let speed = self.speed_value(&s);
+        let accuracy = self.accuracy_value(&s);
+        let flashlight = self.flashlight_value(&s);
+
+        let pp = (aim.powf(1.1) + speed.powf(1.1) + accuracy.powf(1.1) + flashlight.powf(1.1))
+            .powf(1.0 / 1.1)
+            * multiplier;
+
+        StandardPerformanceAttributes {
+            difficulty: attributes.clone(),
+            aim,
+            speed,
+            accuracy,
+            flashlight,
+            effective_miss_count: s.effective_miss_count,
+            pp,
+        }
+    }
+
+    fn aim_value(&self, s: &Score) -> f64 {
+        let attributes = self.attributes;
+
+        let mut aim_value = (5.0 * (attributes.aim / 0.0675).max(1.0) - 4.0).powi(3) / 100_000.0;
+
+        let length_bonus = length_bonus(s.total_hits);
+        aim_value *= length_bonus;
+
+        // penalize misses, assuming a miss on a longer map is less likely
+        if s.effective_miss_count > 0.0 {
+            aim_value *= 0.97
+                * (1.0 - (s.effective_miss_count / s.total_hits).powf(0.775))
+                    .powf(s.effective_miss_count);
+        }
+
+        aim_value *= self.combo_scaling_factor(s);
+
+        let mut approach_rate_factor = if attributes.ar > 10.33 {
+            0.3 * (attributes.ar - 10.33)
+        } else if attributes.ar < 8.0 {
+            0.05 * (8.0 - attributes.ar)
+        } else {
+            0.0
+        };
+        if s.mods.contains(Mods::RELAX) {
+            approach_rate_factor = 0.0;
+        }
+
+        // high AR rewards reading, low AR rewards memorisation
+        aim_value *= 1.0 + approach_rate_factor * length_bonus;
+
+        if s.mods.contains(Mods::HIDDEN) {
+            aim_value *= 1.0 + 0.04 * (12.0 - attributes.ar);
+        }
+
+        // dropped slider ends make the difficulty of sliders less relevant
+        let estimate_difficult_sliders = attributes.n_sliders as f64 * 0.15;
+        if attributes.n_sliders > 0 {
+            let estimate_slider_ends_dropped = (s.n100 + s.n50 + s.n_miss)
+                .min(attributes.max_combo as f64 - s.max_combo)
+                .clamp(0.0, estimate_difficult_sliders);
+            let slider_nerf_factor = (1.0 - attributes.slider_factor)
+                * (1.0 - estimate_slider_ends_dropped / estimate_difficult_sliders).powi(3)
+                + attributes.slider_factor;
+            aim_value *= slider_nerf_factor;
+        }
+
+        aim_value *= s.accuracy;
+        aim_value *= 0.98 + attributes.od.powi(2) / 2500.0;
+
+        aim_value
+    }
+
+    fn speed_value(&self, s: &Score) -> f64 {
+        if s.mods.contains(Mods::RELAX) {
+            return 0.0;
+        }
+
+        let attributes = self.attributes;
+
+        let mut speed_value =
+            (5.0 * (attributes.speed / 0.0675).max(1.0) - 4.0).powi(3) / 100_000.0;
+
+        let length_bonus = length_bonus(s.total_hits);
+        speed_value *= length_bonus;
+
+        if s.effective_miss_count > 0.0 {
+            speed_value *= 0.97
+                * (1.0 - (s.effective_miss_count / s.total_hits).powf(0.775))
+                    .powf(s.effective_miss_count.powf(0.875));
+        }
+
+        speed_value *= self.combo_scaling_factor(s);
+
+        let approach_rate_factor = if attributes.ar > 10.33 {
+            0.3 * (attributes.ar - 10.33)
+        } else {
+            0.0
+        };
+        speed_value *= 1.0 + approach_rate_factor * length_bonus;
+
+        if s.mods.contains(Mods::HIDDEN) {
+            speed_value *= 1.0 + 0.04 * (12.0 - attributes.ar);
+        }
+
+        // accuracy on the notes that matter for speed, assuming the worst judgements are on them
+        let relevant_total_diff = s.total_hits - attributes.speed_note_count;
+        let relevant_n300 = (s.n300 - relevant_total_diff).max(0.0);
+        let relevant_n100 = (s.n100 - (relevant_total_diff - s.n300).max(0.0)).max(0.0);
+        let relevant_n50 = (s.n50 - (relevant_total_diff - s.n300 - s.n100).max(0.0)).max(0.0);
+        let relevant_accuracy = if attributes.speed_note_count == 0.0 {
+            0.0
+        } else {
+            (relevant_n300 * 6.0 + relevant_n100 * 2.0 + relevant_n50)
+                / (attributes.speed_note_count * 6.0)
+        };
+
+        speed_value *= (0.95 + attributes.od.powi(2) / 750.0)
+            * ((s.accuracy + relevant_accuracy) / 2.0).powf((14.5 - attributes.od.max(8.0)) / 2.0);
+
+        // many 50s mean the notes were not tapped in time
+        let n50_threshold = s.total_hits / 500.0;
+        if s.n50 >= n50_threshold {
+            speed_value *= 0.99f64.powf(s.n50 - n50_threshold);
+        }
+
+        speed_value
+    }
+
+    fn accuracy_value(&self, s: &Score) -> f64 {
+        if s.mods.contains(Mods::RELAX) {
+            return 0.0;
+        }
+
+        let attributes = self.attributes;
+
+        // only circles are judged on accuracy, unless with Score V2
+        let mut objects_with_accuracy = attributes.n_circles as f64;
+        if s.mods.contains(Mods::SCORE_V2) {
+            objects_with_accuracy += attributes.n_sliders as f64;
+        }
+
+        let better_accuracy = if objects_with_accuracy > 0.0 {
+            (((s.n300 - (s.total_hits - objects_with_accuracy)) * 6.0 + s.n100 * 2.0 + s.n50)
+                / (objects_with_accuracy * 6.0))
+                .max(0.0)
+        } else {
+            0.0
+        };
+
+        let mut accuracy_value = 1.52163f64.powf(attributes.od) * better_accuracy.powi(24) * 2.83;
+        accuracy_value *= (objects_with_accuracy / 1000.0).powf(0.3).min(1.15);
+
+        if s.mods.contains(Mods::HIDDEN) {
+            accuracy_value *= 1.08;
+        }
+        if s.mods.contains(Mods::FLASHLIGHT) {
+            accuracy_value *= 1.02;
+        }
+
+        accuracy_value
+    }
+
+    fn flashlight_value(&self, s: &Score) -> f64 {
+        if !s.mods.contains(Mods::FLASHLIGHT) {
+            return 0.0;
+        }
+
+        let attributes = self.attributes;
+
+        let mut flashlight_value = attributes.flashlight.powi(2) * 25.0;
+
+        if s.effective_miss_count > 0.0 {
+            flashlight_value *= 0.97
+                * (1.0 - (s.effective_miss_count / s.total_hits).powf(0.775))
+                    .powf(s.effective_miss_count.powf(0.875));
+        }
+
+        flashlight_value *= self.combo_scaling_factor(s);
+
+        // longer maps are harder to memorise
+        flashlight_value *= 0.7
+            + 0.1 * (s.total_hits / 200.0).min(1.0)
+            + if s.total_hits > 200.0 {
+                0.2 * ((s.total_hits - 200.0) / 200.0).min(1.0)
+            } else {
+                0.0
+            };
+
+        flashlight_value *= 0.5 + s.accuracy / 2.0;
+        flashlight_value *= 0.98 + attributes.od.powi(2) / 2500.0;
+
+        flashlight_value
+    }
+
+    /// Estimates the number of misses from the combo, since slider breaks are not counted as
+    /// misses.
+    fn effective_miss_count(&self, s: &Score) -> f64 {
+        let attributes = self.attributes;
+
+        let mut combo_based_miss_count = 0.0;
+        if attributes.n_sliders > 0 {
+            let full_combo_threshold =
+                attributes.max_combo as f64 - 0.1 * attributes.n_sliders as f64;
+            if s.max_combo < full_combo_threshold {
+                combo_based_miss_count = full_combo_threshold / s.max_combo.max(1.0);
+            }
+        }
+
+        combo_based_miss_count = combo_based_miss_count.min(s.n100 + s.n50 + s.n_miss);
+
+        s.n_miss.max(combo_based_miss_count)
+    }
+
+    fn combo_scaling_factor(&self, s: &Score) -> f64 {
+        let max_combo = self.attributes.max_combo as f64;
+        if max_combo <= 0.0 {
+            return 1.0;
+        }
+
+        (s.max_combo.powf(0.8) / max_combo.powf(0.8)).min(1.0)
+    }
+}
+
+#[inline]
+fn length_bonus(total_hits: f64) -> f64 {
+    0.95 + 0.4 * (total_hits / 2000.0).min(1.0)
+        + if total_hits > 2000.0 {
+            (total_hits / 2000.0).log10() * 0.5
+        } else {
+            0.0
+        }
+}
@@ -0,0 +1,66 @@
+/// Highest strain of consecutive sections of a beatmap, used by strain-based difficulty skills.
+#[derive(Debug, Clone)]
+pub(crate) struct StrainPeaks {
+    section_length: f64,
+    section_end: Option<f64>,
+    peak: f64,
+    peaks: Vec<f64>,
+}
+
+impl StrainPeaks {
+    #[inline]
+    pub fn new(section_length: f64) -> Self {
+        Self {
+            section_length,
+            section_end: None,
+            peak: 0.0,
+            peaks: Vec::new(),
+        }
+    }
+
+    /// Closes every section ending before `time`, starting each new section with the strain
+    /// `initial` returns for the start of the section.
+    pub fn advance(&mut self, time: f64, initial: impl Fn(f64) -> f64) {
+        let length = self.section_length;
+        let mut end = self
+            .section_end
+            .unwrap_or_else(|| (time / length).ceil() * length);
+
+        while time > end {
+            self.peaks.push(self.peak);
+            self.peak = initial(end);
+            end += length;
+        }
+
+        self.section_end = Some(end);
+    }
+
+    /// Records the strain of an object of the current section.
+    #[inline]
+    pub fn update(&mut self, strain: f64) {
+        self.peak = self.peak.max(strain);
+    }
+
+    /// Returns the peak of every section, including the current one.
+    #[inline]
+    pub fn peaks(&self) -> Vec<f64> {
+        let mut peaks = self.peaks.clone();
+        peaks.push(self.peak);
+        peaks
+    }
+}
+
+/// Sums `values` from the highest to the lowest, each weighted `decay_weight` times less than the
+/// previous one.
+pub(crate) fn weighted_sum(mut values: Vec<f64>, decay_weight: f64) -> f64 {
+    values.sort_by(|a, b| b.total_cmp(a));
+
+    let mut weight = 1.0;
+    let mut sum = 0.0;
+    for value in values {
+        sum += value * weight;
+        weight *= decay_weight;
+    }
+
+    sum
+}
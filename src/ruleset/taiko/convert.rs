@@ -0,0 +1,162 @@
+//! Conversion of osu!standard beatmaps to osu!taiko, following the rules of osu!stable.
+
+use crate::{
+    file::beatmap::{
+        difficulty::Difficulty,
+        hit_objects::{
+            slider::Slider, slider_events::BASE_SCORING_DISTANCE, HitObject, HitObjectKind,
+        },
+        hit_sound::HitSound,
+        timing_point::control_points::ControlPoints,
+        Beatmap,
+    },
+    mode::Mode,
+    time::Time,
+    Error,
+};
+
+use super::object::{merge_simultaneous, TaikoObject};
+
+/// Multiplier osu!stable applies to the slider multiplier of converts, making drumrolls shorter
+/// than the sliders they come from.
+pub const LEGACY_VELOCITY_MULTIPLIER: f64 = 1.4;
+
+/// Converts the objects of an osu!standard `beatmap` to osu!taiko.
+///
+/// Circles become dons, or kats with a whistle or clap, and big notes with a finish. Sliders
+/// short and slow enough to be played as a stream are split into a hit per tick, taking the
+/// hit sounds of the slider's nodes in turn, and become drumrolls otherwise. Spinners become
+/// swells. Objects sharing a start time are merged into a single big one.
+///
+/// This function will return [Err(Error::InvalidInput)][Error::InvalidInput] if `beatmap` is
+/// not an osu!standard beatmap.
+pub fn convert(beatmap: &Beatmap) -> Result<Vec<TaikoObject>, Error> {
+    if beatmap.general.mode != Mode::Standard {
+        return Err(Error::InvalidInput(
+            "only osu!standard beatmaps can be converted",
+        ));
+    }
+
+    Ok(convert_objects(beatmap))
+}
+
+pub(super) fn convert_objects(beatmap: &Beatmap) -> Vec<TaikoObject> {
+    let control_points = beatmap.control_points();
+    let mut objects = Vec::with_capacity(beatmap.hit_objects.len());
+
+    for hit_object in &beatmap.hit_objects {
+        match &hit_object.kind {
+            HitObjectKind::HitCircle | HitObjectKind::Hold(_) => {
+                objects.push(TaikoObject::hit(hit_object.time, hit_object.hit_sound))
+            }
+            HitObjectKind::Slider(slider) => {
+                convert_slider(beatmap, &control_points, hit_object, slider, &mut objects)
+            }
+            HitObjectKind::Spinner(spinner) => objects.push(TaikoObject::swell(
+                hit_object.time,
+                spinner.end_time,
+                beatmap.difficulty.od as f64,
+            )),
+        }
+    }
+
+    merge_simultaneous(&mut objects);
+    objects
+}
+
+/// Converts a slider to a drumroll, or to a stream of hits if it is short and slow.
+///
+/// Like osu!stable, the distance is computed from the declared length of the slider rather than
+/// from its path, the beat length from the legacy BPM multiplier rather than the slider
+/// velocity, and the calculations are kept in the order osu!stable does them, as the floating
+/// point errors decide whether some sliders are split.
+fn convert_slider(
+    beatmap: &Beatmap,
+    control_points: &ControlPoints,
+    hit_object: &HitObject,
+    slider: &Slider,
+    objects: &mut Vec<TaikoObject>,
+) {
+    let time = hit_object.time;
+    let spans = slider.slides.max(1) as f64;
+    let slider_tick_rate = beatmap.difficulty.slider_tick_rate as f64;
+
+    let DrumRollTiming {
+        distance,
+        velocity: taiko_velocity,
+        beat_length,
+        duration,
+    } = DrumRollTiming::new(&beatmap.difficulty, control_points, time, slider);
+    let timing_beat_length = control_points.beat_length_at(time);
+
+    let osu_velocity = taiko_velocity * (1000.0 / beat_length);
+
+    // osu!stable only uses the slider velocity to space the hits of beatmaps before v8
+    let beat_length = if beatmap.file_format_version >= 8 {
+        timing_beat_length
+    } else {
+        beat_length
+    };
+    let tick_spacing = (beat_length / slider_tick_rate).min(duration / spans);
+
+    if tick_spacing > 0.0 && distance / osu_velocity * 1000.0 < 2.0 * beat_length {
+        let start = time.as_millis();
+        let mut hit_sounds = slider.edge_sounds.iter().copied().cycle();
+
+        let mut t = start;
+        while t <= start + duration + tick_spacing / 8.0 {
+            let hit_sound = hit_sounds.next().unwrap_or(hit_object.hit_sound);
+            objects.push(TaikoObject::hit(Time(t), hit_sound));
+            t += tick_spacing;
+        }
+    } else {
+        objects.push(TaikoObject::drum_roll(
+            time,
+            Time(duration),
+            hit_object.hit_sound.contains(HitSound::FINISH),
+            timing_beat_length,
+            slider_tick_rate,
+        ));
+    }
+}
+
+/// Timing of a drumroll made from a slider, computed like osu!stable from the declared length
+/// of the slider and the [legacy beat length][ControlPoints::legacy_beat_length_at] at its
+/// start.
+pub(super) struct DrumRollTiming {
+    /// Distance travelled over every span, in osu! pixels.
+    pub distance: f64,
+    /// Distance travelled in a beat, in osu! pixels.
+    pub velocity: f64,
+    pub beat_length: f64,
+    /// Duration in milliseconds, truncated like osu!stable does.
+    pub duration: f64,
+}
+
+impl DrumRollTiming {
+    pub fn new(
+        difficulty: &Difficulty,
+        control_points: &ControlPoints,
+        time: Time,
+        slider: &Slider,
+    ) -> Self {
+        let spans = slider.slides.max(1) as f64;
+        let distance = slider.length as f64 * LEGACY_VELOCITY_MULTIPLIER * spans;
+
+        let beat_length = control_points.legacy_beat_length_at(time);
+
+        let slider_multiplier = difficulty.slider_multiplier as f64 * LEGACY_VELOCITY_MULTIPLIER;
+        let slider_tick_rate = difficulty.slider_tick_rate as f64;
+        let scoring_point_distance = BASE_SCORING_DISTANCE * slider_multiplier / slider_tick_rate;
+
+        let velocity = scoring_point_distance * slider_tick_rate;
+        let duration = (distance / velocity * beat_length) as i32 as f64;
+
+        Self {
+            distance,
+            velocity,
+            beat_length,
+            duration,
+        }
+    }
+}
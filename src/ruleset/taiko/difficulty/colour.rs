@@ -0,0 +1,357 @@
+use std::f64::consts::E;
+
+use crate::ruleset::{strain::StrainPeaks, taiko::object::TaikoObjectKind};
+
+use super::{object::DifficultyObject, strain_decay, SECTION_LENGTH};
+
+const SKILL_MULTIPLIER: f64 = 0.12;
+const STRAIN_DECAY_BASE: f64 = 0.8;
+
+/// Patterns further apart than this are not considered repetitions of each other.
+const MAX_REPETITION_INTERVAL: usize = 16;
+
+/// Difficulty of changing between dons and kats.
+pub(super) struct Colour {
+    /// Difficulty of each object, from the colour patterns it starts.
+    difficulties: Vec<f64>,
+    strain: f64,
+    pub peaks: StrainPeaks,
+}
+
+impl Colour {
+    pub fn new(objects: &[DifficultyObject]) -> Self {
+        Self {
+            difficulties: evaluate_all(objects),
+            strain: 0.0,
+            peaks: StrainPeaks::new(SECTION_LENGTH),
+        }
+    }
+
+    pub fn process(&mut self, objects: &[DifficultyObject], i: usize) {
+        let current = &objects[i];
+
+        if let Some(previous) = i.checked_sub(1).map(|j| &objects[j]) {
+            let strain = self.strain;
+            self.peaks.advance(current.start_time, |time| {
+                strain * strain_decay(STRAIN_DECAY_BASE, time - previous.start_time)
+            });
+        } else {
+            self.peaks.advance(current.start_time, |_| 0.0);
+        }
+
+        self.strain *= strain_decay(STRAIN_DECAY_BASE, current.delta_time);
+        self.strain += self.difficulties[i] * SKILL_MULTIPLIER;
+
+        self.peaks.update(self.strain);
+    }
+}
+
+/// Notes of the same colour in a row, or a drumroll or swell followed by the notes of the
+/// colour before it.
+struct MonoStreak {
+    objects: Vec<usize>,
+    /// Colour of the first object, [`None`] for drumrolls and swells.
+    kind: Option<TaikoObjectKind>,
+}
+
+/// Mono streaks of the same length in a row, alternating between colours.
+struct AlternatingMonoPattern {
+    streaks: Vec<usize>,
+}
+
+/// Alternating mono patterns repeating every other one.
+struct RepeatingHitPatterns {
+    patterns: Vec<usize>,
+    /// How many repeating hit patterns before this one the same one was last played, above
+    /// [`MAX_REPETITION_INTERVAL`] if it was not.
+    repetition_interval: usize,
+}
+
+/// Encodes the notes into nested colour patterns and returns the difficulty of every object,
+/// given by the patterns starting at it.
+fn evaluate_all(objects: &[DifficultyObject]) -> Vec<f64> {
+    // like osu!, every object is compared with the previous note, so drumrolls and swells start
+    // a streak of their own, which the notes after them continue if they have the same colour
+    // as the notes before
+    let mut streaks: Vec<MonoStreak> = Vec::new();
+    let mut previous_note = None;
+    for object in objects {
+        let kind = object.base.is_hit().then_some(object.base.kind);
+
+        match streaks.last_mut() {
+            Some(streak) if previous_note.is_some() && kind == previous_note => {
+                streak.objects.push(object.index)
+            }
+            _ => streaks.push(MonoStreak {
+                objects: vec![object.index],
+                kind,
+            }),
+        }
+
+        if kind.is_some() {
+            previous_note = kind;
+        }
+    }
+
+    let mut alternating: Vec<AlternatingMonoPattern> = Vec::new();
+    for (i, streak) in streaks.iter().enumerate() {
+        match alternating.last_mut() {
+            Some(pattern) if streaks[i - 1].objects.len() == streak.objects.len() => {
+                pattern.streaks.push(i)
+            }
+            _ => alternating.push(AlternatingMonoPattern { streaks: vec![i] }),
+        }
+    }
+
+    let first_streak = |pattern: usize| &streaks[alternating[pattern].streaks[0]];
+    let identical_mono_length =
+        |a: usize, b: usize| first_streak(a).objects.len() == first_streak(b).objects.len();
+    let is_repetition = |a: usize, b: usize| {
+        identical_mono_length(a, b)
+            && alternating[a].streaks.len() == alternating[b].streaks.len()
+            && first_streak(a).kind == first_streak(b).kind
+    };
+    let is_coupled = |i: usize| i + 2 < alternating.len() && is_repetition(i, i + 2);
+
+    let mut repeating: Vec<RepeatingHitPatterns> = Vec::new();
+    let mut i = 0;
+    while i < alternating.len() {
+        let mut patterns = Vec::new();
+        if is_coupled(i) {
+            while is_coupled(i) {
+                patterns.push(i);
+                i += 1;
+            }
+            patterns.push(i);
+            patterns.push(i + 1);
+            i += 1;
+        } else {
+            patterns.push(i);
+        }
+        i += 1;
+
+        repeating.push(RepeatingHitPatterns {
+            patterns,
+            repetition_interval: MAX_REPETITION_INTERVAL + 1,
+        });
+    }
+
+    for i in 0..repeating.len() {
+        let is_repetition_of = |other: &RepeatingHitPatterns| {
+            let current = &repeating[i].patterns;
+            current.len() == other.patterns.len()
+                && current
+                    .iter()
+                    .zip(&other.patterns)
+                    .take(2)
+                    .all(|(&a, &b)| identical_mono_length(a, b))
+        };
+
+        repeating[i].repetition_interval = (1..MAX_REPETITION_INTERVAL)
+            .take_while(|&interval| interval <= i)
+            .find(|&interval| is_repetition_of(&repeating[i - interval]))
+            .unwrap_or(MAX_REPETITION_INTERVAL + 1);
+    }
+
+    let mut difficulties = vec![0.0; objects.len()];
+    for repeating in &repeating {
+        let repeating_difficulty =
+            2.0 * (1.0 - sigmoid(repeating.repetition_interval as f64, 2.0, 2.0, 0.5, 1.0));
+
+        for (i, &pattern) in repeating.patterns.iter().enumerate() {
+            let pattern_difficulty = sigmoid(i as f64, 2.0, 2.0, 0.5, 1.0) * repeating_difficulty;
+
+            for (j, &streak) in alternating[pattern].streaks.iter().enumerate() {
+                let streak_difficulty =
+                    sigmoid(j as f64, 2.0, 2.0, 0.5, 1.0) * pattern_difficulty * 0.5;
+
+                let first = streaks[streak].objects[0];
+                difficulties[first] += streak_difficulty;
+                if j == 0 {
+                    difficulties[first] += pattern_difficulty;
+                    if i == 0 {
+                        difficulties[first] += repeating_difficulty;
+                    }
+                }
+            }
+        }
+    }
+
+    difficulties
+}
+
+#[inline]
+fn sigmoid(value: f64, center: f64, width: f64, middle: f64, height: f64) -> f64 {
+    (E * -(value - center) / width).tanh() * (height / 2.0) + middle
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        file::beatmap::hit_sound::HitSound, ruleset::taiko::object::TaikoObject, time::Time,
+    };
+
+    use super::*;
+
+    /// Returns the colour difficulty of each object of `pattern`, written with `d` for dons, `k`
+    /// for kats and `r` for drumrolls.
+    fn difficulties(pattern: &str) -> Vec<f64> {
+        // the first two objects have no difficulty object
+        let objects = "dd"
+            .chars()
+            .chain(pattern.chars())
+            .enumerate()
+            .map(|(i, c)| {
+                let time = Time(i as f64 * 100.0);
+                match c {
+                    'r' => TaikoObject::drum_roll(time, Time(50.0), false, 100.0, 1.0),
+                    'k' => TaikoObject::hit(time, HitSound::WHISTLE),
+                    _ => TaikoObject::hit(time, HitSound::empty()),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        evaluate_all(&DifficultyObject::new_all(&objects, 1.0))
+    }
+
+    fn s(value: f64) -> f64 {
+        sigmoid(value, 2.0, 2.0, 0.5, 1.0)
+    }
+
+    /// Difficulty of a repeating hit pattern played `interval` patterns after the same one.
+    fn repeating(interval: usize) -> f64 {
+        2.0 * (1.0 - s(interval as f64))
+    }
+
+    /// Difficulty of the first note of a repeating hit pattern, alternating mono pattern and mono
+    /// streak all starting on it.
+    fn first_of_repeating(interval: usize) -> f64 {
+        let repeating = repeating(interval);
+        s(0.0) * s(0.0) * repeating * 0.5 + s(0.0) * repeating + repeating
+    }
+
+    #[track_caller]
+    fn assert_close(actual: &[f64], expected: &[f64]) {
+        assert_eq!(actual.len(), expected.len());
+        for (a, e) in actual.iter().zip(expected) {
+            assert!(
+                (a - e).abs() < 1e-12,
+                "expected {expected:?}, got {actual:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn single_colour() {
+        let never_repeated = first_of_repeating(MAX_REPETITION_INTERVAL + 1);
+        assert_close(&difficulties("dddd"), &[never_repeated, 0.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn alternating_streaks_of_the_same_length() {
+        // one alternating mono pattern of four streaks
+        let repeating = repeating(MAX_REPETITION_INTERVAL + 1);
+        let pattern = s(0.0) * repeating;
+        let streak = |index: f64| s(index) * pattern * 0.5;
+
+        assert_close(
+            &difficulties("ddkkddkk"),
+            &[
+                streak(0.0) + pattern + repeating,
+                0.0,
+                streak(1.0),
+                0.0,
+                streak(2.0),
+                0.0,
+                streak(3.0),
+                0.0,
+            ],
+        );
+    }
+
+    #[test]
+    fn repetition_interval() {
+        // streaks of 1, 2 and 3 notes, twice: each is its own repeating hit pattern, repeated
+        // three patterns later
+        let never_repeated = first_of_repeating(MAX_REPETITION_INTERVAL + 1);
+        let repeated = first_of_repeating(3);
+
+        let expected = [
+            vec![never_repeated],
+            vec![never_repeated, 0.0],
+            vec![never_repeated, 0.0, 0.0],
+            vec![repeated],
+            vec![repeated, 0.0],
+            vec![repeated, 0.0, 0.0],
+        ]
+        .concat();
+        assert_close(&difficulties("dkkdddkddkkk"), &expected);
+    }
+
+    #[test]
+    fn repetition_interval_is_capped() {
+        // alternating streaks of `between` repeating hit patterns of 1, 3 or 4 notes between two
+        // of 2 notes
+        let difficulty_of_last = |between: usize| {
+            let lengths = std::iter::once(2)
+                .chain([1, 3, 4].into_iter().cycle().take(between))
+                .chain(std::iter::once(2));
+            let pattern = lengths
+                .enumerate()
+                .map(|(i, length)| if i % 2 == 0 { "d" } else { "k" }.repeat(length))
+                .collect::<String>();
+
+            difficulties(&pattern)[pattern.len() - 2]
+        };
+
+        assert!(
+            (difficulty_of_last(14) - first_of_repeating(MAX_REPETITION_INTERVAL - 1)).abs()
+                < 1e-12
+        );
+        assert!(
+            (difficulty_of_last(15) - first_of_repeating(MAX_REPETITION_INTERVAL + 1)).abs()
+                < 1e-12
+        );
+    }
+
+    #[test]
+    fn coupled_patterns() {
+        // single dons between pairs of kats: every other alternating mono pattern is the same,
+        // so they are all part of the same repeating hit pattern
+        let repeating = repeating(MAX_REPETITION_INTERVAL + 1);
+        let pattern = |index: f64| s(index) * repeating;
+        let first = |index: f64| s(0.0) * pattern(index) * 0.5 + pattern(index);
+
+        assert_close(
+            &difficulties("dkkdkkdkk"),
+            &[
+                first(0.0) + repeating,
+                first(1.0),
+                0.0,
+                first(2.0),
+                first(3.0),
+                0.0,
+                first(4.0),
+                first(5.0),
+                0.0,
+            ],
+        );
+    }
+
+    #[test]
+    fn drum_roll_starts_a_streak() {
+        // the drumroll starts a streak of its own, which the don after it continues: streaks of
+        // 1 and 2 objects, which are not repetitions of each other
+        let never_repeated = first_of_repeating(MAX_REPETITION_INTERVAL + 1);
+        assert_close(&difficulties("drd"), &[never_repeated, never_repeated, 0.0]);
+
+        // without it, the dons are a single streak
+        assert_close(&difficulties("dd"), &[never_repeated, 0.0]);
+
+        // streaks of 2 and 3 objects
+        assert_close(
+            &difficulties("ddrdd"),
+            &[never_repeated, 0.0, never_repeated, 0.0, 0.0],
+        );
+    }
+}
@@ -0,0 +1,207 @@
+//! osu!taiko star rating, following the current osu! difficulty algorithm.
+
+mod colour;
+mod object;
+mod rhythm;
+mod stamina;
+
+use colour::Colour;
+use object::DifficultyObject;
+use rhythm::Rhythm;
+use stamina::Stamina;
+
+use crate::{
+    file::beatmap::{difficulty::attributes::HitWindows, Beatmap},
+    mode::Mode,
+    mods::Mods,
+    ruleset::strain::weighted_sum,
+};
+
+use super::object::{is_convert, TaikoObject};
+
+/// Length of the sections strain peaks are taken from, in milliseconds adjusted by the clock rate.
+pub const SECTION_LENGTH: f64 = 400.0;
+
+const DIFFICULTY_MULTIPLIER: f64 = 1.35;
+const DECAY_WEIGHT: f64 = 0.9;
+const FINAL_MULTIPLIER: f64 = 0.0625;
+const RHYTHM_SKILL_MULTIPLIER: f64 = 0.2 * FINAL_MULTIPLIER;
+const COLOUR_SKILL_MULTIPLIER: f64 = 0.375 * FINAL_MULTIPLIER;
+const STAMINA_SKILL_MULTIPLIER: f64 = 0.375 * FINAL_MULTIPLIER;
+
+/// Difficulty of an osu!taiko beatmap played with mods.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TaikoDifficultyAttributes {
+    pub mods: Mods,
+    pub stars: f64,
+    pub stamina: f64,
+    pub rhythm: f64,
+    pub colour: f64,
+    /// Difficulty of the combined strain peaks, before scaling it to the star rating.
+    pub peak: f64,
+    /// Half-width of the GREAT hit window in milliseconds, adjusted by the mods and the clock
+    /// rate.
+    pub great_hit_window: f64,
+    pub max_combo: u32,
+    /// Whether the beatmap is an osu!standard beatmap converted to osu!taiko.
+    pub is_convert: bool,
+}
+
+/// Strain peaks of every [`SECTION_LENGTH`] section of an osu!taiko beatmap, for graphing.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TaikoStrains {
+    pub colour: Vec<f64>,
+    pub rhythm: Vec<f64>,
+    pub stamina: Vec<f64>,
+}
+
+/// Computes the star rating of an osu!taiko beatmap, or of an osu!standard beatmap converted to
+/// osu!taiko.
+#[derive(Debug, Clone)]
+pub struct TaikoDifficultyCalculator<'a> {
+    beatmap: &'a Beatmap,
+    mods: Mods,
+}
+
+impl<'a> TaikoDifficultyCalculator<'a> {
+    #[inline]
+    pub fn new(beatmap: &'a Beatmap) -> Self {
+        Self {
+            beatmap,
+            mods: Mods::empty(),
+        }
+    }
+
+    #[inline]
+    pub fn mods(mut self, mods: Mods) -> Self {
+        self.mods = mods;
+        self
+    }
+
+    /// Computes the difficulty attributes of the beatmap.
+    pub fn calculate(&self) -> TaikoDifficultyAttributes {
+        let objects = TaikoObject::from_beatmap(self.beatmap);
+        let skills = self.skills(&objects);
+
+        let mods = self.mods;
+        let difficulty = self.beatmap.difficulty.apply_mods(Mode::Taiko, mods);
+        let clock_rate = mods.clock_rate();
+        let is_convert = is_convert(self.beatmap);
+
+        let colour_peaks = skills.colour.peaks.peaks();
+        let rhythm_peaks = skills.rhythm.peaks.peaks();
+        let stamina_peaks = skills.stamina.peaks.peaks();
+
+        let colour = weighted_sum(colour_peaks.clone(), DECAY_WEIGHT)
+            * COLOUR_SKILL_MULTIPLIER
+            * DIFFICULTY_MULTIPLIER;
+        let rhythm = weighted_sum(rhythm_peaks.clone(), DECAY_WEIGHT)
+            * RHYTHM_SKILL_MULTIPLIER
+            * DIFFICULTY_MULTIPLIER;
+        let stamina = weighted_sum(stamina_peaks.clone(), DECAY_WEIGHT)
+            * STAMINA_SKILL_MULTIPLIER
+            * DIFFICULTY_MULTIPLIER;
+
+        // colour and stamina are combined before rhythm, so that maps hard in both are rewarded
+        let combined_peaks = colour_peaks
+            .iter()
+            .zip(&rhythm_peaks)
+            .zip(&stamina_peaks)
+            .map(|((colour, rhythm), stamina)| {
+                let peak = norm(
+                    1.5,
+                    colour * COLOUR_SKILL_MULTIPLIER,
+                    stamina * STAMINA_SKILL_MULTIPLIER,
+                );
+                norm(2.0, peak, rhythm * RHYTHM_SKILL_MULTIPLIER)
+            })
+            .filter(|&peak| peak > 0.0)
+            .collect();
+        let peak = weighted_sum(combined_peaks, DECAY_WEIGHT) * DIFFICULTY_MULTIPLIER;
+
+        let mut stars = rescale(peak * 1.4);
+
+        // converts can be played with more than two keys per colour, which is not detected
+        if is_convert {
+            stars *= 0.925;
+            // more so when there are few colour changes and many fast notes
+            if colour < 2.0 && stamina > 8.0 {
+                stars *= 0.8;
+            }
+        }
+
+        let great_hit_window = HitWindows::new(Mode::Taiko, difficulty.od)
+            .map_or(0.0, |windows| windows.great.as_millis())
+            / clock_rate;
+
+        TaikoDifficultyAttributes {
+            mods,
+            stars,
+            stamina,
+            rhythm,
+            colour,
+            peak,
+            great_hit_window,
+            max_combo: objects.iter().filter(|o| o.is_hit()).count() as u32,
+            is_convert,
+        }
+    }
+
+    /// Computes the strain peaks of the beatmap.
+    pub fn strains(&self) -> TaikoStrains {
+        let objects = TaikoObject::from_beatmap(self.beatmap);
+        let skills = self.skills(&objects);
+
+        TaikoStrains {
+            colour: skills.colour.peaks.peaks(),
+            rhythm: skills.rhythm.peaks.peaks(),
+            stamina: skills.stamina.peaks.peaks(),
+        }
+    }
+
+    fn skills(&self, objects: &[TaikoObject]) -> Skills {
+        let difficulty_objects = DifficultyObject::new_all(objects, self.mods.clock_rate());
+
+        let mut skills = Skills {
+            colour: Colour::new(&difficulty_objects),
+            rhythm: Rhythm::new(),
+            stamina: Stamina::new(),
+        };
+
+        for i in 0..difficulty_objects.len() {
+            skills.colour.process(&difficulty_objects, i);
+            skills.rhythm.process(&difficulty_objects, i);
+            skills.stamina.process(&difficulty_objects, i);
+        }
+
+        skills
+    }
+}
+
+struct Skills {
+    colour: Colour,
+    rhythm: Rhythm,
+    stamina: Stamina,
+}
+
+#[inline]
+fn strain_decay(base: f64, ms: f64) -> f64 {
+    base.powf(ms / 1000.0)
+}
+
+#[inline]
+fn norm(p: f64, a: f64, b: f64) -> f64 {
+    (a.powf(p) + b.powf(p)).powf(1.0 / p)
+}
+
+/// Scales the combined difficulty to the star rating.
+#[inline]
+fn rescale(stars: f64) -> f64 {
+    if stars < 0.0 {
+        return stars;
+    }
+
+    10.43 * (stars / 8.0 + 1.0).ln()
+}
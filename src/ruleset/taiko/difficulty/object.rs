@@ -0,0 +1,104 @@
+use crate::ruleset::taiko::object::{TaikoObject, TaikoObjectKind};
+
+/// Ratio between the time since the previous object and the time between the two objects before.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) struct Rhythm {
+    pub ratio: f64,
+    pub difficulty: f64,
+}
+
+impl Rhythm {
+    const fn new(numerator: u32, denominator: u32, difficulty: f64) -> Self {
+        Self {
+            ratio: numerator as f64 / denominator as f64,
+            difficulty,
+        }
+    }
+}
+
+/// Rhythm changes objects are snapped to, with how hard they are to play.
+pub(super) const COMMON_RHYTHMS: [Rhythm; 9] = [
+    Rhythm::new(1, 1, 0.0),
+    Rhythm::new(2, 1, 0.3),
+    Rhythm::new(1, 2, 0.5),
+    Rhythm::new(3, 1, 0.3),
+    Rhythm::new(1, 3, 0.35),
+    // needs a hand switch when alternating
+    Rhythm::new(3, 2, 0.6),
+    Rhythm::new(2, 3, 0.4),
+    Rhythm::new(5, 4, 0.5),
+    Rhythm::new(4, 5, 0.7),
+];
+
+/// An osu!taiko object preprocessed for difficulty calculation, relative to the objects before
+/// it.
+#[derive(Debug, Clone)]
+pub(super) struct DifficultyObject<'a> {
+    pub base: &'a TaikoObject,
+    /// Index among the difficulty objects.
+    pub index: usize,
+    /// Start time adjusted by the clock rate.
+    pub start_time: f64,
+    /// Time since the previous object, adjusted by the clock rate.
+    pub delta_time: f64,
+    /// Index in [`COMMON_RHYTHMS`].
+    pub rhythm: usize,
+    /// Index of the second previous object of the same colour, which is hit with the same key
+    /// when alternating.
+    pub key_previous: Option<usize>,
+}
+
+impl<'a> DifficultyObject<'a> {
+    /// Builds the difficulty objects of `objects`, skipping the first two which have no rhythm.
+    pub fn new_all(objects: &'a [TaikoObject], clock_rate: f64) -> Vec<Self> {
+        let mut dons = Vec::new();
+        let mut kats = Vec::new();
+        let mut difficulty_objects = Vec::with_capacity(objects.len().saturating_sub(2));
+
+        for (i, window) in objects.windows(3).enumerate() {
+            let [last_last, last, base] = window else {
+                unreachable!()
+            };
+
+            let start_time = base.time.as_millis() / clock_rate;
+            let delta_time = (base.time - last.time).as_millis() / clock_rate;
+            let previous_length = (last.time - last_last.time).as_millis() / clock_rate;
+            let ratio = delta_time / previous_length;
+
+            let rhythm = COMMON_RHYTHMS
+                .iter()
+                .enumerate()
+                .min_by(|(_, a), (_, b)| {
+                    (a.ratio - ratio).abs().total_cmp(&(b.ratio - ratio).abs())
+                })
+                .map_or(0, |(j, _)| j);
+
+            let mono = match base.kind {
+                TaikoObjectKind::Don => Some(&mut dons),
+                TaikoObjectKind::Kat => Some(&mut kats),
+                _ => None,
+            };
+            let key_previous = mono.and_then(|mono: &mut Vec<usize>| {
+                let previous = mono.len().checked_sub(2).map(|j| mono[j]);
+                mono.push(i);
+                previous
+            });
+
+            difficulty_objects.push(Self {
+                base,
+                index: i,
+                start_time,
+                delta_time,
+                rhythm,
+                key_previous,
+            });
+        }
+
+        difficulty_objects
+    }
+
+    #[inline]
+    pub fn rhythm(&self) -> &'static Rhythm {
+        &COMMON_RHYTHMS[self.rhythm]
+    }
+}
@@ -0,0 +1,244 @@
+use std::collections::VecDeque;
+
+use crate::ruleset::strain::StrainPeaks;
+
+use super::{object::DifficultyObject, strain_decay, SECTION_LENGTH};
+
+const SKILL_MULTIPLIER: f64 = 10.0;
+/// Rhythm strain does not carry over between objects, it is accumulated by the skill instead.
+const STRAIN_DECAY_BASE: f64 = 0.0;
+
+const HISTORY_MAX_LENGTH: usize = 8;
+const STRAIN_DECAY: f64 = 0.96;
+
+/// Difficulty of following changes of rhythm.
+pub(super) struct Rhythm {
+    history: VecDeque<usize>,
+    rhythm_strain: f64,
+    notes_since_rhythm_change: u32,
+    strain: f64,
+    pub peaks: StrainPeaks,
+}
+
+impl Rhythm {
+    pub fn new() -> Self {
+        Self {
+            history: VecDeque::with_capacity(HISTORY_MAX_LENGTH),
+            rhythm_strain: 0.0,
+            notes_since_rhythm_change: 0,
+            strain: 0.0,
+            peaks: StrainPeaks::new(SECTION_LENGTH),
+        }
+    }
+
+    pub fn process(&mut self, objects: &[DifficultyObject], i: usize) {
+        let current = &objects[i];
+
+        if let Some(previous) = i.checked_sub(1).map(|j| &objects[j]) {
+            let strain = self.strain;
+            self.peaks.advance(current.start_time, |time| {
+                strain * strain_decay(STRAIN_DECAY_BASE, time - previous.start_time)
+            });
+        } else {
+            self.peaks.advance(current.start_time, |_| 0.0);
+        }
+
+        self.strain *= strain_decay(STRAIN_DECAY_BASE, current.delta_time);
+        self.strain += self.evaluate(objects, i) * SKILL_MULTIPLIER;
+
+        self.peaks.update(self.strain);
+    }
+
+    fn evaluate(&mut self, objects: &[DifficultyObject], i: usize) -> f64 {
+        let current = &objects[i];
+
+        // drumrolls and swells are exempt
+        if !current.base.is_hit() {
+            self.reset();
+            return 0.0;
+        }
+
+        self.rhythm_strain *= STRAIN_DECAY;
+        self.notes_since_rhythm_change += 1;
+
+        let difficulty = current.rhythm().difficulty;
+        if difficulty == 0.0 {
+            return 0.0;
+        }
+
+        let mut strain = difficulty;
+        strain *= self.repetition_penalties(objects, i);
+        strain *= pattern_length_penalty(self.notes_since_rhythm_change);
+        strain *= self.speed_penalty(current.delta_time);
+
+        self.notes_since_rhythm_change = 0;
+        self.rhythm_strain += strain;
+
+        self.rhythm_strain
+    }
+
+    /// Penalises rhythm patterns that were played recently.
+    fn repetition_penalties(&mut self, objects: &[DifficultyObject], i: usize) -> f64 {
+        if self.history.len() == HISTORY_MAX_LENGTH {
+            self.history.pop_front();
+        }
+        self.history.push_back(i);
+
+        let history = &self.history;
+        let same_pattern = |start: usize, length: usize| {
+            (0..length).all(|k| {
+                objects[history[start + k]].rhythm
+                    == objects[history[history.len() - length + k]].rhythm
+            })
+        };
+
+        let mut penalty = 1.0;
+        for length in 2..=HISTORY_MAX_LENGTH / 2 {
+            let Some(last_start) = history.len().checked_sub(length + 1) else {
+                continue;
+            };
+
+            if let Some(start) = (0..=last_start)
+                .rev()
+                .find(|&start| same_pattern(start, length))
+            {
+                let notes_since = i - history[start];
+                penalty *= (0.032 * notes_since as f64).min(1.0);
+            }
+        }
+
+        penalty
+    }
+
+    /// Penalises slow rhythm changes, which are easy to read.
+    fn speed_penalty(&mut self, delta_time: f64) -> f64 {
+        if delta_time < 80.0 {
+            1.0
+        } else if delta_time < 210.0 {
+            (1.4 - 0.005 * delta_time).max(0.0)
+        } else {
+            self.reset();
+            0.0
+        }
+    }
+
+    #[inline]
+    fn reset(&mut self) {
+        self.rhythm_strain = 0.0;
+        self.notes_since_rhythm_change = 0;
+    }
+}
+
+/// Penalises rhythm changes too close to or too far from the previous one.
+#[inline]
+fn pattern_length_penalty(pattern_length: u32) -> f64 {
+    let short_pattern_penalty = (0.15 * pattern_length as f64).min(1.0);
+    let long_pattern_penalty = (2.5 - 0.15 * pattern_length as f64).clamp(0.0, 1.0);
+    short_pattern_penalty.min(long_pattern_penalty)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        file::beatmap::hit_sound::HitSound, ruleset::taiko::object::TaikoObject, time::Time,
+    };
+
+    use super::*;
+
+    /// Returns the rhythm difficulty of each difficulty object of `objects`.
+    fn evaluate_all(objects: &[TaikoObject]) -> Vec<f64> {
+        let objects = DifficultyObject::new_all(objects, 1.0);
+        let mut rhythm = Rhythm::new();
+
+        (0..objects.len())
+            .map(|i| rhythm.evaluate(&objects, i))
+            .collect()
+    }
+
+    fn hits(times: &[f64]) -> Vec<TaikoObject> {
+        times
+            .iter()
+            .map(|&t| TaikoObject::hit(Time(t), HitSound::empty()))
+            .collect()
+    }
+
+    #[track_caller]
+    fn assert_close(actual: &[f64], expected: &[f64]) {
+        assert_eq!(actual.len(), expected.len());
+        for (a, e) in actual.iter().zip(expected) {
+            assert!(
+                (a - e).abs() < 1e-12,
+                "expected {expected:?}, got {actual:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn constant_rhythm() {
+        let times = (0..10).map(|i| i as f64 * 100.0).collect::<Vec<_>>();
+        assert!(evaluate_all(&hits(&times)).iter().all(|&d| d == 0.0));
+    }
+
+    #[test]
+    fn repeated_rhythm_changes() {
+        // 1/1, then alternating between 1/2 and 2/1
+        let objects = hits(&[0.0, 100.0, 200.0, 250.0, 350.0, 400.0, 500.0]);
+
+        // difficulty · pattern length penalty · speed penalty, added to the decaying strain
+        let first = 0.5 * 0.3;
+        let second = first * 0.96 + 0.3 * 0.15 * 0.9;
+        let third = second * 0.96 + 0.5 * 0.15;
+        // 1/2 then 2/1 was played 3 notes before
+        let fourth = third * 0.96 + 0.3 * 0.15 * 0.9 * 0.032 * 3.0;
+
+        assert_close(
+            &evaluate_all(&objects),
+            &[0.0, first, second, third, fourth],
+        );
+    }
+
+    #[test]
+    fn history_is_limited() {
+        // 2/1 and 1/2 alternating, of which only the last 8 are remembered
+        let mut times = vec![0.0, 50.0];
+        for _ in 0..12 {
+            let last = *times.last().unwrap();
+            times.extend([last + 100.0, last + 150.0]);
+        }
+        let objects = hits(&times);
+        let objects = DifficultyObject::new_all(&objects, 1.0);
+
+        let mut rhythm = Rhythm::new();
+        for i in 0..objects.len() - 1 {
+            rhythm.evaluate(&objects, i);
+        }
+        assert_eq!(rhythm.history.len(), HISTORY_MAX_LENGTH);
+        assert_eq!(
+            rhythm.history.iter().copied().collect::<Vec<_>>(),
+            (objects.len() - 1 - HISTORY_MAX_LENGTH..objects.len() - 1).collect::<Vec<_>>()
+        );
+
+        // patterns of 2, 3 and 4 rhythms were all played 2 notes before, which started 3, 4 and
+        // 5 notes before
+        let i = objects.len() - 1;
+        assert_close(
+            &[rhythm.repetition_penalties(&objects, i)],
+            &[(0.032 * 3.0) * (0.032 * 4.0) * (0.032 * 5.0)],
+        );
+    }
+
+    #[test]
+    fn resets() {
+        // a slow rhythm change resets the strain
+        let objects = hits(&[0.0, 100.0, 150.0, 400.0, 450.0]);
+        assert_close(&evaluate_all(&objects), &[0.5 * 0.15, 0.0, 0.35 * 0.15]);
+
+        // so do drumrolls
+        let mut objects = hits(&[0.0, 100.0, 150.0, 200.0, 300.0]);
+        objects[3] = TaikoObject::drum_roll(Time(200.0), Time(50.0), false, 500.0, 1.0);
+        assert_close(
+            &evaluate_all(&objects),
+            &[0.5 * 0.15, 0.0, 0.3 * 0.15 * 0.9],
+        );
+    }
+}
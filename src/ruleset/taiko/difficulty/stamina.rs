@@ -0,0 +1,60 @@
+use crate::ruleset::strain::StrainPeaks;
+
+use super::{object::DifficultyObject, strain_decay, SECTION_LENGTH};
+
+const SKILL_MULTIPLIER: f64 = 1.1;
+const STRAIN_DECAY_BASE: f64 = 0.4;
+
+/// Difficulty of hitting fast notes, assuming every note is alternated between two keys of each
+/// colour.
+pub(super) struct Stamina {
+    strain: f64,
+    pub peaks: StrainPeaks,
+}
+
+impl Stamina {
+    pub fn new() -> Self {
+        Self {
+            strain: 0.0,
+            peaks: StrainPeaks::new(SECTION_LENGTH),
+        }
+    }
+
+    pub fn process(&mut self, objects: &[DifficultyObject], i: usize) {
+        let current = &objects[i];
+
+        if let Some(previous) = i.checked_sub(1).map(|j| &objects[j]) {
+            let strain = self.strain;
+            self.peaks.advance(current.start_time, |time| {
+                strain * strain_decay(STRAIN_DECAY_BASE, time - previous.start_time)
+            });
+        } else {
+            self.peaks.advance(current.start_time, |_| 0.0);
+        }
+
+        self.strain *= strain_decay(STRAIN_DECAY_BASE, current.delta_time);
+        self.strain += evaluate(objects, i) * SKILL_MULTIPLIER;
+
+        self.peaks.update(self.strain);
+    }
+}
+
+fn evaluate(objects: &[DifficultyObject], i: usize) -> f64 {
+    let current = &objects[i];
+    if !current.base.is_hit() {
+        return 0.0;
+    }
+
+    let Some(key_previous) = current.key_previous.map(|j| &objects[j]) else {
+        return 0.0;
+    };
+
+    // every object has a base strain, plus a bonus for the speed of the key it is hit with
+    0.5 + speed_bonus(current.start_time - key_previous.start_time)
+}
+
+#[inline]
+fn speed_bonus(interval: f64) -> f64 {
+    // capped at 600 bpm 1/4, so that simultaneous notes do not give an infinite bonus
+    30.0 / interval.max(50.0)
+}
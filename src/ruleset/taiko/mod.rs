@@ -0,0 +1,4 @@
+pub mod convert;
+pub mod difficulty;
+pub mod object;
+pub mod performance;
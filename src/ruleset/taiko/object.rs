@@ -0,0 +1,201 @@
+use crate::{
+    file::beatmap::{
+        difficulty::attributes::difficulty_range, hit_objects::HitObjectKind, hit_sound::HitSound,
+        Beatmap,
+    },
+    mode::Mode,
+    time::Time,
+};
+
+use super::convert::{convert_objects, DrumRollTiming};
+
+/// Multiplier of the required hits of a swell per second of its duration.
+const SWELL_HIT_MULTIPLIER: f64 = 1.65;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TaikoObjectKind {
+    /// Centre hit.
+    Don,
+    /// Rim hit.
+    Kat,
+    DrumRoll {
+        /// Number of ticks, including the one at the start of the drumroll.
+        tick_count: u32,
+    },
+    Swell {
+        /// Number of hits required to complete the swell.
+        required_hits: u32,
+    },
+}
+
+/// An osu!taiko hit object.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TaikoObject {
+    pub time: Time,
+    pub end_time: Time,
+    /// Big notes and drumrolls are hit with both hands; swells are never big.
+    pub big: bool,
+    pub kind: TaikoObjectKind,
+}
+
+impl TaikoObject {
+    /// Builds the objects of an osu!taiko `beatmap`, or of an osu!standard beatmap converted to
+    /// osu!taiko with [`convert`][super::convert::convert].
+    ///
+    /// Circles become dons, or kats with a whistle or clap, and big notes with a finish; sliders
+    /// become drumrolls and spinners become swells. Objects sharing a start time are merged into
+    /// a single big one.
+    pub fn from_beatmap(beatmap: &Beatmap) -> Vec<Self> {
+        if beatmap.general.mode == Mode::Standard {
+            return convert_objects(beatmap);
+        }
+
+        let difficulty = &beatmap.difficulty;
+        let control_points = beatmap.control_points();
+
+        let mut objects = beatmap
+            .hit_objects
+            .iter()
+            .map(|hit_object| {
+                let time = hit_object.time;
+                let big = hit_object.hit_sound.contains(HitSound::FINISH);
+
+                match &hit_object.kind {
+                    HitObjectKind::HitCircle | HitObjectKind::Hold(_) => {
+                        Self::hit(time, hit_object.hit_sound)
+                    }
+                    HitObjectKind::Slider(slider) => {
+                        let timing = DrumRollTiming::new(difficulty, &control_points, time, slider);
+
+                        Self::drum_roll(
+                            time,
+                            Time(timing.duration),
+                            big,
+                            control_points.beat_length_at(time),
+                            difficulty.slider_tick_rate as f64,
+                        )
+                    }
+                    HitObjectKind::Spinner(spinner) => {
+                        Self::swell(time, spinner.end_time, difficulty.od as f64)
+                    }
+                }
+            })
+            .collect();
+
+        merge_simultaneous(&mut objects);
+        objects
+    }
+
+    /// Returns a don or kat at `time`, big with a finish.
+    #[inline]
+    pub fn hit(time: Time, hit_sound: HitSound) -> Self {
+        let kind = if hit_sound.intersects(HitSound::WHISTLE | HitSound::CLAP) {
+            TaikoObjectKind::Kat
+        } else {
+            TaikoObjectKind::Don
+        };
+
+        Self {
+            time,
+            end_time: time,
+            big: hit_sound.contains(HitSound::FINISH),
+            kind,
+        }
+    }
+
+    /// Returns a drumroll lasting `duration`, with ticks every beat divided by the slider tick
+    /// rate, which osu!stable limits to 3 or 4 ticks per beat.
+    #[inline]
+    pub fn drum_roll(
+        time: Time,
+        duration: Time,
+        big: bool,
+        beat_length: f64,
+        slider_tick_rate: f64,
+    ) -> Self {
+        let tick_rate = if slider_tick_rate == 3.0 { 3.0 } else { 4.0 };
+
+        Self {
+            time,
+            end_time: time + duration,
+            big,
+            kind: TaikoObjectKind::DrumRoll {
+                tick_count: tick_count(duration, beat_length / tick_rate),
+            },
+        }
+    }
+
+    /// Returns a swell from `time` to `end_time`, requiring more hits per second the higher `od`
+    /// is.
+    #[inline]
+    pub fn swell(time: Time, end_time: Time, od: f64) -> Self {
+        let hits_per_second = difficulty_range(od, 3.0, 5.0, 7.5) * SWELL_HIT_MULTIPLIER;
+
+        Self {
+            time,
+            end_time,
+            big: false,
+            kind: TaikoObjectKind::Swell {
+                required_hits: ((end_time - time).as_secs() * hits_per_second).max(1.0) as u32,
+            },
+        }
+    }
+
+    /// Returns `true` for dons and kats, the only objects that are judged and give combo.
+    #[inline]
+    pub fn is_hit(&self) -> bool {
+        matches!(self.kind, TaikoObjectKind::Don | TaikoObjectKind::Kat)
+    }
+
+    #[inline]
+    pub fn is_drum_roll(&self) -> bool {
+        matches!(self.kind, TaikoObjectKind::DrumRoll { .. })
+    }
+
+    #[inline]
+    pub fn is_swell(&self) -> bool {
+        matches!(self.kind, TaikoObjectKind::Swell { .. })
+    }
+}
+
+/// Returns `true` if `beatmap` is an osu!standard beatmap played in osu!taiko.
+#[inline]
+pub fn is_convert(beatmap: &Beatmap) -> bool {
+    beatmap.general.mode != Mode::Taiko
+}
+
+/// Sorts the objects by start time, keeping only the first of the objects sharing one and
+/// making it big unless it is a swell.
+pub(super) fn merge_simultaneous(objects: &mut Vec<TaikoObject>) {
+    // the sort is stable, so the first of the objects at the same time is the first in the
+    // beatmap
+    objects.sort_by(|a, b| a.time.total_cmp(&b.time));
+    objects.dedup_by(|object, first| {
+        if object.time != first.time {
+            return false;
+        }
+
+        if !first.is_swell() {
+            first.big = true;
+        }
+        true
+    });
+}
+
+/// Counts the ticks of a drumroll, placed every `tick_spacing` from its start up to its end.
+fn tick_count(duration: Time, tick_spacing: f64) -> u32 {
+    if tick_spacing <= 0.0 {
+        return 0;
+    }
+
+    let mut count = 0;
+    let mut t = 0.0;
+    while t < duration.as_millis() + tick_spacing / 2.0 {
+        count += 1;
+        t += tick_spacing;
+    }
+
+    count
+}
@@ -0,0 +1,159 @@
+//! osu!taiko performance points, following the current osu! performance algorithm.
+
+use crate::{file::beatmap::Beatmap, mods::Mods};
+
+use super::difficulty::{TaikoDifficultyAttributes, TaikoDifficultyCalculator};
+
+/// Judgements of an osu!taiko score; drumrolls and swells are not counted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TaikoScore {
+    pub mods: Mods,
+    /// GREAT judgements.
+    pub n300: u32,
+    /// OK judgements.
+    pub n100: u32,
+    pub n_miss: u32,
+}
+
+impl TaikoScore {
+    /// Returns the number of judged dons and kats.
+    #[inline]
+    pub fn total_hits(&self) -> u32 {
+        self.n300 + self.n100 + self.n_miss
+    }
+
+    /// Returns the accuracy between 0 and 1.
+    #[inline]
+    pub fn accuracy(&self) -> f64 {
+        let total_hits = self.total_hits();
+        if total_hits == 0 {
+            return 0.0;
+        }
+
+        (2 * self.n300 + self.n100) as f64 / (2 * total_hits) as f64
+    }
+
+    /// Computes the difficulty of the beatmap, then the performance of the score.
+    pub fn performance(&self, beatmap: &Beatmap) -> TaikoPerformanceAttributes {
+        let attributes = TaikoDifficultyCalculator::new(beatmap)
+            .mods(self.mods)
+            .calculate();
+
+        TaikoPerformanceCalculator::new(&attributes).calculate(self)
+    }
+}
+
+/// Performance points of an osu!taiko score.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TaikoPerformanceAttributes {
+    pub difficulty: TaikoDifficultyAttributes,
+    pub strain: f64,
+    pub accuracy: f64,
+    /// Misses, weighted more on beatmaps with few objects.
+    pub effective_miss_count: f64,
+    pub pp: f64,
+}
+
+/// Computes the performance of osu!taiko scores from the difficulty of a beatmap.
+#[derive(Debug, Clone)]
+pub struct TaikoPerformanceCalculator<'a> {
+    attributes: &'a TaikoDifficultyAttributes,
+}
+
+impl<'a> TaikoPerformanceCalculator<'a> {
+    /// `attributes` must have been computed with the mods of the scores.
+    #[inline]
+    pub fn new(attributes: &'a TaikoDifficultyAttributes) -> Self {
+        Self { attributes }
+    }
+
+    pub fn calculate(&self, score: &TaikoScore) -> TaikoPerformanceAttributes {
+        let mods = score.mods;
+        let total_hits = score.total_hits() as f64;
+        let total_successful_hits = (score.n300 + score.n100) as f64;
+        let accuracy = score.accuracy();
+
+        // misses on short beatmaps are more punishing
+        let effective_miss_count = if total_successful_hits > 0.0 {
+            (1000.0 / total_successful_hits).max(1.0) * score.n_miss as f64
+        } else {
+            0.0
+        };
+
+        let mut multiplier = 1.13;
+        if mods.contains(Mods::HIDDEN) {
+            multiplier *= 1.075;
+        }
+        if mods.contains(Mods::EASY) {
+            multiplier *= 0.975;
+        }
+
+        let strain = self.strain_value(mods, total_hits, accuracy, effective_miss_count);
+        let accuracy_value = self.accuracy_value(mods, total_hits, accuracy);
+
+        let pp = (strain.powf(1.1) + accuracy_value.powf(1.1)).powf(1.0 / 1.1) * multiplier;
+
+        TaikoPerformanceAttributes {
+            difficulty: self.attributes.clone(),
+            strain,
+            accuracy: accuracy_value,
+            effective_miss_count,
+            pp,
+        }
+    }
+
+    fn strain_value(
+        &self,
+        mods: Mods,
+        total_hits: f64,
+        accuracy: f64,
+        effective_miss_count: f64,
+    ) -> f64 {
+        let mut strain_value =
+            (5.0 * (self.attributes.stars / 0.115).max(1.0) - 4.0).powf(2.25) / 1150.0;
+
+        let length_bonus = 1.0 + 0.1 * (total_hits / 1500.0).min(1.0);
+        strain_value *= length_bonus;
+
+        strain_value *= 0.986f64.powf(effective_miss_count);
+
+        if mods.contains(Mods::EASY) {
+            strain_value *= 0.985;
+        }
+        if mods.contains(Mods::HIDDEN) {
+            strain_value *= 1.025;
+        }
+        if mods.contains(Mods::HARD_ROCK) {
+            strain_value *= 1.05;
+        }
+        if mods.contains(Mods::FLASHLIGHT) {
+            strain_value *= 1.05 * length_bonus;
+        }
+
+        strain_value * accuracy.powi(2)
+    }
+
+    fn accuracy_value(&self, mods: Mods, total_hits: f64, accuracy: f64) -> f64 {
+        let great_hit_window = self.attributes.great_hit_window;
+        if great_hit_window <= 0.0 {
+            return 0.0;
+        }
+
+        let mut accuracy_value = (60.0 / great_hit_window).powf(1.1)
+            * accuracy.powi(8)
+            * self.attributes.stars.powf(0.4)
+            * 27.0;
+
+        let length_bonus = (total_hits / 1500.0).powf(0.3).min(1.15);
+        accuracy_value *= length_bonus;
+
+        // reading with both hidden and flashlight is slightly harder
+        if mods.contains(Mods::HIDDEN | Mods::FLASHLIGHT) {
+            accuracy_value *= (1.075 * length_bonus).max(1.05);
+        }
+
+        accuracy_value
+    }
+}
@@ -0,0 +1,190 @@
+use std::{
+    fmt,
+    ops::{Add, AddAssign, Div, Mul, Neg, Sub, SubAssign},
+    str::FromStr,
+    time::Duration,
+};
+
+use crate::Error;
+
+/// A point in time or a time span, in milliseconds.
+///
+/// Unlike [`Duration`], times can be negative (objects placed before the start of the audio,
+/// negative offsets) and fractional (timing points exported by osu!lazer).
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+pub struct Time(pub f64);
+
+impl Time {
+    pub const ZERO: Self = Self(0.0);
+
+    #[inline]
+    pub const fn from_millis(millis: f64) -> Self {
+        Self(millis)
+    }
+
+    #[inline]
+    pub fn from_secs(secs: f64) -> Self {
+        Self(secs * 1000.0)
+    }
+
+    #[inline]
+    pub const fn as_millis(self) -> f64 {
+        self.0
+    }
+
+    #[inline]
+    pub fn as_secs(self) -> f64 {
+        self.0 / 1000.0
+    }
+
+    /// Converts the time into a [`Duration`], returning [`None`] if it is negative or not finite.
+    #[inline]
+    pub fn to_duration(self) -> Option<Duration> {
+        Duration::try_from_secs_f64(self.as_secs()).ok()
+    }
+
+    /// Converts the time into a [`Duration`], clamping negative times to zero.
+    #[inline]
+    pub fn to_duration_saturating(self) -> Duration {
+        self.to_duration().unwrap_or_default()
+    }
+
+    /// Rounds the time to whole milliseconds, the way osu!stable stores most times.
+    #[inline]
+    pub fn round(self) -> Self {
+        Self(self.0.round())
+    }
+
+    #[inline]
+    pub fn abs(self) -> Self {
+        Self(self.0.abs())
+    }
+
+    #[inline]
+    pub fn min(self, other: Self) -> Self {
+        Self(self.0.min(other.0))
+    }
+
+    #[inline]
+    pub fn max(self, other: Self) -> Self {
+        Self(self.0.max(other.0))
+    }
+
+    /// Total ordering of times, see [`f64::total_cmp`].
+    #[inline]
+    pub fn total_cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+impl From<Duration> for Time {
+    #[inline]
+    fn from(duration: Duration) -> Self {
+        Self::from_secs(duration.as_secs_f64())
+    }
+}
+
+impl TryFrom<Time> for Duration {
+    type Error = Error;
+
+    /// Attempts to convert a [`Time`] into a [`Duration`].
+    ///
+    /// This function will return [`Err(Error::InvalidInput)`][Error::InvalidInput] if the time is
+    /// negative or not finite.
+    #[inline]
+    fn try_from(time: Time) -> Result<Self, Self::Error> {
+        time.to_duration()
+            .ok_or(Error::InvalidInput("time is negative or not finite"))
+    }
+}
+
+impl FromStr for Time {
+    type Err = Error;
+
+    #[inline]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self(s.parse()?))
+    }
+}
+
+impl fmt::Display for Time {
+    /// Formats the time in milliseconds, without a fractional part for whole milliseconds.
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl Add for Time {
+    type Output = Self;
+
+    #[inline]
+    fn add(self, rhs: Self) -> Self {
+        Self(self.0 + rhs.0)
+    }
+}
+
+impl AddAssign for Time {
+    #[inline]
+    fn add_assign(&mut self, rhs: Self) {
+        self.0 += rhs.0;
+    }
+}
+
+impl Sub for Time {
+    type Output = Self;
+
+    #[inline]
+    fn sub(self, rhs: Self) -> Self {
+        Self(self.0 - rhs.0)
+    }
+}
+
+impl SubAssign for Time {
+    #[inline]
+    fn sub_assign(&mut self, rhs: Self) {
+        self.0 -= rhs.0;
+    }
+}
+
+impl Mul<f64> for Time {
+    type Output = Self;
+
+    #[inline]
+    fn mul(self, rhs: f64) -> Self {
+        Self(self.0 * rhs)
+    }
+}
+
+impl Div<f64> for Time {
+    type Output = Self;
+
+    #[inline]
+    fn div(self, rhs: f64) -> Self {
+        Self(self.0 / rhs)
+    }
+}
+
+impl Div for Time {
+    type Output = f64;
+
+    /// Ratio between two times.
+    #[inline]
+    fn div(self, rhs: Self) -> f64 {
+        self.0 / rhs.0
+    }
+}
+
+impl Neg for Time {
+    type Output = Self;
+
+    #[inline]
+    fn neg(self) -> Self {
+        Self(-self.0)
+    }
+}
@@ -0,0 +1,338 @@
+mod common;
+
+use common::BeatmapBuilder;
+use osulib::{
+    file::beatmap::{hit_objects::HitObjectKind, Beatmap, BeatmapDecoder},
+    mods::Mods,
+    ruleset::catch::{
+        convert::convert,
+        object::{catcher_width, CatchObject, CatchObjectKind, PalpableKind},
+    },
+    time::Time,
+};
+
+// The random offsets below were computed with a separate implementation of the xorshift
+// generator of osu!stable, seeded with 1337.
+
+/// Decodes an osu!catch beatmap at CS 5 where sliders travel 100 osu! pixels per 500ms.
+fn decode(hit_objects: &[&str]) -> Beatmap {
+    decode_mode(2, hit_objects)
+}
+
+fn decode_mode(mode: u8, hit_objects: &[&str]) -> Beatmap {
+    BeatmapBuilder::new()
+        .mode(mode)
+        .difficulty("CircleSize", 5)
+        .hit_objects(hit_objects)
+        .build()
+}
+
+#[track_caller]
+fn assert_near(actual: f32, expected: f32) {
+    assert!(
+        (actual - expected).abs() < 1e-3,
+        "expected {expected}, got {actual}"
+    );
+}
+
+#[test]
+fn juice_stream() {
+    let beatmap = decode(&["0,0,1000,2,0,L|250:0,1,250"]);
+    let objects = CatchObject::from_beatmap(&beatmap, Mods::empty());
+    let nested = objects[0].palpable();
+
+    let kinds = nested.iter().map(|o| o.kind).collect::<Vec<_>>();
+    let tiny = |n| vec![PalpableKind::TinyDroplet; n];
+    assert_eq!(
+        kinds,
+        [
+            vec![PalpableKind::Fruit],
+            tiny(7),
+            vec![PalpableKind::Droplet],
+            tiny(7),
+            vec![PalpableKind::Droplet],
+            tiny(3),
+            vec![PalpableKind::Fruit],
+        ]
+        .concat()
+    );
+    assert_eq!(objects[0].end_time, Time(2250.0));
+    assert_eq!(objects[0].max_combo(), 4);
+
+    // 500ms between the ticks are halved until at most 100ms
+    let times = nested.iter().map(|o| o.time.0).collect::<Vec<_>>();
+    let positions = nested.iter().map(|o| o.x).collect::<Vec<_>>();
+    for k in 1..8 {
+        assert_eq!(times[k], 1000.0 + 62.5 * k as f64);
+        assert_near(positions[k], 12.5 * k as f32);
+        assert_eq!(times[8 + k], 1500.0 + 62.5 * k as f64);
+    }
+    assert_eq!((times[8], positions[8]), (1500.0, 100.0));
+    assert_eq!((times[16], positions[16]), (2000.0, 200.0));
+
+    // the last tiny droplets lead to the legacy last tick at 2214, not to the tail
+    assert_eq!(&times[17..20], [2053.5, 2107.0, 2160.5]);
+    for (position, expected) in positions[17..20].iter().zip([210.7, 221.4, 232.1]) {
+        assert_near(*position, expected);
+    }
+    assert_eq!((times[20], positions[20]), (2250.0, 250.0));
+
+    // tiny droplets are offset by up to 20 pixels, but kept in the playfield
+    let offsets = nested
+        .iter()
+        .filter(|o| o.kind == PalpableKind::TinyDroplet)
+        .map(|o| o.x_offset)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        offsets,
+        [
+            -12.5, -10.0, -2.0, 15.0, 17.0, 1.0, 0.0, -7.0, -11.0, -3.0, 14.0, 4.0, -8.0, 5.0,
+            -8.0, 18.0, 17.0
+        ]
+    );
+    assert!(nested
+        .iter()
+        .filter(|o| o.kind != PalpableKind::TinyDroplet)
+        .all(|o| o.x_offset == 0.0));
+}
+
+#[test]
+fn short_juice_stream_has_no_tiny_droplets() {
+    // 80ms or less between two events
+    let beatmap = decode(&["0,0,1000,2,0,L|16:0,1,16"]);
+    let objects = CatchObject::from_beatmap(&beatmap, Mods::empty());
+
+    let kinds = objects[0]
+        .palpable()
+        .iter()
+        .map(|o| o.kind)
+        .collect::<Vec<_>>();
+    assert_eq!(kinds, [PalpableKind::Fruit, PalpableKind::Fruit]);
+    assert_eq!(objects[0].palpable()[1].time, Time(1080.0));
+}
+
+#[test]
+fn banana_shower() {
+    let beatmap = decode(&["256,192,1000,12,0,2000,0:0:0:0:"]);
+    let objects = CatchObject::from_beatmap(&beatmap, Mods::empty());
+    let bananas = objects[0].palpable();
+
+    // 1000ms halved until at most 100ms, from the start to the end inclusive
+    assert_eq!(bananas.len(), 17);
+    for (i, banana) in bananas.iter().enumerate() {
+        assert_eq!(banana.kind, PalpableKind::Banana);
+        assert_eq!(banana.time, Time(1000.0 + 62.5 * i as f64));
+        assert_eq!(banana.x, 0.0);
+    }
+    assert_eq!(objects[0].max_combo(), 0);
+
+    let offsets = bananas.iter().map(|b| b.effective_x()).collect::<Vec<_>>();
+    assert_eq!(
+        offsets,
+        [
+            65.55122, 482.88156, 164.77008, 315.21667, 145.71701, 159.17236, 310.30988, 441.51633,
+            428.4978, 243.26352, 422.69315, 481.3454, 104.397484, 473.5371, 135.23026, 360.97977,
+            123.46462
+        ]
+    );
+}
+
+#[test]
+fn spinner_without_duration_has_no_bananas() {
+    let beatmap = decode(&["256,192,1000,12,0,1000,0:0:0:0:"]);
+    let objects = CatchObject::from_beatmap(&beatmap, Mods::empty());
+
+    assert!(objects[0].palpable().is_empty());
+}
+
+#[test]
+fn hard_rock_offsets() {
+    let hit_objects = [
+        "256,192,1000,1,0,0:0:0:0:",
+        // stacked on the previous fruit: moved left by a random 5 pixels
+        "256,192,1100,1,0,0:0:0:0:",
+        // 44 pixels from the last unstacked fruit in 200ms: moved 44 pixels further
+        "300,192,1200,1,0,0:0:0:0:",
+        // too far to be moved
+        "100,192,1300,1,0,0:0:0:0:",
+        // more than a second later
+        "100,192,2500,1,0,0:0:0:0:",
+        "0,192,2600,1,0,0:0:0:0:",
+        // osu!stable takes the fruit at 0 for the first one, so this one is not moved
+        "20,192,2700,1,0,0:0:0:0:",
+        "30,192,2800,1,0,0:0:0:0:",
+    ];
+    let beatmap = decode(&hit_objects);
+
+    let offsets = |mods| {
+        CatchObject::from_beatmap(&beatmap, mods)
+            .iter()
+            .map(|o| o.palpable()[0].x_offset)
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        offsets(Mods::HARD_ROCK),
+        [0.0, -5.0, 44.0, 0.0, 0.0, 0.0, 0.0, 10.0]
+    );
+    assert_eq!(offsets(Mods::empty()), [0.0; 8]);
+}
+
+#[test]
+fn hyper_dashes() {
+    // at CS 5, osu!stable uses half of the full catcher width of 106.75 pixels
+    assert_eq!(catcher_width(5.0), 106.75 * 0.8);
+
+    let beatmap = decode(&[
+        // 512 pixels in 200ms
+        "0,192,1000,1,0,0:0:0:0:",
+        "512,192,1200,1,0,0:0:0:0:",
+        // then back 112 pixels in 500ms
+        "400,192,1700,1,0,0:0:0:0:",
+        // 100 pixels in 100ms, then in 50ms in the same direction: only the second one needs a
+        // hyperdash, since the catcher is already past the first fruit
+        "300,192,1800,1,0,0:0:0:0:",
+        "200,192,1850,1,0,0:0:0:0:",
+    ]);
+    let objects = CatchObject::from_beatmap(&beatmap, Mods::empty());
+    let fruits = objects.iter().map(|o| o.palpable()[0]).collect::<Vec<_>>();
+
+    let half_catcher_width = 106.75 / 2.0;
+    let quarter_frame = 1000.0 / 60.0 / 4.0;
+
+    assert!(fruits[0].hyper_dash);
+    assert_eq!(fruits[0].distance_to_hyper_dash, 0.0);
+
+    // the direction changes, so the excess of the previous fruit is not used
+    assert!(!fruits[1].hyper_dash);
+    let excess = 500.0 - quarter_frame - (112.0 - half_catcher_width);
+    assert_near(fruits[1].distance_to_hyper_dash, excess as f32);
+
+    // an excess of more than half the catcher is capped
+    assert!(!fruits[2].hyper_dash);
+    let excess = 100.0 - quarter_frame - (100.0 - half_catcher_width);
+    assert_near(fruits[2].distance_to_hyper_dash, excess as f32);
+
+    assert!(fruits[3].hyper_dash);
+    assert!(!fruits[4].hyper_dash);
+}
+
+#[test]
+fn hyper_dashes_depend_on_circle_size() {
+    let hit_objects = ["0,192,1000,1,0,0:0:0:0:", "295,192,1250,1,0,0:0:0:0:"];
+    let beatmap = decode(&hit_objects);
+
+    // 295 pixels in 250ms, less half the catcher width: 53.4 pixels at CS 5, 42.2 at CS 6.5 with HR
+    let objects = CatchObject::from_beatmap(&beatmap, Mods::empty());
+    assert!(!objects[0].palpable()[0].hyper_dash);
+    let objects = CatchObject::from_beatmap(&beatmap, Mods::HARD_ROCK);
+    assert!(objects[0].palpable()[0].hyper_dash);
+}
+
+#[test]
+fn convert_standard_beatmap() {
+    let beatmap = decode_mode(
+        0,
+        &[
+            "100,192,1000,1,0,0:0:0:0:",
+            "0,0,2000,2,0,L|250:0,1,250",
+            "256,192,4000,12,0,4500,0:0:0:0:",
+        ],
+    );
+    let objects = convert(&beatmap, Mods::empty()).unwrap();
+    assert_eq!(objects.len(), 3);
+
+    let CatchObjectKind::Fruit(fruit) = &objects[0].kind else {
+        panic!("expected a fruit");
+    };
+    assert_eq!((fruit.time, fruit.x), (Time(1000.0), 100.0));
+
+    // the same juice stream as above, a second later
+    let CatchObjectKind::JuiceStream { nested, .. } = &objects[1].kind else {
+        panic!("expected a juice stream");
+    };
+    let count = |kind| nested.iter().filter(|o| o.kind == kind).count();
+    assert_eq!(count(PalpableKind::Fruit), 2);
+    assert_eq!(count(PalpableKind::Droplet), 2);
+    assert_eq!(count(PalpableKind::TinyDroplet), 17);
+    assert_eq!(
+        nested.iter().map(|o| o.time.0).collect::<Vec<_>>()[..9],
+        [2000.0, 2062.5, 2125.0, 2187.5, 2250.0, 2312.5, 2375.0, 2437.5, 2500.0]
+    );
+    assert_eq!(objects[1].end_time, Time(3250.0));
+
+    // the random generator carries on from the tiny droplets
+    let CatchObjectKind::BananaShower { bananas } = &objects[2].kind else {
+        panic!("expected a banana shower");
+    };
+    assert_eq!(
+        bananas.iter().map(|b| b.time.0).collect::<Vec<_>>(),
+        [4000.0, 4062.5, 4125.0, 4187.5, 4250.0, 4312.5, 4375.0, 4437.5, 4500.0]
+    );
+    let offsets = [
+        433.8482, 201.61826, 244.1033, 55.052025, 166.91572, 332.67188, 460.33447, 329.27838,
+        156.6308,
+    ];
+    for (banana, offset) in bananas.iter().zip(offsets) {
+        assert_near(banana.x_offset, offset);
+    }
+
+    let max_combo = objects.iter().map(CatchObject::max_combo).sum::<u32>();
+    assert_eq!(max_combo, 5);
+}
+
+#[test]
+fn convert_only_standard_beatmaps() {
+    let beatmap = decode(&["100,192,1000,1,0,0:0:0:0:"]);
+    assert!(convert(&beatmap, Mods::empty()).is_err());
+}
+
+#[test]
+fn convert_bench_beatmap() {
+    let beatmap = BeatmapDecoder::all()
+        .parse(
+            Beatmap::default(),
+            &include_bytes!("../benches/beatmap.osu")[..],
+        )
+        .unwrap();
+    let objects = convert(&beatmap, Mods::empty()).unwrap();
+    assert_eq!(objects.len(), beatmap.hit_objects.len());
+
+    let count = |kind| {
+        objects
+            .iter()
+            .flat_map(CatchObject::palpable)
+            .filter(|o| o.kind == kind)
+            .count()
+    };
+    // a fruit per circle, and on the head, repeats and tail of every slider
+    let fruits = beatmap
+        .hit_objects
+        .iter()
+        .map(|h| match &h.kind {
+            HitObjectKind::HitCircle => 1,
+            HitObjectKind::Slider(slider) => slider.slides as usize + 1,
+            _ => 0,
+        })
+        .sum::<usize>();
+    assert_eq!(count(PalpableKind::Fruit), fruits);
+
+    // a droplet on every slider tick
+    let control_points = beatmap.control_points();
+    let ticks = beatmap
+        .hit_objects
+        .iter()
+        .filter_map(|h| {
+            h.slider_events(
+                &beatmap.difficulty,
+                &control_points,
+                beatmap.file_format_version,
+            )
+        })
+        .map(|events| events.ticks().count())
+        .sum::<usize>();
+    assert_eq!(count(PalpableKind::Droplet), ticks);
+
+    let max_combo = objects.iter().map(CatchObject::max_combo).sum::<u32>();
+    assert_eq!(max_combo as usize, fruits + ticks);
+}
@@ -0,0 +1,95 @@
+//! Beatmaps written out line by line for the tests.
+
+#![allow(dead_code)]
+
+use std::fmt::Display;
+
+use osulib::file::beatmap::{Beatmap, BeatmapDecoder};
+
+/// Builds a `.osu` file, by default a v14 osu!standard beatmap at 120 BPM with a slider
+/// multiplier and tick rate of 1, so that sliders travel 100 osu! pixels per 500ms.
+#[derive(Debug, Clone)]
+pub struct BeatmapBuilder {
+    version: u8,
+    general: Vec<String>,
+    difficulty: Vec<String>,
+    timing_points: Vec<String>,
+    hit_objects: Vec<String>,
+}
+
+impl Default for BeatmapBuilder {
+    fn default() -> Self {
+        Self {
+            version: 14,
+            general: Vec::new(),
+            difficulty: vec!["SliderMultiplier:1".into(), "SliderTickRate:1".into()],
+            timing_points: vec!["0,500,4,1,0,100,1,0".into()],
+            hit_objects: Vec::new(),
+        }
+    }
+}
+
+impl BeatmapBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn version(mut self, version: u8) -> Self {
+        self.version = version;
+        self
+    }
+
+    pub fn mode(self, mode: u8) -> Self {
+        self.general("Mode", mode)
+    }
+
+    pub fn general(mut self, key: &str, value: impl Display) -> Self {
+        self.general.push(format!("{key}: {value}"));
+        self
+    }
+
+    /// Sets a `[Difficulty]` value, overriding the previous one with the same key.
+    pub fn difficulty(mut self, key: &str, value: impl Display) -> Self {
+        self.difficulty
+            .retain(|line| line.split(':').next() != Some(key));
+        self.difficulty.push(format!("{key}:{value}"));
+        self
+    }
+
+    /// Replaces every timing point, including the default one.
+    pub fn timing_points(mut self, timing_points: &[&str]) -> Self {
+        self.timing_points = timing_points.iter().map(|t| t.to_string()).collect();
+        self
+    }
+
+    /// Adds a timing point after the others.
+    pub fn timing_point(mut self, timing_point: &str) -> Self {
+        self.timing_points.push(timing_point.into());
+        self
+    }
+
+    pub fn hit_objects(mut self, hit_objects: &[impl AsRef<str>]) -> Self {
+        self.hit_objects
+            .extend(hit_objects.iter().map(|h| h.as_ref().to_string()));
+        self
+    }
+
+    /// Returns the text of the `.osu` file.
+    pub fn data(&self) -> String {
+        format!(
+            "osu file format v{}\n\n[General]\n{}\n\n[Difficulty]\n{}\n\n\
+             [TimingPoints]\n{}\n\n[HitObjects]\n{}\n",
+            self.version,
+            self.general.join("\n"),
+            self.difficulty.join("\n"),
+            self.timing_points.join("\n"),
+            self.hit_objects.join("\n"),
+        )
+    }
+
+    pub fn build(&self) -> Beatmap {
+        BeatmapDecoder::all()
+            .parse(Beatmap::default(), self.data().as_bytes())
+            .unwrap()
+    }
+}
@@ -0,0 +1,109 @@
+mod common;
+
+use common::BeatmapBuilder;
+use osulib::{
+    file::beatmap::{timing_point::TimingPoint, Beatmap},
+    time::Time,
+};
+
+fn decode(timing_points: &[&str], hit_objects: &[&str]) -> Beatmap {
+    BeatmapBuilder::new()
+        .timing_points(timing_points)
+        .hit_objects(hit_objects)
+        .build()
+}
+
+/// Slider velocity of a single inherited timing point with the given beat length.
+fn slider_velocity(beat_length: &str) -> f64 {
+    let inherited = format!("1000,{beat_length},4,1,0,100,0,0");
+    BeatmapBuilder::new()
+        .timing_point(&inherited)
+        .build()
+        .control_points()
+        .slider_velocity_at(Time(1000.0))
+}
+
+#[test]
+fn slider_velocity_is_clamped() {
+    assert_eq!(slider_velocity("-100"), 1.0);
+    assert_eq!(slider_velocity("-50"), 2.0);
+    assert_eq!(slider_velocity("-1"), 10.0);
+    assert_eq!(slider_velocity("-10000"), 0.1);
+}
+
+#[test]
+fn slider_velocity_of_invalid_beat_lengths() {
+    // osu! only reads a velocity from negative beat lengths
+    for beat_length in ["0", "-0", "100", "NaN", "inf", "-inf"] {
+        assert_eq!(slider_velocity(beat_length), 1.0, "{beat_length}");
+    }
+}
+
+#[test]
+fn legacy_bpm_multiplier() {
+    let bpm_multiplier = |beat_length: &str| {
+        let inherited = format!("1000,{beat_length},4,1,0,100,0,0");
+        BeatmapBuilder::new()
+            .timing_point(&inherited)
+            .build()
+            .control_points()
+            .legacy_bpm_multiplier_at(Time(1000.0))
+    };
+
+    // the inverse of the slider velocity, but clamped between 0.1 and 100 in single precision
+    assert_eq!(bpm_multiplier("-50"), 0.5);
+    assert_eq!(bpm_multiplier("-2000"), 20.0);
+    assert_eq!(bpm_multiplier("-20000"), 100.0);
+    assert_eq!(bpm_multiplier("-1"), 0.1f32 as f64);
+    for beat_length in ["0", "100", "NaN"] {
+        assert_eq!(bpm_multiplier(beat_length), 1.0, "{beat_length}");
+    }
+
+    let beatmap = BeatmapBuilder::new()
+        .timing_point("1000,-2000,4,1,0,100,0,0")
+        .build();
+    let control_points = beatmap.control_points();
+    assert_eq!(control_points.slider_velocity_at(Time(1000.0)), 0.1);
+    assert_eq!(control_points.legacy_beat_length_at(Time(1000.0)), 10000.0);
+    assert_eq!(control_points.legacy_beat_length_at(Time(0.0)), 500.0);
+}
+
+#[test]
+fn slider_velocity_resets_on_uninherited() {
+    let beatmap = decode(
+        &[
+            "0,500,4,1,0,100,1,0",
+            "1000,-50,4,1,0,100,0,0",
+            "2000,400,4,1,0,100,1,0",
+        ],
+        &[],
+    );
+    let control_points = beatmap.control_points();
+
+    assert_eq!(control_points.slider_velocity_at(Time(-500.0)), 1.0);
+    assert_eq!(control_points.slider_velocity_at(Time(1500.0)), 2.0);
+    assert_eq!(control_points.slider_velocity_at(Time(2000.0)), 1.0);
+    assert_eq!(control_points.beat_length_at(Time(-500.0)), 500.0);
+    assert_eq!(control_points.beat_length_at(Time(2500.0)), 400.0);
+}
+
+#[test]
+fn slider_duration_with_nan_beat_length() {
+    let beatmap = decode(
+        &["0,500,4,1,0,100,1,0", "0,NaN,4,1,0,100,0,0"],
+        &["0,0,1000,2,0,L|100:0,1,100"],
+    );
+    assert!(matches!(
+        beatmap.timing_points[1],
+        TimingPoint::Inherited(_)
+    ));
+
+    let events = beatmap.hit_objects[0]
+        .slider_events(
+            &beatmap.difficulty,
+            &beatmap.control_points(),
+            beatmap.file_format_version,
+        )
+        .unwrap();
+    assert_eq!(events.span_duration, Time(500.0));
+}
@@ -0,0 +1,83 @@
+use osulib::{
+    file::beatmap::{document::Document, Beatmap, BeatmapDecoder},
+    mode::Mode,
+    Error,
+};
+
+fn write(document: &Document) -> Vec<u8> {
+    let mut data = Vec::new();
+    document.write_to(&mut data).expect("writing failed");
+    data
+}
+
+#[test]
+fn round_trip_is_byte_identical() {
+    let data = include_bytes!("../benches/beatmap.osu");
+    let document = Document::parse(&data[..]).unwrap();
+
+    assert_eq!(write(&document), data);
+    assert_eq!(document.to_string().as_bytes(), data);
+}
+
+#[test]
+fn round_trip_keeps_layout() {
+    // byte order mark, CRLF and LF mixed, unknown keys and sections, odd separators, comments,
+    // trailing whitespace and no newline at the end
+    let data = b"\xef\xbb\xbfosu file format v14\r\n\r\n[General]\r\nAudioFilename:audio.mp3\r\n\
+        UnknownKey :  42\n// comment\r\nMode: 0  \r\n\r\n[Unknown]\nfoo=bar\n\n[HitObjects]\n\
+        256,192,1000,1,0,0:0:0:0:";
+    let mut document = Document::parse(&data[..]).unwrap();
+
+    assert_eq!(write(&document), data);
+
+    let general = document.section_mut("General").unwrap();
+    assert_eq!(general.get("UnknownKey"), Some("42"));
+    general.set("UnknownKey", 7);
+    general.set("AudioLeadIn", 100);
+
+    let expected =
+        b"\xef\xbb\xbfosu file format v14\r\n\r\n[General]\r\nAudioFilename:audio.mp3\r\n\
+        UnknownKey :  7\n// comment\r\nMode: 0  \r\nAudioLeadIn: 100\r\n\r\n[Unknown]\nfoo=bar\n\n\
+        [HitObjects]\n256,192,1000,1,0,0:0:0:0:";
+    assert_eq!(
+        String::from_utf8(write(&document)).unwrap(),
+        String::from_utf8(expected.to_vec()).unwrap()
+    );
+}
+
+#[test]
+fn decodes_like_the_stream_decoder() {
+    let data = include_bytes!("../benches/beatmap.osu");
+    let document = Document::parse(&data[..]).unwrap();
+
+    let from_document = BeatmapDecoder::all()
+        .parse_document(Beatmap::default(), &document)
+        .unwrap();
+    let from_stream = BeatmapDecoder::all()
+        .parse(Beatmap::default(), &data[..])
+        .unwrap();
+
+    assert_eq!(format!("{from_document:?}"), format!("{from_stream:?}"));
+
+    // section headers followed by whitespace
+    let data = "osu file format v14\n\n[General]  \nMode: 1\n\n\t[Metadata]\r\nTitle:Title\n";
+    let document = Document::parse(data.as_bytes()).unwrap();
+    let from_document = BeatmapDecoder::all()
+        .parse_document(Beatmap::default(), &document)
+        .unwrap();
+    let from_stream = BeatmapDecoder::all()
+        .parse(Beatmap::default(), data.as_bytes())
+        .unwrap();
+
+    assert_eq!(from_stream.general.mode, Mode::Taiko);
+    assert_eq!(from_stream.metadata.title, "Title");
+    assert_eq!(format!("{from_document:?}"), format!("{from_stream:?}"));
+}
+
+#[test]
+fn rejects_non_utf8() {
+    // "Title:" followed by a Shift-JIS encoded title
+    let data = b"osu file format v14\n\n[Metadata]\nTitle:\x83\x65\x83\x58\x83\x67\n";
+
+    assert!(matches!(Document::parse(&data[..]), Err(Error::Io(_))));
+}
@@ -0,0 +1,62 @@
+use osulib::file::beatmap::{general::countdown::Countdown, Beatmap, BeatmapDecoder};
+
+fn decode(data: &[u8]) -> Beatmap {
+    BeatmapDecoder::all()
+        .parse(Beatmap::default(), data)
+        .expect("decoding failed")
+}
+
+fn encode(beatmap: &Beatmap) -> String {
+    let mut data = Vec::new();
+    beatmap.write_to(&mut data).expect("encoding failed");
+    String::from_utf8(data).expect("encoded beatmap is not utf-8")
+}
+
+/// Decodes `data`, encodes it and decodes the result again, checking that nothing was lost and
+/// that encoding is stable.
+fn round_trip(data: &[u8]) -> (Beatmap, String) {
+    let beatmap = decode(data);
+    let encoded = encode(&beatmap);
+    let decoded = decode(encoded.as_bytes());
+
+    assert_eq!(format!("{beatmap:?}"), format!("{decoded:?}"));
+    assert_eq!(encoded, encode(&decoded));
+
+    (beatmap, encoded)
+}
+
+#[test]
+fn round_trip_v14() {
+    let (beatmap, encoded) = round_trip(include_bytes!("../benches/beatmap.osu"));
+
+    assert_eq!(beatmap.file_format_version, 14);
+    assert_eq!(beatmap.general.countdown, None);
+    assert!(encoded.contains("Countdown: 0\n"));
+    assert_eq!(beatmap.hit_objects.len(), 2345);
+}
+
+#[test]
+fn round_trip_keeps_missing_keys_meaning() {
+    let (beatmap, encoded) = round_trip(include_bytes!("fixtures/legacy.osu"));
+
+    // osu! shows a normal countdown when the key is missing
+    assert_eq!(beatmap.general.countdown, Some(Countdown::Normal));
+    assert!(encoded.contains("Countdown: 1\n"));
+
+    // only osu!lazer writes the key, other maps never match the playback rate
+    assert!(!beatmap.general.samples_match_playback_rate);
+    assert!(!encoded.contains("SamplesMatchPlaybackRate"));
+
+    // sliders without edge sounds keep using the hit object's sound for every edge
+    assert!(encoded.ends_with("\n100,100,8000,2,2,L|200:100,1,100\n"));
+}
+
+#[test]
+fn round_trip_samples_match_playback_rate() {
+    let mut beatmap = decode(include_bytes!("fixtures/legacy.osu"));
+    beatmap.general.samples_match_playback_rate = true;
+
+    let (decoded, encoded) = round_trip(encode(&beatmap).as_bytes());
+    assert!(decoded.general.samples_match_playback_rate);
+    assert!(encoded.contains("SamplesMatchPlaybackRate: 1\n"));
+}
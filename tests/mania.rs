@@ -0,0 +1,120 @@
+mod common;
+
+use common::BeatmapBuilder;
+use osulib::{
+    file::beatmap::Beatmap,
+    mods::Mods,
+    ruleset::mania::{convert::convert, difficulty::ManiaDifficultyCalculator},
+    time::Time,
+};
+
+/// Decodes a 4K osu!mania beatmap, or an osu!standard beatmap, with the given OD.
+fn decode(mode: u8, od: f32) -> Beatmap {
+    BeatmapBuilder::new()
+        .mode(mode)
+        .difficulty("CircleSize", 4)
+        .difficulty("OverallDifficulty", od)
+        .hit_objects(&["64,192,1000,1,0,0:0:0:0:", "192,192,1250,1,0,0:0:0:0:"])
+        .build()
+}
+
+fn great_hit_window(beatmap: &Beatmap, mods: Mods) -> f64 {
+    ManiaDifficultyCalculator::new(beatmap)
+        .mods(mods)
+        .calculate()
+        .great_hit_window
+}
+
+#[test]
+fn great_hit_window_with_mods() {
+    let beatmap = decode(3, 10.0);
+
+    assert_eq!(great_hit_window(&beatmap, Mods::empty()), 34.0);
+    // 34 · 1.5 = 51, then ⌈⌊51 · 1.5⌋ / 1.5⌉
+    assert_eq!(great_hit_window(&beatmap, Mods::DOUBLE_TIME), 51.0);
+    assert_eq!(
+        great_hit_window(&beatmap, Mods::DOUBLE_TIME | Mods::NIGHTCORE),
+        51.0
+    );
+    // 34 · 0.75 = 25.5, then ⌈⌊25.5 · 0.75⌋ / 0.75⌉ = ⌈19 / 0.75⌉
+    assert_eq!(great_hit_window(&beatmap, Mods::HALF_TIME), 26.0);
+    // 34 / 1.4 · 1.5 ≈ 36.43, then ⌈⌊54.64⌋ / 1.5⌉
+    assert_eq!(
+        great_hit_window(&beatmap, Mods::HARD_ROCK | Mods::DOUBLE_TIME),
+        36.0
+    );
+    // 34 · 1.4 · 0.75 = 35.7, then ⌈⌊26.775⌋ / 0.75⌉
+    assert_eq!(
+        great_hit_window(&beatmap, Mods::EASY | Mods::HALF_TIME),
+        35.0
+    );
+}
+
+#[test]
+fn great_hit_window_of_od() {
+    assert_eq!(great_hit_window(&decode(3, 5.0), Mods::empty()), 49.0);
+    assert_eq!(great_hit_window(&decode(3, 0.0), Mods::empty()), 64.0);
+    // 49 · 1.5 = 73.5, then ⌈⌊110.25⌋ / 1.5⌉
+    assert_eq!(great_hit_window(&decode(3, 5.0), Mods::DOUBLE_TIME), 74.0);
+
+    // converts use a fixed window
+    assert_eq!(great_hit_window(&decode(0, 9.0), Mods::empty()), 34.0);
+    assert_eq!(great_hit_window(&decode(0, 4.0), Mods::empty()), 47.0);
+    // OD 4.5 rounds to 4, ties going to the even number
+    assert_eq!(great_hit_window(&decode(0, 4.5), Mods::empty()), 47.0);
+    assert_eq!(great_hit_window(&decode(0, 5.5), Mods::empty()), 34.0);
+    assert_eq!(great_hit_window(&decode(0, 4.0), Mods::DOUBLE_TIME), 70.0);
+}
+
+/// Converts a stream of 16 circles 90ms apart, moving right, and returns their columns.
+fn stream_columns(mods: Mods) -> Vec<u32> {
+    let hit_objects = (0..16)
+        .map(|i| format!("{},192,{},1,0,0:0:0:0:", 100 + i * 20, 1000 + i * 90))
+        .collect::<Vec<_>>();
+    let beatmap = BeatmapBuilder::new()
+        .difficulty("HPDrainRate", 5)
+        .difficulty("CircleSize", 4)
+        .difficulty("OverallDifficulty", 5)
+        .difficulty("ApproachRate", 5)
+        .hit_objects(&hit_objects)
+        .build();
+
+    convert(&beatmap, mods)
+        .unwrap()
+        .iter()
+        .map(|o| o.column)
+        .collect()
+}
+
+#[test]
+fn stairs_turn_back_at_the_edges() {
+    assert_eq!(
+        stream_columns(Mods::KEY4),
+        [0, 1, 2, 3, 2, 1, 0, 1, 2, 3, 2, 1, 0, 1, 2, 3]
+    );
+
+    // the special column of 7K+1 is not part of the stairs
+    assert_eq!(
+        stream_columns(Mods::KEY8),
+        [2, 3, 4, 5, 6, 7, 6, 5, 4, 3, 2, 1, 2, 3, 4, 5]
+    );
+}
+
+#[test]
+fn slider_uses_the_legacy_bpm_multiplier() {
+    // osu!stable does not clamp a beat length of -2000 to 0.1x: 100 pixels at 100 pixels per
+    // beat of 500 · 20ms
+    let beatmap = BeatmapBuilder::new()
+        .difficulty("CircleSize", 4)
+        .timing_point("0,-2000,4,1,0,100,0,0")
+        .hit_objects(&["0,192,1000,2,0,L|100:192,1,100"])
+        .build();
+    let objects = convert(&beatmap, Mods::empty()).unwrap();
+
+    assert_eq!(objects.len(), 1);
+    assert!(objects[0].is_hold());
+    assert_eq!(
+        (objects[0].time, objects[0].end_time),
+        (Time(1000.0), Time(11000.0))
+    );
+}
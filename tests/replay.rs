@@ -0,0 +1,185 @@
+use osulib::{
+    file::replay::{
+        frame::{Keys, ReplayFrame},
+        life_bar::LifeBarPoint,
+        Judgements, Replay, ReplayDecoder,
+    },
+    mode::Mode,
+    mods::Mods,
+    time::Time,
+};
+
+const FIXTURE: &[u8] = include_bytes!("fixtures/replay.osr");
+
+fn decode(decoder: ReplayDecoder, data: &[u8]) -> Replay {
+    decoder.parse(Replay::default(), data).unwrap()
+}
+
+/// Writes a string the way osu! does, for strings shorter than 128 bytes.
+fn string(bytes: &[u8]) -> Vec<u8> {
+    [&[0x0b, bytes.len() as u8], bytes].concat()
+}
+
+/// Writes an osu!standard replay without life bar or frames, up to its online score ID.
+fn replay(game_version: i32, player_name: &[u8], mods: Mods) -> Vec<u8> {
+    let mut data = vec![0];
+    data.extend(game_version.to_le_bytes());
+    data.extend(string(b"a5b99395a42bd55bc5eb1d2411cbdf8b"));
+    data.extend(string(player_name));
+    data.push(0);
+    data.extend([0; 12]);
+    data.extend(0i32.to_le_bytes());
+    data.extend(0u16.to_le_bytes());
+    data.push(0);
+    data.extend(mods.bits().to_le_bytes());
+    data.push(0);
+    data.extend(0i64.to_le_bytes());
+    // no frame data
+    data.extend(0i32.to_le_bytes());
+    data
+}
+
+#[test]
+fn header() {
+    let replay = decode(ReplayDecoder::minimal(), FIXTURE);
+    let header = &replay.header;
+
+    assert_eq!(header.mode, Mode::Standard);
+    assert_eq!(header.game_version, 20150101);
+    assert_eq!(header.beatmap_md5, "a5b99395a42bd55bc5eb1d2411cbdf8b");
+    assert_eq!(header.player_name, "peppy");
+    assert_eq!(header.replay_md5, "2f8e3d1c5a7b9e0f1d3c5b7a9e8f0d1c");
+    assert_eq!(
+        header.judgements,
+        Judgements {
+            n300: 100,
+            n100: 5,
+            n50: 1,
+            n_geki: 20,
+            n_katu: 3,
+            n_miss: 2,
+        }
+    );
+    assert_eq!(header.score, 123456);
+    assert_eq!(header.max_combo, 150);
+    assert!(!header.perfect);
+    assert_eq!(header.mods, Mods::HIDDEN | Mods::HARD_ROCK);
+
+    assert_eq!(replay.timestamp, 635000000000000000);
+    assert_eq!(replay.online_score_id, 2_000_000_000_000);
+    assert_eq!(replay.target_practice_accuracy, None);
+}
+
+#[test]
+fn skip_life_bar_and_frames() {
+    let replay = decode(ReplayDecoder::minimal(), FIXTURE);
+
+    // the parts after the frames are still read from the right place
+    assert!(replay.life_bar.is_empty());
+    assert!(replay.frames.is_empty());
+    assert_eq!(replay.online_score_id, 2_000_000_000_000);
+}
+
+#[test]
+fn life_bar_and_frames() {
+    let replay = decode(ReplayDecoder::minimal().life_bar().frames(), FIXTURE);
+
+    assert_eq!(
+        replay.life_bar,
+        [
+            LifeBarPoint {
+                time: Time(0.0),
+                life: 1.0,
+            },
+            LifeBarPoint {
+                time: Time(2000.0),
+                life: 0.75,
+            },
+            LifeBarPoint {
+                time: Time(4000.0),
+                life: 0.5,
+            },
+        ]
+    );
+
+    assert_eq!(replay.frames.len(), 5);
+    assert_eq!(
+        replay.frames[2],
+        ReplayFrame {
+            time_delta: Time(16.0),
+            x: 260.5,
+            y: 190.25,
+            keys: Keys::M1,
+        }
+    );
+    assert_eq!(replay.frames[3].keys, Keys::M1 | Keys::K1);
+    assert_eq!(replay.seed(), Some(1234567));
+    assert_eq!(replay.online_score_id, 2_000_000_000_000);
+}
+
+#[test]
+fn no_frame_data() {
+    let mut data = replay(20150101, b"peppy", Mods::empty());
+    data.extend(1i64.to_le_bytes());
+
+    let replay = decode(ReplayDecoder::minimal().frames(), &data);
+    assert!(replay.frames.is_empty());
+    assert_eq!(replay.seed(), None);
+    assert_eq!(replay.online_score_id, 1);
+}
+
+#[test]
+fn online_score_id_width() {
+    // a 64-bit ID since 2014-07-21
+    let mut data = replay(20140721, b"peppy", Mods::empty());
+    data.extend((-2i64).to_le_bytes());
+    assert_eq!(decode(ReplayDecoder::minimal(), &data).online_score_id, -2);
+
+    // a 32-bit one before
+    let mut data = replay(20140720, b"peppy", Mods::empty());
+    data.extend((-2i32).to_le_bytes());
+    assert_eq!(decode(ReplayDecoder::minimal(), &data).online_score_id, -2);
+
+    // and none before 2012-10-08
+    let data = replay(20121007, b"peppy", Mods::empty());
+    assert_eq!(decode(ReplayDecoder::minimal(), &data).online_score_id, 0);
+
+    // which makes an older replay with a 64-bit ID too short
+    let mut data = replay(20140720, b"peppy", Mods::empty());
+    data.extend(1i16.to_le_bytes());
+    assert!(ReplayDecoder::minimal()
+        .parse(Replay::default(), &data[..])
+        .is_err());
+}
+
+#[test]
+fn target_practice_accuracy() {
+    let mut data = replay(20150101, b"peppy", Mods::TARGET_PRACTICE);
+    data.extend(1i64.to_le_bytes());
+    data.extend(0.875f64.to_le_bytes());
+
+    let replay = decode(ReplayDecoder::minimal(), &data);
+    assert_eq!(replay.online_score_id, 1);
+    assert_eq!(replay.target_practice_accuracy, Some(0.875));
+}
+
+#[test]
+fn invalid_utf8_player_name() {
+    // "Dés" in Windows-1252, from an old replay
+    let data = replay(20120101, b"D\xe9s", Mods::empty());
+
+    let replay = decode(ReplayDecoder::minimal(), &data);
+    assert_eq!(replay.header.player_name, "D\u{fffd}s");
+}
+
+#[test]
+fn truncated() {
+    for length in [0, 10, FIXTURE.len() - 1] {
+        assert!(
+            ReplayDecoder::minimal()
+                .parse(Replay::default(), &FIXTURE[..length])
+                .is_err(),
+            "{length}"
+        );
+    }
+}
@@ -0,0 +1,176 @@
+mod common;
+
+use common::BeatmapBuilder;
+use nalgebra_glm::Vec2;
+use osulib::{
+    file::beatmap::hit_objects::slider_events::{SliderEventKind, SliderEvents},
+    time::Time,
+};
+
+/// Decodes a beatmap with one slider at 1000ms, and one tick per beat, so that the slider
+/// travels 100 osu! pixels per 500ms at a velocity of 1.
+fn slider_events(version: u8, timing_points: &[&str], slider: &str) -> SliderEvents {
+    let beatmap = timing_points
+        .iter()
+        .fold(BeatmapBuilder::new().version(version), |builder, t| {
+            builder.timing_point(t)
+        })
+        .hit_objects(&[slider])
+        .build();
+
+    beatmap.hit_objects[0]
+        .slider_events(
+            &beatmap.difficulty,
+            &beatmap.control_points(),
+            beatmap.file_format_version,
+        )
+        .unwrap()
+}
+
+fn times(events: &SliderEvents) -> Vec<(SliderEventKind, f64)> {
+    events.events.iter().map(|e| (e.kind, e.time.0)).collect()
+}
+
+#[test]
+fn single_span() {
+    let events = slider_events(14, &[], "0,0,1000,2,0,L|250:0,1,250");
+
+    assert_eq!(events.velocity, 0.2);
+    assert_eq!(events.tick_distance, 100.0);
+    assert_eq!(events.span_duration, Time(1250.0));
+    assert_eq!(events.end_time, Time(2250.0));
+    assert_eq!(
+        times(&events),
+        [
+            (SliderEventKind::Head, 1000.0),
+            (SliderEventKind::Tick, 1500.0),
+            (SliderEventKind::Tick, 2000.0),
+            (SliderEventKind::LegacyLastTick, 2214.0),
+            (SliderEventKind::Tail, 2250.0),
+        ]
+    );
+
+    let positions = events.events.iter().map(|e| e.position).collect::<Vec<_>>();
+    assert_eq!(
+        positions,
+        [
+            Vec2::new(0.0, 0.0),
+            Vec2::new(100.0, 0.0),
+            Vec2::new(200.0, 0.0),
+            Vec2::new(242.8, 0.0),
+            Vec2::new(250.0, 0.0),
+        ]
+    );
+}
+
+#[test]
+fn repeats() {
+    let events = slider_events(14, &[], "0,0,1000,2,0,L|250:0,3,250");
+
+    assert_eq!(
+        times(&events),
+        [
+            (SliderEventKind::Head, 1000.0),
+            (SliderEventKind::Tick, 1500.0),
+            (SliderEventKind::Tick, 2000.0),
+            (SliderEventKind::Repeat, 2250.0),
+            // ticks of reversed spans are at the same positions, so in reverse order
+            (SliderEventKind::Tick, 2500.0),
+            (SliderEventKind::Tick, 3000.0),
+            (SliderEventKind::Repeat, 3500.0),
+            (SliderEventKind::Tick, 4000.0),
+            (SliderEventKind::Tick, 4500.0),
+            (SliderEventKind::LegacyLastTick, 4714.0),
+            (SliderEventKind::Tail, 4750.0),
+        ]
+    );
+
+    let repeats = events.repeats().collect::<Vec<_>>();
+    assert_eq!((repeats[0].span_index, repeats[0].path_progress), (0, 1.0));
+    assert_eq!((repeats[1].span_index, repeats[1].path_progress), (1, 0.0));
+
+    let ticks = events.ticks().map(|t| t.path_progress).collect::<Vec<_>>();
+    assert_eq!(ticks, [0.4, 0.8, 0.8, 0.4, 0.4, 0.8]);
+}
+
+#[test]
+fn legacy_last_tick_on_reversed_span() {
+    let events = slider_events(14, &[], "0,0,1000,2,0,L|250:0,2,250");
+    let last_tick = events.legacy_last_tick().unwrap();
+
+    assert_eq!(last_tick.time, Time(3464.0));
+    assert_eq!(last_tick.span_index, 1);
+    // 36ms before the end of a span going back to the head
+    assert!((last_tick.path_progress - 36.0 / 1250.0).abs() < 1e-9);
+    assert_eq!(events.tail().unwrap().path_progress, 0.0);
+}
+
+#[test]
+fn legacy_last_tick_not_before_middle() {
+    // a 50ms slider, whose last tick would otherwise be before its middle
+    let events = slider_events(14, &[], "0,0,1000,2,0,L|10:0,1,10");
+
+    assert_eq!(events.legacy_last_tick().unwrap().time, Time(1025.0));
+    assert_eq!(events.legacy_last_tick().unwrap().path_progress, 0.5);
+}
+
+#[test]
+fn no_tick_near_the_end() {
+    // ticks closer than 10ms to the end of a span are dropped: 200px is exactly at the end
+    let events = slider_events(14, &[], "0,0,1000,2,0,L|200:0,1,200");
+    assert_eq!(
+        events.ticks().map(|t| t.time.0).collect::<Vec<_>>(),
+        [1500.0]
+    );
+
+    // 205px leaves 25ms after the second tick, 201px only 5ms
+    let events = slider_events(14, &[], "0,0,1000,2,0,L|205:0,1,205");
+    assert_eq!(events.ticks().count(), 2);
+    let events = slider_events(14, &[], "0,0,1000,2,0,L|201:0,1,201");
+    assert_eq!(events.ticks().count(), 1);
+}
+
+#[test]
+fn tick_distance_follows_slider_velocity() {
+    // at twice the slider velocity, ticks are twice as far apart since v8
+    let events = slider_events(14, &["0,-50,4,1,0,100,0,0"], "0,0,1000,2,0,L|500:0,1,500");
+
+    assert_eq!(events.velocity, 0.4);
+    assert_eq!(events.tick_distance, 200.0);
+    assert_eq!(
+        events.ticks().map(|t| t.time.0).collect::<Vec<_>>(),
+        [1500.0, 2000.0]
+    );
+}
+
+#[test]
+fn tick_distance_before_v8() {
+    // before v8, ticks keep the same distance whatever the slider velocity
+    let events = slider_events(7, &["0,-50,4,1,0,100,0,0"], "0,0,1000,2,0,L|500:0,1,500");
+
+    assert_eq!(events.velocity, 0.4);
+    assert_eq!(events.tick_distance, 100.0);
+    assert_eq!(
+        events.ticks().map(|t| t.time.0).collect::<Vec<_>>(),
+        [1250.0, 1500.0, 1750.0, 2000.0]
+    );
+}
+
+#[test]
+fn zero_length() {
+    // a slider without any length ends where it starts, and its progress cannot be computed
+    // from its duration
+    for (slides, progress) in [(1, 1.0), (2, 0.0)] {
+        let slider = format!("0,0,1000,2,0,L|0:0,{slides},0");
+        let events = slider_events(14, &[], &slider);
+
+        assert_eq!(events.span_duration, Time(0.0));
+        assert_eq!(events.ticks().count(), 0);
+
+        let last_tick = events.legacy_last_tick().unwrap();
+        assert_eq!(last_tick.time, Time(1000.0));
+        assert_eq!(last_tick.path_progress, progress);
+        assert_eq!(last_tick.position, Vec2::zeros());
+        assert_eq!(events.tail().unwrap().time, Time(1000.0));
+    }
+}
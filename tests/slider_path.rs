@@ -0,0 +1,310 @@
+use nalgebra_glm::Vec2;
+use osulib::file::beatmap::{
+    hit_objects::{slider::Curve, slider_path::SliderPath, HitObjectKind},
+    Beatmap, BeatmapDecoder,
+};
+
+fn tessellate(curve: &str, expected_length: Option<f64>) -> SliderPath {
+    SliderPath::new(Vec2::zeros(), &curve.parse().unwrap(), expected_length)
+}
+
+#[track_caller]
+fn assert_near(actual: Vec2, expected: Vec2, tolerance: f32) {
+    assert!(
+        (actual - expected).norm() <= tolerance,
+        "expected {expected:?}, got {actual:?}"
+    );
+}
+
+/// Checks that the cumulative lengths match the distances between the points.
+#[track_caller]
+fn assert_consistent(path: &SliderPath) {
+    assert_eq!(path.points().len(), path.cumulative_lengths().len());
+    assert_eq!(path.cumulative_lengths()[0], 0.0);
+
+    for (pair, lengths) in path
+        .points()
+        .windows(2)
+        .zip(path.cumulative_lengths().windows(2))
+    {
+        let distance = (pair[1] - pair[0]).norm() as f64;
+        assert!((lengths[1] - lengths[0] - distance).abs() < 1e-3);
+    }
+}
+
+#[test]
+fn linear() {
+    let path = tessellate("L|100:0|100:100", None);
+
+    assert_eq!(
+        path.points(),
+        [
+            Vec2::zeros(),
+            Vec2::new(100.0, 0.0),
+            Vec2::new(100.0, 100.0)
+        ]
+    );
+    assert_eq!(path.cumulative_lengths(), [0.0, 100.0, 200.0]);
+    assert_near(path.position_at(0.25), Vec2::new(50.0, 0.0), 1e-4);
+    assert_near(path.position_at(0.75), Vec2::new(100.0, 50.0), 1e-4);
+    assert_near(path.position_at(2.0), Vec2::new(100.0, 100.0), 1e-4);
+}
+
+#[test]
+fn linear_relative_to_head() {
+    let curve: Curve = "L|200:100".parse().unwrap();
+    let path = SliderPath::new(Vec2::new(100.0, 100.0), &curve, None);
+
+    assert_eq!(path.points(), [Vec2::zeros(), Vec2::new(100.0, 0.0)]);
+}
+
+#[test]
+fn shortened_to_expected_length() {
+    // the last segment is shortened, and every segment past the expected length dropped
+    let path = tessellate("L|100:0|100:100|0:100", Some(150.0));
+
+    assert_eq!(
+        path.points(),
+        [Vec2::zeros(), Vec2::new(100.0, 0.0), Vec2::new(100.0, 50.0)]
+    );
+    assert_eq!(path.cumulative_lengths(), [0.0, 100.0, 150.0]);
+    assert_eq!(path.length(), 150.0);
+}
+
+#[test]
+fn shortened_to_nothing() {
+    let path = tessellate("L|100:0", Some(0.0));
+
+    assert_eq!(path.points(), [Vec2::zeros()]);
+    assert_eq!(path.length(), 0.0);
+}
+
+#[test]
+fn extended_to_expected_length() {
+    // the last segment is extended in its own direction
+    let path = tessellate("L|100:0|100:100", Some(250.0));
+
+    assert_eq!(path.points()[2], Vec2::new(100.0, 150.0));
+    assert_eq!(path.cumulative_lengths(), [0.0, 100.0, 250.0]);
+}
+
+#[test]
+fn not_extended_after_repeated_point() {
+    // osu!stable does not extend sliders whose last two points are equal
+    let path = tessellate("L|100:0|100:0", Some(250.0));
+
+    assert_eq!(path.length(), 100.0);
+    assert_eq!(path.points().last(), Some(&Vec2::new(100.0, 0.0)));
+
+    // but it still shortens them
+    let path = tessellate("L|100:0|100:0", Some(50.0));
+    assert_eq!(path.length(), 50.0);
+    assert_eq!(path.points().last(), Some(&Vec2::new(50.0, 0.0)));
+}
+
+#[test]
+fn perfect() {
+    // half circle of radius 50 centred on (50, 0), going through (50, -50)
+    let path = tessellate("P|50:-50|100:0", None);
+    let centre = Vec2::new(50.0, 0.0);
+
+    assert_consistent(&path);
+    assert_near(path.points()[0], Vec2::zeros(), 1e-3);
+    assert_near(*path.points().last().unwrap(), Vec2::new(100.0, 0.0), 1e-3);
+    for point in path.points() {
+        assert!(((point - centre).norm() - 50.0).abs() < 1e-3);
+        assert!(point.y <= 1e-3, "arc is on the wrong side: {point:?}");
+    }
+
+    // within the tolerance of 0.1 osu! pixels, the polyline is slightly shorter than the arc
+    let arc = std::f64::consts::PI * 50.0;
+    assert!(path.length() < arc && path.length() > arc - 0.5);
+    assert_near(path.position_at(0.5), Vec2::new(50.0, -50.0), 0.1);
+}
+
+#[test]
+fn perfect_other_side() {
+    let path = tessellate("P|50:50|100:0", None);
+
+    for point in path.points() {
+        assert!(point.y >= -1e-3, "arc is on the wrong side: {point:?}");
+    }
+    assert_near(path.position_at(0.5), Vec2::new(50.0, 50.0), 0.1);
+}
+
+#[test]
+fn perfect_extended_along_last_segment() {
+    // a quarter circle extended by 100 pixels past its end, which osu! does along the last
+    // segment of the tessellated arc rather than along the tangent of the circle
+    let curve = "P|14.644661:35.355339|50:50";
+    let quarter = std::f64::consts::FRAC_PI_2 * 50.0;
+    let arc = tessellate(curve, None);
+    let path = tessellate(curve, Some(quarter + 100.0));
+
+    assert_consistent(&path);
+    assert!((path.length() - (quarter + 100.0)).abs() < 1e-4);
+
+    let n = arc.points().len();
+    let (before, last) = (arc.points()[n - 2], arc.points()[n - 1]);
+    let extension = (quarter + 100.0 - arc.cumulative_lengths()[n - 2]) as f32;
+    assert_eq!(path.points().len(), n);
+    assert_near(
+        path.points()[n - 1],
+        before + (last - before).normalize() * extension,
+        1e-3,
+    );
+    // which is close to the tangent, going right from (50, 50)
+    assert_near(path.points()[n - 1], Vec2::new(150.0, 50.0), 7.0);
+}
+
+#[test]
+fn perfect_collinear_is_linear() {
+    // collinear points cannot define a circle, osu! falls back to a bézier curve
+    let path = tessellate("P|50:0|100:0", None);
+
+    assert_consistent(&path);
+    assert!((path.length() - 100.0).abs() < 1e-4);
+    for point in path.points() {
+        assert_eq!(point.y, 0.0);
+    }
+
+    // the same goes for almost collinear points
+    let path = tessellate("P|50:0.0001|100:0", Some(100.0));
+    assert!(path.points().iter().all(|p| p.y.abs() < 1e-3));
+}
+
+#[test]
+fn perfect_with_wrong_point_count_is_bezier() {
+    for curve in ["P|100:0", "P|50:-50|100:0|150:50"] {
+        let perfect = tessellate(curve, None);
+        let bezier = tessellate(&curve.replacen('P', "B", 1), None);
+
+        assert_eq!(perfect, bezier, "{curve}");
+    }
+}
+
+#[test]
+fn bezier() {
+    // quadratic bézier: B(t) = (1 - t)²·A + 2t(1 - t)·B + t²·C
+    let path = tessellate("B|50:100|100:0", None);
+    let at = |t: f32| Vec2::new(100.0 * t, 200.0 * t * (1.0 - t));
+
+    assert_consistent(&path);
+    assert_eq!(path.points()[0], Vec2::zeros());
+    assert_eq!(*path.points().last().unwrap(), Vec2::new(100.0, 0.0));
+
+    // the curve is approximated within 0.25 osu! pixels
+    for point in path.points() {
+        assert_near(*point, at(point.x / 100.0), 0.25);
+    }
+    assert_near(path.position_at(0.5), at(0.5), 0.25);
+
+    // arc length of the parabola y = 2x(1 - x/100), from x = 0 to 100
+    let length = 25.0 * (2.0 * 5f64.sqrt() + (2.0 + 5f64.sqrt()).ln());
+    assert!((path.length() - length).abs() < 0.1, "{}", path.length());
+}
+
+#[test]
+fn bezier_segments() {
+    // a repeated point (red anchor) ends a segment, both segments here being straight
+    let path = tessellate("B|100:0|100:0|100:100", None);
+
+    assert_consistent(&path);
+    assert!(path.points().contains(&Vec2::new(100.0, 0.0)));
+    assert!((path.length() - 200.0).abs() < 1e-4);
+    for point in path.points() {
+        assert!(point.y == 0.0 || point.x == 100.0, "{point:?}");
+    }
+}
+
+#[test]
+fn catmull() {
+    let path = tessellate("C|100:0|100:100", None);
+
+    assert_consistent(&path);
+    // every pair of points is split into 50 segments, each written as two points
+    assert_eq!(path.points().len(), 2 * 50 * 2);
+    // the curve goes through its control points
+    assert_eq!(path.points()[0], Vec2::zeros());
+    assert!(path.points().contains(&Vec2::new(100.0, 0.0)));
+    assert_near(
+        *path.points().last().unwrap(),
+        Vec2::new(100.0, 100.0),
+        1e-3,
+    );
+}
+
+#[test]
+fn bench_map() {
+    let beatmap = BeatmapDecoder::all()
+        .parse(
+            Beatmap::default(),
+            &include_bytes!("../benches/beatmap.osu")[..],
+        )
+        .unwrap();
+
+    let mut sliders = 0;
+    for hit_object in &beatmap.hit_objects {
+        let HitObjectKind::Slider(slider) = &hit_object.kind else {
+            continue;
+        };
+        sliders += 1;
+
+        let path = SliderPath::from_slider(hit_object.position, slider);
+        assert_consistent(&path);
+        assert!(
+            (path.length() - slider.length as f64).abs() < 1e-3,
+            "{} at {}: {} instead of {}",
+            hit_object.position,
+            hit_object.time,
+            path.length(),
+            slider.length
+        );
+    }
+    assert_eq!(sliders, 800);
+
+    // 380,320,1145,6,0,L|384:280,11,28.75
+    let first = &beatmap.hit_objects[0];
+    let HitObjectKind::Slider(slider) = &first.kind else {
+        panic!("expected a slider");
+    };
+    let path = SliderPath::from_slider(first.position, slider);
+    let direction = Vec2::new(4.0, -40.0).normalize();
+    assert_near(*path.points().last().unwrap(), direction * 28.75, 1e-4);
+
+    // 108,50,322418,2,0,P|96:151|112:199,1,91.9999971923829
+    let perfect = beatmap
+        .hit_objects
+        .iter()
+        .find(|h| h.time.as_millis() == 322418.0)
+        .unwrap();
+    let HitObjectKind::Slider(slider) = &perfect.kind else {
+        panic!("expected a slider");
+    };
+    let path = SliderPath::from_slider(perfect.position, slider);
+
+    // the end lies on the circle through the three points, 92 pixels along the arc
+    let (a, b, c) = (
+        Vec2::zeros(),
+        Vec2::new(-12.0, 101.0),
+        Vec2::new(4.0, 149.0),
+    );
+    let d = 2.0 * (a.x * (b.y - c.y) + b.x * (c.y - a.y) + c.x * (a.y - b.y));
+    let centre = Vec2::new(
+        (b.norm_squared() * (c.y - a.y) + c.norm_squared() * (a.y - b.y)) / d,
+        (b.norm_squared() * (a.x - c.x) + c.norm_squared() * (b.x - a.x)) / d,
+    );
+    let radius = centre.norm();
+    let angle = slider.length / radius;
+    let start = -centre;
+    // the arc goes clockwise on screen, towards b
+    let sign = if (b - a).perp(&(c - a)) > 0.0 {
+        1.0
+    } else {
+        -1.0
+    };
+    let (sin, cos) = (sign * angle).sin_cos();
+    let end = centre + Vec2::new(start.x * cos - start.y * sin, start.x * sin + start.y * cos);
+
+    assert_near(*path.points().last().unwrap(), end, 0.1);
+}
@@ -0,0 +1,117 @@
+mod common;
+
+use common::BeatmapBuilder;
+use nalgebra_glm::Vec2;
+use osulib::file::beatmap::{Beatmap, BeatmapDecoder};
+
+/// Decodes a beatmap where sliders travel 100 osu! pixels per 500ms, with a stack threshold of
+/// 840ms (AR 5 and a stack leniency of 0.7).
+fn decode(version: u8, hit_objects: &[&str]) -> Beatmap {
+    BeatmapBuilder::new()
+        .version(version)
+        .general("StackLeniency", 0.7)
+        .difficulty("CircleSize", 5)
+        .difficulty("ApproachRate", 5)
+        .hit_objects(hit_objects)
+        .build()
+}
+
+fn heights(beatmap: &Beatmap) -> Vec<i32> {
+    beatmap.stacking().iter().map(|s| s.height).collect()
+}
+
+#[test]
+fn stack() {
+    let hit_objects = [
+        "100,100,1000,1,0,0:0:0:0:",
+        "100,100,1200,1,0,0:0:0:0:",
+        "100,100,1400,1,0,0:0:0:0:",
+        // too late to stack on the previous object
+        "100,100,2300,1,0,0:0:0:0:",
+    ];
+
+    for version in [5, 14] {
+        let beatmap = decode(version, &hit_objects);
+        let stacking = beatmap.stacking();
+
+        assert_eq!(heights(&beatmap), [2, 1, 0, 0], "v{version}");
+
+        // a circle radius of 32 at CS 5, and 1/10 of it per object
+        assert_eq!(stacking[0].offset, Vec2::new(-6.4, -6.4));
+        assert_eq!(stacking[0].position, Vec2::new(93.6, 93.6));
+        assert_eq!(stacking[3].position, Vec2::new(100.0, 100.0));
+    }
+}
+
+#[test]
+fn old_slider_end_stacks_down_and_right() {
+    // the slider ends at 1500 on (100, 0)
+    let beatmap = decode(
+        5,
+        &[
+            "0,0,1000,2,0,L|100:0,1,100",
+            "100,0,1600,1,0,0:0:0:0:",
+            "100,0,1700,1,0,0:0:0:0:",
+        ],
+    );
+
+    assert_eq!(heights(&beatmap), [0, -1, -2]);
+    assert_eq!(beatmap.stacking()[2].position, Vec2::new(106.4, 6.4));
+}
+
+#[test]
+fn old_inner_slider_does_not_extend_the_stack() {
+    // the slider lasts until 2100, but osu!stable only looks at its start time when stacking the
+    // circle before it, so the last circle is too late for that stack
+    let beatmap = decode(
+        5,
+        &[
+            "100,100,1000,1,0,0:0:0:0:",
+            "100,100,1100,2,0,L|300:100,1,200",
+            "100,100,2500,1,0,0:0:0:0:",
+        ],
+    );
+
+    assert_eq!(heights(&beatmap), [1, 1, 0]);
+}
+
+#[test]
+fn old_inner_spinner_does_not_extend_the_stack() {
+    let beatmap = decode(
+        5,
+        &[
+            "100,100,1000,1,0,0:0:0:0:",
+            "100,100,1100,8,0,3000,0:0:0:0:",
+            "100,100,2500,1,0,0:0:0:0:",
+        ],
+    );
+
+    assert_eq!(heights(&beatmap)[0], 1);
+}
+
+#[test]
+fn slider_end_stacks_down_and_right() {
+    let beatmap = decode(
+        14,
+        &[
+            "0,0,1000,2,0,L|100:0,1,100",
+            "100,0,1600,1,0,0:0:0:0:",
+            "100,0,1700,1,0,0:0:0:0:",
+        ],
+    );
+
+    assert_eq!(heights(&beatmap), [0, -1, -2]);
+}
+
+#[test]
+fn legacy_fixture() {
+    let beatmap = BeatmapDecoder::all()
+        .parse(
+            Beatmap::default(),
+            &include_bytes!("fixtures/legacy.osu")[..],
+        )
+        .unwrap();
+
+    // the circles at the start, and the slider at 5000 with the two circles on its head
+    assert_eq!(heights(&beatmap), [2, 1, 0, 0, 0, 0, 0, 2, 1, 0, 0, 0, 0]);
+}
@@ -0,0 +1,348 @@
+use osulib::{
+    file::beatmap::{Beatmap, BeatmapDecoder},
+    mods::Mods,
+    ruleset::standard::{
+        difficulty::{StandardDifficultyAttributes, StandardDifficultyCalculator},
+        performance::{StandardPerformanceCalculator, StandardScore},
+    },
+};
+
+// The expected values below are written out from the formulas of osu!, independently of the
+// crate, from the difficulty attributes it computes.
+
+fn beatmap() -> Beatmap {
+    BeatmapDecoder::all()
+        .parse(
+            Beatmap::default(),
+            &include_bytes!("../benches/beatmap.osu")[..],
+        )
+        .unwrap()
+}
+
+#[track_caller]
+fn assert_close(actual: f64, expected: f64) {
+    assert!(
+        (actual - expected).abs() <= expected.abs() * 1e-9,
+        "expected {expected}, got {actual}"
+    );
+}
+
+/// Star rating from the aim, speed and flashlight difficulties.
+fn stars(attributes: &StandardDifficultyAttributes) -> f64 {
+    let base_performance = (skill_performance(attributes.aim).powf(1.1)
+        + skill_performance(attributes.speed).powf(1.1)
+        + (attributes.flashlight.powi(2) * 25.0).powf(1.1))
+    .powf(1.0 / 1.1);
+
+    1.14f64.cbrt() * 0.027 * ((1e5 / 2f64.powf(1.0 / 1.1) * base_performance).cbrt() + 4.0)
+}
+
+/// Performance of the difficulty `value` of a skill, before any adjustment.
+fn skill_performance(value: f64) -> f64 {
+    (5.0 * (value / 0.0675).max(1.0) - 4.0).powi(3) / 1e5
+}
+
+fn length_bonus(total_hits: f64) -> f64 {
+    0.95 + 0.4 * (total_hits / 2000.0).min(1.0)
+        + if total_hits > 2000.0 {
+            (total_hits / 2000.0).log10() * 0.5
+        } else {
+            0.0
+        }
+}
+
+/// Aim, speed, accuracy and flashlight performance of a score without relax, no fail, spun out
+/// or Score V2, and its effective miss count.
+fn terms(attributes: &StandardDifficultyAttributes, score: &StandardScore) -> ([f64; 4], f64) {
+    let a = attributes;
+    let total = score.total_hits() as f64;
+    let (n300, n100, n50, n_miss) = (
+        score.n300 as f64,
+        score.n100 as f64,
+        score.n50 as f64,
+        score.n_miss as f64,
+    );
+    let accuracy = (n300 + n100 / 3.0 + n50 / 6.0) / total;
+    let max_combo = a.max_combo as f64;
+    let combo = score.max_combo as f64;
+    let sliders = a.n_sliders as f64;
+
+    let threshold = max_combo - 0.1 * sliders;
+    let combo_misses = if combo < threshold {
+        threshold / combo.max(1.0)
+    } else {
+        0.0
+    };
+    let misses = n_miss.max(combo_misses.min(n100 + n50 + n_miss));
+
+    let miss_penalty = |exponent: f64| {
+        if misses > 0.0 {
+            0.97 * (1.0 - (misses / total).powf(0.775)).powf(exponent)
+        } else {
+            1.0
+        }
+    };
+    let combo_scaling = (combo.powf(0.8) / max_combo.powf(0.8)).min(1.0);
+    let length_bonus = length_bonus(total);
+    let hidden = if score.mods.contains(Mods::HIDDEN) {
+        1.0 + 0.04 * (12.0 - a.ar)
+    } else {
+        1.0
+    };
+    let high_ar = if a.ar > 10.33 {
+        0.3 * (a.ar - 10.33)
+    } else {
+        0.0
+    };
+    let low_ar = if a.ar < 8.0 { 0.05 * (8.0 - a.ar) } else { 0.0 };
+
+    let difficult_sliders = sliders * 0.15;
+    let dropped = (n100 + n50 + n_miss)
+        .min(max_combo - combo)
+        .clamp(0.0, difficult_sliders);
+    let slider_nerf =
+        (1.0 - a.slider_factor) * (1.0 - dropped / difficult_sliders).powi(3) + a.slider_factor;
+    let aim = skill_performance(a.aim)
+        * length_bonus
+        * miss_penalty(misses)
+        * combo_scaling
+        * (1.0 + (high_ar + low_ar) * length_bonus)
+        * hidden
+        * slider_nerf
+        * accuracy
+        * (0.98 + a.od.powi(2) / 2500.0);
+
+    // the worst judgements are assumed to be on the speed notes
+    let others = total - a.speed_note_count;
+    let speed_n300 = (n300 - others).max(0.0);
+    let speed_n100 = (n100 - (others - n300).max(0.0)).max(0.0);
+    let speed_n50 = (n50 - (others - n300 - n100).max(0.0)).max(0.0);
+    let speed_accuracy =
+        (speed_n300 * 6.0 + speed_n100 * 2.0 + speed_n50) / (a.speed_note_count * 6.0);
+    let n50_penalty = 0.99f64.powf((n50 - total / 500.0).max(0.0));
+    let speed = skill_performance(a.speed)
+        * length_bonus
+        * miss_penalty(misses.powf(0.875))
+        * combo_scaling
+        * (1.0 + high_ar * length_bonus)
+        * hidden
+        * (0.95 + a.od.powi(2) / 750.0)
+        * ((accuracy + speed_accuracy) / 2.0).powf((14.5 - a.od.max(8.0)) / 2.0)
+        * n50_penalty;
+
+    // only circles count, with every other object judged a 300
+    let circles = a.n_circles as f64;
+    let circle_accuracy = ((n300 - (total - circles)) * 6.0 + n100 * 2.0 + n50) / (circles * 6.0);
+    let mut accuracy_value = 1.52163f64.powf(a.od)
+        * circle_accuracy.max(0.0).powi(24)
+        * 2.83
+        * (circles / 1000.0).powf(0.3).min(1.15);
+
+    let mut flashlight = 0.0;
+    if score.mods.contains(Mods::HIDDEN) {
+        accuracy_value *= 1.08;
+    }
+    if score.mods.contains(Mods::FLASHLIGHT) {
+        accuracy_value *= 1.02;
+        flashlight = a.flashlight.powi(2)
+            * 25.0
+            * miss_penalty(misses.powf(0.875))
+            * combo_scaling
+            * (0.7
+                + 0.1 * (total / 200.0).min(1.0)
+                + 0.2 * ((total - 200.0) / 200.0).clamp(0.0, 1.0))
+            * (0.5 + accuracy / 2.0)
+            * (0.98 + a.od.powi(2) / 2500.0);
+    }
+
+    ([aim, speed, accuracy_value, flashlight], misses)
+}
+
+/// Checks every term of the performance of `score` against [`terms`].
+#[track_caller]
+fn assert_performance(attributes: &StandardDifficultyAttributes, score: &StandardScore) {
+    let performance = StandardPerformanceCalculator::new(attributes).calculate(score);
+    let ([aim, speed, accuracy, flashlight], misses) = terms(attributes, score);
+
+    assert_close(performance.effective_miss_count, misses);
+    assert_close(performance.aim, aim);
+    assert_close(performance.speed, speed);
+    assert_close(performance.accuracy, accuracy);
+    if flashlight == 0.0 {
+        assert_eq!(performance.flashlight, 0.0);
+    } else {
+        assert_close(performance.flashlight, flashlight);
+    }
+    assert_close(
+        performance.pp,
+        (aim.powf(1.1) + speed.powf(1.1) + accuracy.powf(1.1) + flashlight.powf(1.1))
+            .powf(1.0 / 1.1)
+            * 1.14,
+    );
+}
+
+fn full_combo(attributes: &StandardDifficultyAttributes) -> StandardScore {
+    StandardScore {
+        mods: attributes.mods,
+        max_combo: attributes.max_combo,
+        n300: attributes.n_circles + attributes.n_sliders + attributes.n_spinners,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn ss() {
+    let beatmap = beatmap();
+    let attributes = StandardDifficultyCalculator::new(&beatmap).calculate();
+
+    assert_eq!(
+        (
+            attributes.max_combo,
+            attributes.n_circles,
+            attributes.n_sliders,
+            attributes.n_spinners
+        ),
+        (3693, 1535, 800, 10)
+    );
+    assert_eq!((attributes.od, attributes.ar), (9.0, 9.7f32 as f64));
+    assert_close(attributes.stars, stars(&attributes));
+
+    let score = full_combo(&attributes);
+    let performance = StandardPerformanceCalculator::new(&attributes).calculate(&score);
+    assert_eq!(performance.effective_miss_count, 0.0);
+    assert_eq!(performance.flashlight, 0.0);
+
+    // without misses and at AR 9.7, only the length and OD change the aim and speed
+    let length_bonus = 0.95 + 0.4 + (2345.0f64 / 2000.0).log10() * 0.5;
+    assert_close(
+        performance.aim,
+        skill_performance(attributes.aim) * length_bonus * (0.98 + 81.0 / 2500.0),
+    );
+    assert_close(
+        performance.speed,
+        skill_performance(attributes.speed) * length_bonus * (0.95 + 81.0 / 750.0),
+    );
+    // every circle is a 300: 1.52163^OD · 2.83 · min(1.15, (circles / 1000)^0.3)
+    assert_close(
+        performance.accuracy,
+        1.52163f64.powi(9) * 2.83 * (1535.0f64 / 1000.0).powf(0.3),
+    );
+    assert_performance(&attributes, &score);
+}
+
+#[test]
+fn ss_with_mods() {
+    let beatmap = beatmap();
+
+    let attributes = StandardDifficultyCalculator::new(&beatmap)
+        .mods(Mods::HIDDEN | Mods::DOUBLE_TIME)
+        .calculate();
+    // AR 9.7 and OD 9 at 1.5 times the speed
+    assert_close(attributes.ar, 10.799999872843426);
+    assert_close(attributes.od, 10.444444444444445);
+    assert_close(attributes.stars, stars(&attributes));
+    assert_performance(&attributes, &full_combo(&attributes));
+
+    let attributes = StandardDifficultyCalculator::new(&beatmap)
+        .mods(Mods::HIDDEN | Mods::FLASHLIGHT)
+        .calculate();
+    assert!(attributes.flashlight > 0.0);
+    assert_close(attributes.stars, stars(&attributes));
+    assert_performance(&attributes, &full_combo(&attributes));
+
+    let performance =
+        StandardPerformanceCalculator::new(&attributes).calculate(&full_combo(&attributes));
+    // a long map is worth the whole flashlight difficulty
+    assert_close(
+        performance.flashlight,
+        attributes.flashlight.powi(2) * 25.0 * (0.98 + 81.0 / 2500.0),
+    );
+}
+
+#[test]
+fn misses() {
+    let beatmap = beatmap();
+    let score = StandardScore {
+        mods: Mods::empty(),
+        max_combo: 1000,
+        n300: 2300,
+        n100: 35,
+        n50: 2,
+        n_miss: 8,
+    };
+
+    let performance = score.performance(&beatmap);
+    assert_eq!(performance.effective_miss_count, 8.0);
+    assert_performance(&performance.difficulty, &score);
+
+    // 50s above one per 500 objects are penalised on speed
+    let score = StandardScore {
+        n300: 2287,
+        n50: 15,
+        ..score
+    };
+    assert_performance(&performance.difficulty, &score);
+}
+
+#[test]
+fn combo_break_counts_as_misses() {
+    let beatmap = beatmap();
+    let attributes = StandardDifficultyCalculator::new(&beatmap).calculate();
+    let score = StandardScore {
+        max_combo: 500,
+        n300: 2324,
+        n100: 20,
+        n_miss: 1,
+        ..Default::default()
+    };
+
+    // (max combo - 0.1 · sliders) / combo, at most the number of imperfect judgements
+    let performance = StandardPerformanceCalculator::new(&attributes).calculate(&score);
+    assert_close(performance.effective_miss_count, (3693.0 - 80.0) / 500.0);
+    assert_performance(&attributes, &score);
+
+    let score = StandardScore {
+        n300: 2342,
+        n100: 2,
+        ..score
+    };
+    let performance = StandardPerformanceCalculator::new(&attributes).calculate(&score);
+    assert_eq!(performance.effective_miss_count, 3.0);
+    assert_performance(&attributes, &score);
+}
+
+#[test]
+fn failed_score() {
+    let beatmap = beatmap();
+    let score = StandardScore {
+        mods: Mods::empty(),
+        max_combo: 500,
+        n300: 990,
+        n100: 6,
+        n50: 0,
+        n_miss: 4,
+    };
+
+    let performance = score.performance(&beatmap);
+    assert_eq!(
+        (
+            performance.difficulty.max_combo,
+            performance.difficulty.n_circles,
+            performance.difficulty.n_sliders,
+            performance.difficulty.n_spinners
+        ),
+        (1527, 720, 277, 3)
+    );
+    assert_close(performance.difficulty.stars, stars(&performance.difficulty));
+    assert_performance(&performance.difficulty, &score);
+
+    // the same as the beatmap cut after the last judged object
+    let mut prefix = beatmap.clone();
+    prefix.hit_objects.truncate(1000);
+    let attributes = StandardDifficultyCalculator::new(&prefix).calculate();
+    assert_eq!(performance.difficulty, attributes);
+    assert_eq!(
+        performance,
+        StandardPerformanceCalculator::new(&attributes).calculate(&score)
+    );
+}
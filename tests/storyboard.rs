@@ -0,0 +1,557 @@
+use nalgebra_glm::Vec2;
+use osulib::{
+    file::{
+        beatmap::{colour::Colour, diagnostic::Strictness, Beatmap, BeatmapDecoder},
+        storyboard::{
+            animation::LoopType,
+            command::{Command, Loop, Parameter, ParameterCommand, Trigger, Tween},
+            easing::Easing,
+            layer::Layer,
+            origin::Origin,
+            state::SpriteState,
+            variables::Variables,
+            Storyboard, StoryboardObject,
+        },
+    },
+    time::Time,
+};
+
+/// A storyboard with a loop and a trigger, and the same indentation osu! writes back.
+const EVENTS: &str = "\
+//Storyboard Layer 0 (Background)
+Animation,Background,TopLeft,\"sb/bird.png\",0,0,4,100,LoopOnce
+ MX,1,0,1000,0,640
+//Storyboard Layer 1 (Fail)
+//Storyboard Layer 2 (Pass)
+//Storyboard Layer 3 (Foreground)
+Sprite,Foreground,Centre,\"sb/star.png\",320,240
+ F,0,1000,2000,0,1
+ F,0,2000,3000,1,0
+ L,3000,2
+  M,0,0,500,100,100,200,200
+  S,0,500,1000,1,2
+ T,HitSoundClap,0,10000,1
+  C,0,0,100,255,0,0,255,255,255
+ P,0,1000,1000,H
+//Storyboard Layer 4 (Overlay)
+//Storyboard Sound Samples
+Sample,500,0,\"sb/hit.wav\",70
+";
+
+fn parse_lines(lines: &str) -> Storyboard {
+    let mut storyboard = Storyboard::default();
+    for line in lines.lines().filter(|l| !l.starts_with("//")) {
+        storyboard.parse_line(line).unwrap();
+    }
+    storyboard
+}
+
+fn write(storyboard: &Storyboard) -> String {
+    let mut data = Vec::new();
+    storyboard.write(&mut data).unwrap();
+    String::from_utf8(data).unwrap()
+}
+
+fn tween<T>(easing: Easing, start_time: f64, end_time: f64, start: T, end: T) -> Tween<T> {
+    Tween {
+        easing,
+        start_time: Time(start_time),
+        end_time: Time(end_time),
+        start,
+        end,
+    }
+}
+
+#[test]
+fn nested_commands() {
+    let storyboard = parse_lines(
+        "\
+Sprite,Foreground,Centre,\"sb/star.png\",320,240
+ F,0,1000,2000,0,1,0
+ L,3000,2
+  M,0,0,500,100,100,200,200
+  S,0,500,,1,2
+ T,HitSoundClap,0,10000,1
+  C,0,0,100,255,0,0,255,255,255
+ P,0,1000,,H
+Animation,Background,TopLeft,\"sb/bird.png\",0,0,4,100,LoopOnce
+_MX,1,0,1000,0,640
+Sample,500,0,\"sb/hit.wav\",70",
+    );
+
+    assert_eq!(storyboard.objects.len(), 3);
+
+    let StoryboardObject::Sprite(sprite) = &storyboard.objects[0] else {
+        panic!("expected sprite, got {:?}", storyboard.objects[0]);
+    };
+    assert_eq!(sprite.layer, Layer::Foreground);
+    assert_eq!(sprite.origin, Origin::Centre);
+    assert_eq!(sprite.filepath.to_str(), Some("sb/star.png"));
+    assert_eq!(sprite.position, Vec2::new(320.0, 240.0));
+    assert_eq!(
+        sprite.commands,
+        [
+            // three values expand into two consecutive fades of the same duration
+            Command::Fade(tween(Easing::Linear, 1000.0, 2000.0, 0.0, 1.0)),
+            Command::Fade(tween(Easing::Linear, 2000.0, 3000.0, 1.0, 0.0)),
+            Command::Loop(Loop {
+                start_time: Time(3000.0),
+                loop_count: 2,
+                commands: vec![
+                    Command::Move(tween(
+                        Easing::Linear,
+                        0.0,
+                        500.0,
+                        Vec2::new(100.0, 100.0),
+                        Vec2::new(200.0, 200.0),
+                    )),
+                    // an empty end time is the start time
+                    Command::Scale(tween(Easing::Linear, 500.0, 500.0, 1.0, 2.0)),
+                ],
+            }),
+            Command::Trigger(Trigger {
+                trigger: "HitSoundClap".into(),
+                start_time: Time(0.0),
+                end_time: Time(10000.0),
+                group: Some(1),
+                commands: vec![Command::Colour(tween(
+                    Easing::Linear,
+                    0.0,
+                    100.0,
+                    Colour(255, 0, 0),
+                    Colour(255, 255, 255),
+                ))],
+            }),
+            Command::Parameter(ParameterCommand {
+                easing: Easing::Linear,
+                start_time: Time(1000.0),
+                end_time: Time(1000.0),
+                parameter: Parameter::FlipHorizontal,
+            }),
+        ]
+    );
+
+    // the loop spans its two iterations of 500ms
+    assert_eq!(sprite.commands[2].start_time(), Time(3000.0));
+    assert_eq!(sprite.commands[2].end_time(), Time(4000.0));
+
+    let StoryboardObject::Animation(animation) = &storyboard.objects[1] else {
+        panic!("expected animation, got {:?}", storyboard.objects[1]);
+    };
+    assert_eq!(animation.frame_count, 4);
+    assert_eq!(animation.frame_delay, Time(100.0));
+    assert_eq!(animation.loop_type, LoopType::LoopOnce);
+    // underscores indent like spaces
+    assert_eq!(
+        animation.commands,
+        [Command::MoveX(tween(Easing::Out, 0.0, 1000.0, 0.0, 640.0))]
+    );
+
+    assert!(storyboard.objects[2].is_sample());
+    assert_eq!(storyboard.objects[2].layer(), Layer::Background);
+}
+
+#[test]
+fn write_back() {
+    let storyboard = parse_lines(EVENTS);
+
+    // grouped by layer with the samples last, and every command on its own line
+    assert_eq!(write(&storyboard), EVENTS);
+    assert_eq!(parse_lines(&write(&storyboard)), storyboard);
+}
+
+#[test]
+fn misplaced_commands() {
+    let mut storyboard = Storyboard::default();
+    assert!(storyboard.parse_line(" F,0,0,1000,1").is_err());
+
+    // samples have no commands
+    storyboard
+        .parse_line("Sample,500,0,\"sb/hit.wav\",70")
+        .unwrap();
+    assert!(storyboard.parse_line(" F,0,0,1000,1").is_err());
+
+    // nested commands need a loop or a trigger
+    storyboard
+        .parse_line("Sprite,Foreground,Centre,\"sb/star.png\",320,240")
+        .unwrap();
+    storyboard.parse_line(" F,0,0,1000,1").unwrap();
+    assert!(storyboard.parse_line("  F,0,0,1000,1").is_err());
+
+    assert!(storyboard.parse_line(" X,0,0,1000,1").is_err());
+    assert!(storyboard.parse_line(" M,0,0,1000,1").is_err());
+}
+
+#[test]
+fn beatmap_events() {
+    let data = format!(
+        "osu file format v14\n\n[Events]\n//Background and Video events\n0,0,\"bg.jpg\",0,0\n\
+         //Break Periods\n{EVENTS}\n[HitObjects]\n"
+    );
+
+    let beatmap = BeatmapDecoder::all()
+        .parse(Beatmap::default(), data.as_bytes())
+        .unwrap();
+    assert_eq!(beatmap.storyboard, parse_lines(EVENTS));
+    // the background is not part of the storyboard
+    assert_eq!(beatmap.events.len(), 1);
+
+    let beatmap = BeatmapDecoder::minimal()
+        .parse(Beatmap::default(), data.as_bytes())
+        .unwrap();
+    assert!(beatmap.storyboard.is_empty());
+
+    let mut written = Vec::new();
+    BeatmapDecoder::all()
+        .parse(Beatmap::default(), data.as_bytes())
+        .unwrap()
+        .write_to(&mut written)
+        .unwrap();
+    assert!(String::from_utf8(written).unwrap().contains(EVENTS));
+}
+
+fn sprite_path(object: &StoryboardObject) -> &str {
+    match object {
+        StoryboardObject::Sprite(s) => s.filepath.to_str().unwrap(),
+        _ => panic!("expected sprite, got {object:?}"),
+    }
+}
+
+#[test]
+fn longest_variable_first() {
+    // `$ab` is substituted as a whole even though `$a` is defined first
+    let mut variables = Variables::default();
+    variables.parse("$a=1").unwrap();
+    variables.parse("$ab = 2").unwrap();
+
+    assert_eq!(variables.get("$ab"), Some("2"));
+    assert_eq!(variables.substitute(" S,0,$ab,,$a"), " S,0,2,,1");
+    assert_eq!(variables.substitute(" S,0,$ab$a,,1"), " S,0,21,,1");
+    assert_eq!(
+        variables.iter().collect::<Vec<_>>(),
+        [("$ab", "2"), ("$a", "1")]
+    );
+
+    // redefining a variable replaces it
+    variables.parse("$a=3").unwrap();
+    assert_eq!(variables.substitute(" S,0,$ab,,$a"), " S,0,2,,3");
+
+    assert!(variables.parse("a=1").is_err());
+    assert!(variables.parse("$a").is_err());
+}
+
+#[test]
+fn chained_variables() {
+    // values can refer to variables defined after them
+    let mut variables = Variables::default();
+    variables.parse("$pos=$x,$y").unwrap();
+    variables.parse("$x=320").unwrap();
+    variables.parse("$y=$x").unwrap();
+
+    assert_eq!(
+        variables.substitute("Sprite,Foreground,Centre,\"a.png\",$pos"),
+        "Sprite,Foreground,Centre,\"a.png\",320,320"
+    );
+
+    // a variable referring to itself stops expanding instead of looping forever, after one
+    // pass per variable and one more
+    variables.parse("$loop=$loop!").unwrap();
+    assert_eq!(variables.substitute("$loop"), "$loop!!!!!");
+}
+
+#[test]
+fn decode_osb() {
+    let data = "\
+[Variables]
+$star=\"sb/star.png\"
+$centre=320,240
+$fade=F,0,0,1000
+
+[Events]
+//Background and Video events
+0,0,\"bg.jpg\",0,0
+//Storyboard Layer 3 (Foreground)
+Sprite,Foreground,Centre,$star,$centre
+ $fade,0,1
+";
+
+    let storyboard = Storyboard::parse(data.as_bytes()).unwrap();
+    assert_eq!(
+        storyboard,
+        parse_lines("Sprite,Foreground,Centre,\"sb/star.png\",320,240\n F,0,0,1000,0,1",)
+    );
+
+    // malformed lines are skipped when lenient, and reported with their line number: the
+    // variable, the sprite using it and the command left without a sprite
+    let data = data.replace("$centre=320,240", "centre=320,240");
+    assert!(Storyboard::parse(data.as_bytes()).is_err());
+
+    let (storyboard, diagnostics) =
+        Storyboard::parse_with(Strictness::Lenient, data.as_bytes()).unwrap();
+    assert_eq!(
+        diagnostics.iter().map(|d| d.line).collect::<Vec<_>>(),
+        [Some(3), Some(10), Some(11)]
+    );
+    assert!(storyboard.objects.is_empty());
+}
+
+#[test]
+fn beatmap_variables() {
+    let data = "osu file format v14\n\n[Variables]\n$star=\"sb/star.png\"\n\n[Events]\n\
+                Sprite,Foreground,Centre,$star,320,240\n";
+
+    let beatmap = BeatmapDecoder::all()
+        .parse(Beatmap::default(), data.as_bytes())
+        .unwrap();
+    assert_eq!(sprite_path(&beatmap.storyboard.objects[0]), "sb/star.png");
+}
+
+#[test]
+fn merge_draws_set_objects_above() {
+    let mut difficulty = parse_lines(
+        "\
+Sprite,Foreground,Centre,\"difficulty-fg.png\",320,240
+Sprite,Background,Centre,\"difficulty-bg.png\",320,240",
+    );
+    let set = parse_lines(
+        "\
+Sprite,Background,Centre,\"set-bg.png\",320,240
+Sprite,Foreground,Centre,\"set-fg.png\",320,240",
+    );
+
+    difficulty.merge(set);
+
+    assert_eq!(
+        difficulty
+            .layer(Layer::Background)
+            .map(sprite_path)
+            .collect::<Vec<_>>(),
+        ["difficulty-bg.png", "set-bg.png"]
+    );
+    assert_eq!(
+        difficulty
+            .state_at(Time::ZERO)
+            .map(|state| sprite_path(state.object))
+            .collect::<Vec<_>>(),
+        [
+            "difficulty-bg.png",
+            "set-bg.png",
+            "difficulty-fg.png",
+            "set-fg.png"
+        ]
+    );
+}
+
+/// State of the only sprite or animation of `storyboard` at `time`.
+fn state_at(storyboard: &Storyboard, time: f64) -> SpriteState<'_> {
+    let mut states = storyboard.state_at(Time(time));
+    let state = states.next().expect("no sprite nor animation");
+    assert!(states.next().is_none());
+    state
+}
+
+#[test]
+fn easing_curves() {
+    for easing in Easing::ALL {
+        // elastic easings only come close to their ends
+        assert!(easing.apply(0.0).abs() < 1e-3, "{easing:?}");
+        assert!((easing.apply(1.0) - 1.0).abs() < 1e-3, "{easing:?}");
+        assert_eq!(Easing::from_i32(easing as i32), Some(easing));
+    }
+    assert_eq!(Easing::from_i32(35), None);
+
+    assert_eq!(Easing::Linear.apply(0.25), 0.25);
+    assert_eq!(Easing::In.apply(0.5), 0.25);
+    assert_eq!(Easing::Out.apply(0.5), 0.75);
+    assert_eq!(Easing::QuadInOut.apply(0.25), 0.125);
+    assert_eq!(Easing::QuadInOut.apply(0.75), 0.875);
+    assert_eq!(Easing::CubicOut.apply(0.5), 0.875);
+    assert_eq!(Easing::QuintIn.apply(0.5), 1.0 / 32.0);
+    assert!((Easing::SineInOut.apply(0.5) - 0.5).abs() < 1e-12);
+    assert_eq!(Easing::ExpoOut.apply(0.1), 0.5);
+    // on the second bounce, 1/22 before its top at 1.5 / 2.75
+    assert!((Easing::BounceOut.apply(0.5) - (7.5625 / 484.0 + 0.75)).abs() < 1e-12);
+    // back easings overshoot
+    assert!(Easing::BackIn.apply(0.25) < 0.0);
+    assert!(Easing::BackOut.apply(0.75) > 1.0);
+
+    // progress outside of the command is clamped
+    assert_eq!(Easing::Linear.apply(-0.5), 0.0);
+    assert_eq!(Easing::QuadIn.apply(1.5), 1.0);
+}
+
+#[test]
+fn eased_values() {
+    let storyboard = parse_lines(
+        "\
+Sprite,Foreground,Centre,\"sb/star.png\",320,240
+ M,2,1000,2000,0,0,100,200
+ F,1,1000,2000,0,1
+ C,0,1000,2000,0,0,0,255,255,255
+ V,0,1000,2000,1,1,2,3
+ S,0,1000,2000,2",
+    );
+
+    // a quarter through In, three quarters through Out, halfway through the others
+    let state = state_at(&storyboard, 1500.0);
+    assert_eq!(state.position, Vec2::new(25.0, 50.0));
+    assert_eq!(state.opacity, 0.75);
+    assert_eq!(state.colour, Colour(128, 128, 128));
+    assert_eq!(state.scale, Vec2::new(3.0, 4.0));
+    assert_eq!(state.rotation, 0.0);
+
+    // the start values hold before the commands, and the end values after them
+    let state = state_at(&storyboard, 0.0);
+    assert_eq!(state.position, Vec2::new(0.0, 0.0));
+    assert_eq!(state.opacity, 0.0);
+    let state = state_at(&storyboard, 3000.0);
+    assert_eq!(state.position, Vec2::new(100.0, 200.0));
+    assert_eq!(state.colour, Colour(255, 255, 255));
+}
+
+#[test]
+fn untouched_properties() {
+    let storyboard = parse_lines(
+        "\
+Sprite,Foreground,Centre,\"sb/star.png\",320,240
+ MX,0,0,1000,0,100",
+    );
+
+    let state = state_at(&storyboard, 500.0);
+    assert_eq!(state.position, Vec2::new(50.0, 240.0));
+    assert_eq!(state.scale, Vec2::new(1.0, 1.0));
+    assert_eq!(state.colour, Colour(255, 255, 255));
+    assert_eq!(state.opacity, 1.0);
+    assert_eq!(state.frame, None);
+}
+
+#[test]
+fn loop_expansion() {
+    // iterations of 1000ms starting at 1000, 2000 and 3000
+    let storyboard = parse_lines(
+        "\
+Sprite,Foreground,Centre,\"sb/star.png\",320,240
+ L,1000,3
+  F,0,0,500,0,1
+  F,0,500,1000,1,0",
+    );
+
+    for (time, opacity) in [
+        (1250.0, 0.5),
+        (2250.0, 0.5),
+        (2500.0, 1.0),
+        (3750.0, 0.5),
+        (4000.0, 0.0),
+    ] {
+        assert_eq!(state_at(&storyboard, time).opacity, opacity, "{time}");
+    }
+
+    // iterations last as long as the nested commands, even if they start late
+    let storyboard = parse_lines(
+        "\
+Sprite,Foreground,Centre,\"sb/star.png\",320,240
+ L,1000,2
+  MX,0,200,700,0,100",
+    );
+
+    for (time, x) in [
+        (1100.0, 0.0),
+        (1450.0, 50.0),
+        (1950.0, 50.0),
+        (2500.0, 100.0),
+    ] {
+        assert_eq!(state_at(&storyboard, time).position.x, x, "{time}");
+    }
+    assert!(!state_at(&storyboard, 1199.0).visible);
+    assert!(state_at(&storyboard, 2200.0).visible);
+    assert!(!state_at(&storyboard, 2201.0).visible);
+}
+
+#[test]
+fn lifetime_edges() {
+    let storyboard = parse_lines(
+        "\
+Sprite,Foreground,Centre,\"sb/star.png\",320,240
+ F,0,1000,2000,1",
+    );
+
+    assert!(!state_at(&storyboard, 999.0).visible);
+    assert!(state_at(&storyboard, 1000.0).visible);
+    assert!(state_at(&storyboard, 2000.0).visible);
+    assert!(!state_at(&storyboard, 2001.0).visible);
+
+    // fully transparent objects are not drawn
+    let storyboard = parse_lines(
+        "\
+Sprite,Foreground,Centre,\"sb/star.png\",320,240
+ F,0,1000,2000,1,0",
+    );
+    assert!(state_at(&storyboard, 1999.0).visible);
+    assert!(!state_at(&storyboard, 2000.0).visible);
+
+    // triggers depend on gameplay, so they do not make the object alive
+    let storyboard = parse_lines(
+        "\
+Sprite,Foreground,Centre,\"sb/star.png\",320,240
+ T,HitSoundClap,0,10000
+  F,0,0,100,1",
+    );
+    assert!(!state_at(&storyboard, 50.0).visible);
+}
+
+#[test]
+fn parameters() {
+    let storyboard = parse_lines(
+        "\
+Sprite,Foreground,Centre,\"sb/star.png\",320,240
+ F,0,0,3000,1
+ P,0,1000,,H
+ P,0,1000,2000,V",
+    );
+
+    let state = state_at(&storyboard, 999.0);
+    assert!(!state.flip_horizontal && !state.flip_vertical);
+
+    let state = state_at(&storyboard, 1000.0);
+    assert!(state.flip_horizontal && state.flip_vertical);
+    assert!(!state.additive);
+
+    // a zero-length command stays applied until the end of the lifetime
+    let state = state_at(&storyboard, 2001.0);
+    assert!(state.flip_horizontal && !state.flip_vertical);
+    assert!(state_at(&storyboard, 3000.0).flip_horizontal);
+}
+
+#[test]
+fn animation_frames() {
+    let lines = "\
+Animation,Foreground,Centre,\"sb/bird.png\",320,240,4,100,LoopForever
+ F,0,1000,2000,1";
+
+    let storyboard = parse_lines(lines);
+    for (time, frame) in [
+        (0.0, 0),
+        (1000.0, 0),
+        (1099.0, 0),
+        (1100.0, 1),
+        (1399.0, 3),
+        (1450.0, 0),
+    ] {
+        assert_eq!(state_at(&storyboard, time).frame, Some(frame), "{time}");
+    }
+
+    let storyboard = parse_lines(&lines.replace("LoopForever", "LoopOnce"));
+    assert_eq!(state_at(&storyboard, 1450.0).frame, Some(3));
+    assert_eq!(state_at(&storyboard, 1950.0).frame, Some(3));
+
+    let StoryboardObject::Animation(animation) = &storyboard.objects[0] else {
+        panic!("expected animation, got {:?}", storyboard.objects[0]);
+    };
+    assert_eq!(animation.frame_filepath(2).to_str(), Some("sb/bird2.png"));
+}
+
+#[test]
+fn samples_have_no_state() {
+    let storyboard = parse_lines("Sample,500,0,\"sb/hit.wav\",70");
+    assert_eq!(storyboard.state_at(Time(500.0)).count(), 0);
+}
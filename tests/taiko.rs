@@ -0,0 +1,430 @@
+mod common;
+
+use common::BeatmapBuilder;
+use osulib::{
+    file::beatmap::{Beatmap, BeatmapDecoder},
+    mods::Mods,
+    ruleset::taiko::{
+        convert::convert,
+        difficulty::{TaikoDifficultyAttributes, TaikoDifficultyCalculator, TaikoStrains},
+        object::{TaikoObject, TaikoObjectKind},
+        performance::{TaikoPerformanceCalculator, TaikoScore},
+    },
+    time::Time,
+};
+
+/// Decodes a beatmap at OD 5 where sliders travel 100 osu! pixels per 500ms.
+fn decode(mode: u8, hit_objects: &[&str]) -> Beatmap {
+    BeatmapBuilder::new()
+        .mode(mode)
+        .difficulty("OverallDifficulty", 5)
+        .hit_objects(hit_objects)
+        .build()
+}
+
+fn times(objects: &[TaikoObject]) -> Vec<(f64, bool)> {
+    objects.iter().map(|o| (o.time.0, o.big)).collect()
+}
+
+#[test]
+fn convert_merges_simultaneous_objects() {
+    // the slider is split into hits at 1000 and 1250, the second one at the same time as a
+    // circle further in the beatmap
+    let beatmap = decode(
+        0,
+        &[
+            "0,0,1000,2,0,L|50:0,1,50",
+            "0,0,1100,1,2,0:0:0:0:",
+            "0,0,1250,1,0,0:0:0:0:",
+            "0,0,1500,1,0,0:0:0:0:",
+            "0,0,1500,12,0,2000,0:0:0:0:",
+        ],
+    );
+    let objects = convert(&beatmap).unwrap();
+
+    assert_eq!(
+        times(&objects),
+        [
+            (1000.0, false),
+            (1100.0, false),
+            (1250.0, true),
+            (1500.0, true)
+        ]
+    );
+    assert_eq!(objects[1].kind, TaikoObjectKind::Kat);
+    assert_eq!(objects[3].kind, TaikoObjectKind::Don);
+}
+
+#[test]
+fn convert_swell_is_never_big() {
+    let beatmap = decode(0, &["0,0,1000,12,0,2000,0:0:0:0:", "0,0,1000,1,0,0:0:0:0:"]);
+    let objects = convert(&beatmap).unwrap();
+
+    assert_eq!(objects.len(), 1);
+    assert!(objects[0].is_swell());
+    assert!(!objects[0].big);
+}
+
+#[test]
+fn convert_uses_the_declared_slider_length() {
+    // osu! does not extend a path ending on a repeated point, but still uses the declared 250
+    // pixels for the drumroll: 250 · 1.4 pixels at 140 pixels per beat
+    let beatmap = decode(0, &["0,0,1000,2,0,L|100:0|100:0,1,250"]);
+    let objects = convert(&beatmap).unwrap();
+
+    assert_eq!(objects.len(), 1);
+    assert_eq!(objects[0].end_time, Time(2250.0));
+    assert_eq!(
+        objects[0].kind,
+        TaikoObjectKind::DrumRoll { tick_count: 11 }
+    );
+
+    // twice as long with a repeat
+    let beatmap = decode(0, &["0,0,1000,2,0,L|100:0|100:0,2,250"]);
+    assert_eq!(convert(&beatmap).unwrap()[0].end_time, Time(3500.0));
+}
+
+#[test]
+fn convert_uses_the_legacy_bpm_multiplier() {
+    // osu!stable does not clamp a beat length of -2000 to 0.1x: 100 · 1.4 pixels at 140 pixels
+    // per beat of 500 · 20ms
+    let beatmap = BeatmapBuilder::new()
+        .timing_point("0,-2000,4,1,0,100,0,0")
+        .hit_objects(&["0,0,1000,2,0,L|100:0,1,100"])
+        .build();
+    let objects = convert(&beatmap).unwrap();
+
+    assert!(objects[0].is_drum_roll());
+    assert_eq!(objects[0].end_time, Time(11000.0));
+
+    // before v8, the hits of a split slider are a beat of 10000ms apart, over 15000ms
+    let beatmap = BeatmapBuilder::new()
+        .version(7)
+        .timing_point("0,-2000,4,1,0,100,0,0")
+        .hit_objects(&["0,0,1000,2,0,L|150:0,1,150"])
+        .build();
+    let objects = convert(&beatmap).unwrap();
+
+    assert_eq!(times(&objects), [(1000.0, false), (11000.0, false)]);
+}
+
+#[test]
+fn native_drum_roll_duration() {
+    // the same timing as converts: the declared length, not that of the path
+    let beatmap = decode(1, &["0,0,1000,2,0,L|100:0|100:0,1,250"]);
+    assert_eq!(
+        TaikoObject::from_beatmap(&beatmap)[0].end_time,
+        Time(2250.0)
+    );
+
+    // and the legacy BPM multiplier of 20 rather than a slider velocity of 0.1
+    let beatmap = BeatmapBuilder::new()
+        .mode(1)
+        .timing_point("0,-2000,4,1,0,100,0,0")
+        .hit_objects(&["0,0,1000,2,0,L|100:0,1,100"])
+        .build();
+    assert_eq!(
+        TaikoObject::from_beatmap(&beatmap)[0].end_time,
+        Time(11000.0)
+    );
+}
+
+#[test]
+fn native_merges_simultaneous_objects() {
+    let beatmap = decode(
+        1,
+        &[
+            "0,0,1000,2,0,L|400:0,1,400",
+            "0,0,1000,1,8,0:0:0:0:",
+            "0,0,3000,1,0,0:0:0:0:",
+            "0,0,4000,12,0,5000,0:0:0:0:",
+            "0,0,4000,1,0,0:0:0:0:",
+        ],
+    );
+    let objects = TaikoObject::from_beatmap(&beatmap);
+
+    assert_eq!(
+        times(&objects),
+        [(1000.0, true), (3000.0, false), (4000.0, false)]
+    );
+    assert!(objects[0].is_drum_roll());
+    assert!(objects[2].is_swell());
+}
+
+// The difficulty and performance below are checked against the formulas of osu!, written out
+// independently of the crate: by hand on a stream whose strains have a closed form, and from
+// the strain peaks the crate reports on real beatmaps.
+
+const DECAY_WEIGHT: f64 = 0.9;
+const DIFFICULTY_MULTIPLIER: f64 = 1.35;
+const COLOUR_MULTIPLIER: f64 = 0.375 * 0.0625;
+const RHYTHM_MULTIPLIER: f64 = 0.2 * 0.0625;
+const STAMINA_MULTIPLIER: f64 = 0.375 * 0.0625;
+
+fn fixture(bytes: &[u8]) -> Beatmap {
+    BeatmapDecoder::all()
+        .parse(Beatmap::default(), bytes)
+        .unwrap()
+}
+
+#[track_caller]
+fn assert_close(actual: f64, expected: f64) {
+    assert!(
+        (actual - expected).abs() <= expected.abs() * 1e-9,
+        "expected {expected}, got {actual}"
+    );
+}
+
+fn weighted_sum(mut peaks: Vec<f64>) -> f64 {
+    peaks.sort_by(|a, b| b.total_cmp(a));
+    peaks
+        .iter()
+        .enumerate()
+        .map(|(i, peak)| peak * DECAY_WEIGHT.powi(i as i32))
+        .sum()
+}
+
+/// Combines the strain peaks of each section, colour and stamina first.
+fn peak(strains: &TaikoStrains) -> f64 {
+    let peaks = (0..strains.colour.len())
+        .map(|i| {
+            let colour = strains.colour[i] * COLOUR_MULTIPLIER;
+            let stamina = strains.stamina[i] * STAMINA_MULTIPLIER;
+            let rhythm = strains.rhythm[i] * RHYTHM_MULTIPLIER;
+
+            let peak = (colour.powf(1.5) + stamina.powf(1.5)).powf(1.0 / 1.5);
+            (peak.powi(2) + rhythm.powi(2)).sqrt()
+        })
+        .filter(|&peak| peak > 0.0)
+        .collect();
+
+    weighted_sum(peaks) * DIFFICULTY_MULTIPLIER
+}
+
+/// Star rating from the combined peaks.
+fn stars(attributes: &TaikoDifficultyAttributes) -> f64 {
+    let stars = 10.43 * (attributes.peak * 1.4 / 8.0 + 1.0).ln();
+    if attributes.is_convert {
+        stars * 0.925
+    } else {
+        stars
+    }
+}
+
+/// Checks every rating against the strain peaks the crate reports.
+#[track_caller]
+fn assert_ratings(attributes: &TaikoDifficultyAttributes, strains: &TaikoStrains) {
+    let rating = |peaks: &[f64], multiplier: f64| {
+        weighted_sum(peaks.to_vec()) * multiplier * DIFFICULTY_MULTIPLIER
+    };
+
+    assert_close(
+        attributes.colour,
+        rating(&strains.colour, COLOUR_MULTIPLIER),
+    );
+    assert_close(
+        attributes.rhythm,
+        rating(&strains.rhythm, RHYTHM_MULTIPLIER),
+    );
+    assert_close(
+        attributes.stamina,
+        rating(&strains.stamina, STAMINA_MULTIPLIER),
+    );
+    assert_close(attributes.peak, peak(strains));
+    assert_close(attributes.stars, stars(attributes));
+}
+
+/// Performance of a score without mods, or with HR.
+fn performance(attributes: &TaikoDifficultyAttributes, score: &TaikoScore) -> f64 {
+    let total_hits = (score.n300 + score.n100 + score.n_miss) as f64;
+    let accuracy = (score.n300 as f64 + 0.5 * score.n100 as f64) / total_hits;
+    let misses = (1000.0 / (score.n300 + score.n100) as f64).max(1.0) * score.n_miss as f64;
+    let hard_rock = if score.mods.contains(Mods::HARD_ROCK) {
+        1.05
+    } else {
+        1.0
+    };
+
+    let strain = (5.0 * (attributes.stars / 0.115) - 4.0).powf(2.25) / 1150.0
+        * (1.0 + 0.1 * (total_hits / 1500.0).min(1.0))
+        * 0.986f64.powf(misses)
+        * hard_rock
+        * accuracy.powi(2);
+    let accuracy = (60.0 / attributes.great_hit_window).powf(1.1)
+        * accuracy.powi(8)
+        * attributes.stars.powf(0.4)
+        * 27.0
+        * (total_hits / 1500.0).powf(0.3).min(1.15);
+
+    (strain.powf(1.1) + accuracy.powf(1.1)).powf(1.0 / 1.1) * 1.13
+}
+
+#[test]
+fn stream_difficulty() {
+    // dons every 100ms from 0 to 4000ms
+    let hit_objects = (0..=40)
+        .map(|i| format!("256,192,{},1,0,0:0:0:0:", i * 100))
+        .collect::<Vec<_>>();
+    let beatmap = BeatmapBuilder::new()
+        .mode(1)
+        .hit_objects(&hit_objects)
+        .build();
+    let calculator = TaikoDifficultyCalculator::new(&beatmap);
+    let attributes = calculator.calculate();
+
+    // the first two objects have no difficulty object, the others start at 200ms and are in
+    // sections of 400ms ending on a note
+    let times = (2..=40).map(|i| i as f64 * 100.0).collect::<Vec<_>>();
+
+    // no rhythm change
+    assert_eq!(attributes.rhythm, 0.0);
+
+    // stamina: from the third difficulty object on, each note is hit 200ms after the previous
+    // one on the same key, for 0.5 + 30 / 200 times 1.1, and the strain decays by 0.4 per second
+    let decay = 0.4f64.powf(0.1);
+    let mut strain = 0.0;
+    let stamina = times
+        .iter()
+        .enumerate()
+        .map(|(j, _)| {
+            strain *= decay;
+            if j >= 2 {
+                strain += (0.5 + 30.0 / 200.0) * 1.1;
+            }
+            strain
+        })
+        .collect::<Vec<_>>();
+    // the peak of a section is the strain of its last note, at 400ms, 800ms...
+    let stamina_peaks = (0..10).map(|k| stamina[4 * k + 2]).collect::<Vec<_>>();
+
+    // colour: a single streak, never repeated, only counts on the first note, then decays by
+    // 0.8 per second
+    let sigmoid = |x: f64| (std::f64::consts::E * -(x - 2.0) / 2.0).tanh() * 0.5 + 0.5;
+    let repeating = 2.0 * (1.0 - sigmoid(17.0));
+    let first =
+        sigmoid(0.0) * sigmoid(0.0) * repeating * 0.5 + sigmoid(0.0) * repeating + repeating;
+    // every section after the first starts with the strain of the note on its boundary
+    let colour_peaks = (0..10)
+        .map(|k| first * 0.12 * 0.8f64.powf((400.0 * k as f64 - 200.0).max(0.0) / 1000.0))
+        .collect::<Vec<_>>();
+
+    let strains = calculator.strains();
+    for (actual, expected) in [
+        (&strains.stamina, &stamina_peaks),
+        (&strains.colour, &colour_peaks),
+    ] {
+        assert_eq!(actual.len(), expected.len());
+        for (a, e) in actual.iter().zip(expected) {
+            assert_close(*a, *e);
+        }
+    }
+
+    assert_close(
+        attributes.stamina,
+        weighted_sum(stamina_peaks) * STAMINA_MULTIPLIER * DIFFICULTY_MULTIPLIER,
+    );
+    assert_ratings(&attributes, &strains);
+    assert_eq!(attributes.max_combo, 41);
+
+    let ss = TaikoScore {
+        n300: 41,
+        ..Default::default()
+    };
+    assert_close(ss.performance(&beatmap).pp, performance(&attributes, &ss));
+}
+
+#[test]
+fn native_difficulty() {
+    let beatmap = fixture(include_bytes!("fixtures/taiko.osu"));
+    let objects = TaikoObject::from_beatmap(&beatmap);
+    assert_eq!(objects.iter().filter(|o| o.is_drum_roll()).count(), 1);
+    assert_eq!(objects.iter().filter(|o| o.is_swell()).count(), 1);
+
+    let calculator = TaikoDifficultyCalculator::new(&beatmap);
+    let attributes = calculator.calculate();
+    assert!(!attributes.is_convert);
+    assert_eq!(attributes.max_combo, 344);
+    // OD 6
+    assert_eq!(attributes.great_hit_window, 32.0);
+    assert_ratings(&attributes, &calculator.strains());
+
+    let calculator = calculator.mods(Mods::HARD_ROCK | Mods::DOUBLE_TIME);
+    let with_mods = calculator.calculate();
+    assert_ratings(&with_mods, &calculator.strains());
+    // OD 8.4 in single precision, 35 - 15 · 3.4 / 5 = 24.8ms shortened by the clock rate
+    assert!((with_mods.great_hit_window - 24.8 / 1.5).abs() < 1e-5);
+    assert!(with_mods.stars > attributes.stars);
+}
+
+#[test]
+fn native_performance() {
+    let beatmap = fixture(include_bytes!("fixtures/taiko.osu"));
+    let attributes = TaikoDifficultyCalculator::new(&beatmap).calculate();
+
+    let ss = TaikoScore {
+        n300: 344,
+        ..Default::default()
+    };
+    assert_close(ss.performance(&beatmap).pp, performance(&attributes, &ss));
+
+    // misses weigh more on short beatmaps, as if there were 1000 hits
+    let score = TaikoScore {
+        n300: 329,
+        n100: 10,
+        n_miss: 5,
+        ..Default::default()
+    };
+    let result = score.performance(&beatmap);
+    assert_close(result.effective_miss_count, 1000.0 / 339.0 * 5.0);
+    assert_close(result.pp, performance(&attributes, &score));
+
+    let score = TaikoScore {
+        mods: Mods::HARD_ROCK | Mods::DOUBLE_TIME,
+        ..score
+    };
+    let result = score.performance(&beatmap);
+    assert_close(result.pp, performance(&result.difficulty, &score));
+}
+
+#[test]
+fn convert_difficulty() {
+    let beatmap = fixture(include_bytes!("../benches/beatmap.osu"));
+    let objects = TaikoObject::from_beatmap(&beatmap);
+    assert_eq!(objects.len(), 3603);
+    assert_eq!(objects.iter().filter(|o| o.is_drum_roll()).count(), 19);
+    assert_eq!(objects.iter().filter(|o| o.is_swell()).count(), 10);
+
+    let calculator = TaikoDifficultyCalculator::new(&beatmap);
+    let attributes = calculator.calculate();
+    assert!(attributes.is_convert);
+    assert_eq!(attributes.max_combo, 3574);
+    // OD 9
+    assert_eq!(attributes.great_hit_window, 23.0);
+    // not penalised for having few colour changes
+    assert!(attributes.colour >= 2.0);
+    assert_ratings(&attributes, &calculator.strains());
+}
+
+#[test]
+fn convert_performance() {
+    let beatmap = fixture(include_bytes!("../benches/beatmap.osu"));
+    let attributes = TaikoDifficultyCalculator::new(&beatmap).calculate();
+
+    let ss = TaikoScore {
+        n300: 3574,
+        ..Default::default()
+    };
+    let result = TaikoPerformanceCalculator::new(&attributes).calculate(&ss);
+    assert_close(result.pp, performance(&attributes, &ss));
+
+    // long enough for misses to count as they are
+    let score = TaikoScore {
+        n300: 3559,
+        n100: 10,
+        n_miss: 5,
+        ..Default::default()
+    };
+    let result = TaikoPerformanceCalculator::new(&attributes).calculate(&score);
+    assert_eq!(result.effective_miss_count, 5.0);
+    assert_close(result.pp, performance(&attributes, &score));
+}